@@ -0,0 +1,102 @@
+//! Benchmarks for directory traversal.
+
+use affs_read::{AffsReader, AffsReaderVar, BlockDevice, hash_name, normal_sum};
+
+#[global_allocator]
+static ALLOC: divan::AllocProfiler = divan::AllocProfiler::system();
+
+fn main() {
+    divan::main();
+}
+
+/// In-memory disk image.
+struct MemDevice {
+    blocks: Vec<[u8; 512]>,
+}
+
+impl BlockDevice for MemDevice {
+    fn read_block(&self, block: u32, buf: &mut [u8; 512]) -> Result<(), ()> {
+        *buf = *self.blocks.get(block as usize).ok_or(())?;
+        Ok(())
+    }
+}
+
+fn write_u32(buf: &mut [u8; 512], offset: usize, val: u32) {
+    buf[offset..offset + 4].copy_from_slice(&val.to_be_bytes());
+}
+
+fn set_checksum(buf: &mut [u8; 512]) {
+    write_u32(buf, 20, 0);
+    let sum = normal_sum(buf, 20);
+    write_u32(buf, 20, sum);
+}
+
+fn set_name(buf: &mut [u8; 512], name: &[u8]) {
+    buf[0x1B0] = name.len() as u8;
+    buf[0x1B1..0x1B1 + name.len()].copy_from_slice(name);
+}
+
+/// Build an FFS floppy whose root directory holds `count` empty files.
+fn create_disk(count: u32) -> MemDevice {
+    let mut blocks = vec![[0u8; 512]; 1760];
+    blocks[0][0..4].copy_from_slice(b"DOS\x01");
+    write_u32(&mut blocks[0], 8, 880);
+
+    let mut root = [0u8; 512];
+    write_u32(&mut root, 0, 2); // T_HEADER
+    write_u32(&mut root, 12, 72);
+    write_u32(&mut root, 508, 1); // ST_ROOT
+    set_name(&mut root, b"Bench");
+
+    for i in 0..count {
+        let block = 882 + i;
+        let name = format!("file{i:04}");
+        let hash = hash_name(name.as_bytes(), false);
+
+        let mut header = [0u8; 512];
+        write_u32(&mut header, 0, 2); // T_HEADER
+        write_u32(&mut header, 4, block);
+        set_name(&mut header, name.as_bytes());
+        // Prepend to the hash chain
+        let head = u32::from_be_bytes(root[24 + hash * 4..28 + hash * 4].try_into().unwrap());
+        write_u32(&mut header, 0x1F0, head);
+        write_u32(&mut header, 0x1F4, 880);
+        write_u32(&mut header, 508, (-3i32) as u32); // ST_FILE
+        set_checksum(&mut header);
+        blocks[block as usize] = header;
+
+        write_u32(&mut root, 24 + hash * 4, block);
+    }
+
+    set_checksum(&mut root);
+    blocks[880] = root;
+    MemDevice { blocks }
+}
+
+#[divan::bench(args = [16, 256])]
+fn bench_count_entries(bencher: divan::Bencher, count: u32) {
+    let device = create_disk(count);
+    let reader = AffsReader::new(&device).unwrap();
+    bencher.bench_local(|| divan::black_box(reader.count_entries(880).unwrap()));
+}
+
+#[divan::bench(args = [16, 256])]
+fn bench_read_dir_count(bencher: divan::Bencher, count: u32) {
+    let device = create_disk(count);
+    let reader = AffsReader::new(&device).unwrap();
+    bencher.bench_local(|| divan::black_box(reader.read_dir(880).unwrap().count()));
+}
+
+#[divan::bench(args = [16, 256])]
+fn bench_read_dir_collect(bencher: divan::Bencher, count: u32) {
+    let device = create_disk(count);
+    let reader = AffsReader::new(&device).unwrap();
+    bencher.bench_local(|| divan::black_box(reader.read_dir(880).unwrap().collect::<Vec<_>>()));
+}
+
+#[divan::bench(args = [16, 256])]
+fn bench_var_read_dir_count(bencher: divan::Bencher, count: u32) {
+    let device = create_disk(count);
+    let reader = AffsReaderVar::new(&device, 1760).unwrap();
+    bencher.bench_local(|| divan::black_box(reader.read_dir(880).unwrap().count()));
+}
@@ -0,0 +1,233 @@
+//! Benchmarks for streaming file reads.
+
+use affs_read::{AffsReader, BlockDevice, BufReader, normal_sum};
+
+fn main() {
+    divan::main();
+}
+
+/// In-memory disk image.
+struct MemDevice {
+    blocks: Vec<[u8; 512]>,
+}
+
+impl BlockDevice for MemDevice {
+    fn read_block(&self, block: u32, buf: &mut [u8; 512]) -> Result<(), ()> {
+        *buf = *self.blocks.get(block as usize).ok_or(())?;
+        Ok(())
+    }
+}
+
+fn write_u32(buf: &mut [u8; 512], offset: usize, val: u32) {
+    buf[offset..offset + 4].copy_from_slice(&val.to_be_bytes());
+}
+
+fn set_checksum(buf: &mut [u8; 512]) {
+    write_u32(buf, 20, 0);
+    let sum = normal_sum(buf, 20);
+    write_u32(buf, 20, sum);
+}
+
+/// Number of data blocks in the benchmark file.
+const FILE_BLOCKS: u32 = 64;
+
+/// Build an OFS floppy with a single file of `FILE_BLOCKS` full data blocks at block 882.
+fn create_ofs_disk() -> MemDevice {
+    let mut blocks = vec![[0u8; 512]; 1760];
+    blocks[0][0..4].copy_from_slice(b"DOS\x00");
+    write_u32(&mut blocks[0], 8, 880);
+
+    let mut root = [0u8; 512];
+    write_u32(&mut root, 0, 2); // T_HEADER
+    write_u32(&mut root, 12, 72);
+    write_u32(&mut root, 508, 1); // ST_ROOT
+    set_checksum(&mut root);
+    blocks[880] = root;
+
+    let first = 890;
+    let mut header = [0u8; 512];
+    write_u32(&mut header, 0, 2); // T_HEADER
+    write_u32(&mut header, 4, 882);
+    write_u32(&mut header, 16, first);
+    write_u32(&mut header, 0x144, FILE_BLOCKS * 488);
+    header[0x1B0] = 4;
+    header[0x1B1..0x1B5].copy_from_slice(b"file");
+    write_u32(&mut header, 0x1F4, 880);
+    write_u32(&mut header, 508, (-3i32) as u32); // ST_FILE
+    set_checksum(&mut header);
+    blocks[882] = header;
+
+    for i in 0..FILE_BLOCKS {
+        let block = first + i;
+        let mut data = [0u8; 512];
+        write_u32(&mut data, 0, 8); // T_DATA
+        write_u32(&mut data, 4, 882);
+        write_u32(&mut data, 8, i + 1);
+        write_u32(&mut data, 12, 488);
+        write_u32(
+            &mut data,
+            16,
+            if i + 1 < FILE_BLOCKS { block + 1 } else { 0 },
+        );
+        data[24..].fill(i as u8);
+        set_checksum(&mut data);
+        blocks[block as usize] = data;
+    }
+
+    MemDevice { blocks }
+}
+
+/// Read the whole OFS file in chunks of `chunk` bytes.
+#[divan::bench(args = [16, 128, 4096])]
+fn bench_ofs_read(bencher: divan::Bencher, chunk: usize) {
+    let device = create_ofs_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let mut buf = vec![0u8; chunk];
+
+    bencher
+        .counter(divan::counter::BytesCount::new(FILE_BLOCKS as usize * 488))
+        .bench_local(|| {
+            let mut file = reader.read_file(882).unwrap();
+            let mut total = 0;
+            loop {
+                let n = file.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                total += n;
+            }
+            divan::black_box(total)
+        });
+}
+
+/// Read the whole OFS file one byte at a time, without buffering.
+#[divan::bench]
+fn bench_ofs_read_bytes_unbuffered(bencher: divan::Bencher) {
+    let device = create_ofs_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    bencher
+        .counter(divan::counter::BytesCount::new(FILE_BLOCKS as usize * 488))
+        .bench_local(|| {
+            let mut file = reader.read_file(882).unwrap();
+            let mut byte = [0u8; 1];
+            let mut total = 0;
+            while file.read(&mut byte).unwrap() == 1 {
+                total += 1;
+            }
+            divan::black_box(total)
+        });
+}
+
+/// Read the whole OFS file one byte at a time through a `BufReader`.
+#[divan::bench]
+fn bench_ofs_read_bytes_buffered(bencher: divan::Bencher) {
+    let device = create_ofs_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    bencher
+        .counter(divan::counter::BytesCount::new(FILE_BLOCKS as usize * 488))
+        .bench_local(|| {
+            let mut file: BufReader<'_, _> = BufReader::new(reader.read_file(882).unwrap());
+            let mut byte = [0u8; 1];
+            let mut total = 0;
+            while file.read(&mut byte).unwrap() == 1 {
+                total += 1;
+            }
+            divan::black_box(total)
+        });
+}
+
+/// Number of extension blocks in the multi-extension FFS file.
+const EXT_COUNT: u32 = 20;
+
+/// Number of data blocks in the multi-extension FFS file.
+const FFS_FILE_BLOCKS: u32 = 72 * (EXT_COUNT + 1);
+
+/// Build an FFS disk with a single file of `FFS_FILE_BLOCKS` data blocks at
+/// block 882, chained through `EXT_COUNT` extension blocks at 883.
+fn create_multi_ext_ffs_disk() -> MemDevice {
+    let mut blocks = vec![[0u8; 512]; 4096];
+    blocks[0][0..4].copy_from_slice(b"DOS\x01");
+    write_u32(&mut blocks[0], 8, 880);
+
+    let mut root = [0u8; 512];
+    write_u32(&mut root, 0, 2); // T_HEADER
+    write_u32(&mut root, 12, 72);
+    write_u32(&mut root, 508, 1); // ST_ROOT
+    set_checksum(&mut root);
+    blocks[880] = root;
+
+    let first = 1000;
+    let table = |buf: &mut [u8; 512], start: u32| {
+        for i in 0..72 {
+            // Pointers are stored in reverse order
+            write_u32(buf, 24 + (71 - i as usize) * 4, first + start + i);
+        }
+        write_u32(buf, 8, 72);
+    };
+
+    let mut header = [0u8; 512];
+    write_u32(&mut header, 0, 2); // T_HEADER
+    write_u32(&mut header, 4, 882);
+    write_u32(&mut header, 16, first);
+    table(&mut header, 0);
+    write_u32(&mut header, 0x144, FFS_FILE_BLOCKS * 512);
+    header[0x1B0] = 4;
+    header[0x1B1..0x1B5].copy_from_slice(b"file");
+    write_u32(&mut header, 0x1F4, 880);
+    write_u32(&mut header, 0x1F8, 883);
+    write_u32(&mut header, 508, (-3i32) as u32); // ST_FILE
+    set_checksum(&mut header);
+    blocks[882] = header;
+
+    for ext in 0..EXT_COUNT {
+        let mut buf = [0u8; 512];
+        write_u32(&mut buf, 0, 16); // T_LIST
+        write_u32(&mut buf, 4, 883 + ext);
+        table(&mut buf, 72 * (ext + 1));
+        write_u32(&mut buf, 0x1F4, 882);
+        if ext + 1 < EXT_COUNT {
+            write_u32(&mut buf, 0x1F8, 884 + ext);
+        }
+        write_u32(&mut buf, 508, (-3i32) as u32); // ST_FILE
+        set_checksum(&mut buf);
+        blocks[(883 + ext) as usize] = buf;
+    }
+
+    for i in 0..FFS_FILE_BLOCKS {
+        blocks[(first + i) as usize].fill(i as u8);
+    }
+
+    MemDevice { blocks }
+}
+
+/// Seek back and forth within a file spanning many extension blocks.
+///
+/// A fresh reader walks the extension chain on every backward seek; once
+/// the chain has been read, the reader jumps straight to the extension
+/// holding the target.
+#[divan::bench(args = ["cold", "warm"])]
+fn bench_ffs_seek_multi_ext(bencher: divan::Bencher, state: &str) {
+    let device = create_multi_ext_ffs_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let end = FFS_FILE_BLOCKS * 512;
+    let mut warm = reader.read_file(882).unwrap();
+    warm.seek(end - 1).unwrap();
+
+    bencher.bench_local(|| {
+        let mut cold;
+        let file = if state == "cold" {
+            cold = reader.read_file(882).unwrap();
+            &mut cold
+        } else {
+            &mut warm
+        };
+        let mut byte = [0u8; 1];
+        for pos in [end - 1, 100, end / 2 + 1, end - 513] {
+            file.seek(pos).unwrap();
+            file.read(&mut byte).unwrap();
+        }
+        divan::black_box(byte)
+    });
+}
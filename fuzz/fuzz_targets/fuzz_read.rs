@@ -1,44 +1,15 @@
 #![no_main]
 
-use affs_read::{AffsReader, BlockDevice};
+use affs_read::{AffsReader, SliceDevice};
 use libfuzzer_sys::fuzz_target;
 
-/// A mock block device backed by fuzzed data.
-struct FuzzDevice<'a> {
-    data: &'a [u8],
-}
-
-impl<'a> FuzzDevice<'a> {
-    fn new(data: &'a [u8]) -> Self {
-        Self { data }
-    }
-}
-
-impl BlockDevice for FuzzDevice<'_> {
-    fn read_block(&self, block: u32, buf: &mut [u8; 512]) -> Result<(), ()> {
-        let offset = (block as usize) * 512;
-        if offset + 512 <= self.data.len() {
-            buf.copy_from_slice(&self.data[offset..offset + 512]);
-            Ok(())
-        } else if offset < self.data.len() {
-            // Partial block - fill with zeros
-            buf.fill(0);
-            let available = self.data.len() - offset;
-            buf[..available].copy_from_slice(&self.data[offset..]);
-            Ok(())
-        } else {
-            Err(())
-        }
-    }
-}
-
 fuzz_target!(|data: &[u8]| {
     // Need at least 2 blocks (boot block) + 1 block (root) = 1536 bytes minimum
     if data.len() < 1536 {
         return;
     }
 
-    let device = FuzzDevice::new(data);
+    let device = SliceDevice::new(data);
     let num_blocks = (data.len() / 512) as u32;
 
     // Try to create the reader
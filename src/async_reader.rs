@@ -0,0 +1,493 @@
+//! Async reader interface.
+//!
+//! Mirrors the core of [`AffsReader`](crate::AffsReader) for devices whose
+//! reads are asynchronous. Block parsing is shared with the sync reader;
+//! only the device I/O is awaited.
+
+use crate::block::{
+    BootBlock, EntryBlock, FileExtBlock, OfsDataBlock, RootBlock, hash_name, names_equal,
+};
+use crate::constants::*;
+use crate::dir::{DirEntry, is_rejected_entry, keeps_chain, parse_chain_entry};
+use crate::error::{AffsError, Result};
+use crate::reader::{LinkHops, Options, array_ref_mut, is_uninitialized_root};
+use crate::types::{FsFlags, FsType};
+
+/// Async block device trait for reading blocks from storage.
+///
+/// The async counterpart of [`BlockDevice`](crate::BlockDevice). The
+/// returned futures carry no `Send` bound, so they suit single-threaded
+/// and embedded executors.
+#[allow(async_fn_in_trait)]
+pub trait AsyncBlockDevice {
+    /// Read a single 512-byte block.
+    ///
+    /// # Arguments
+    /// * `block` - Block number to read
+    /// * `buf` - Buffer to read into (must be exactly 512 bytes)
+    ///
+    /// # Returns
+    /// `Ok(())` on success, `Err(())` on failure.
+    #[allow(clippy::result_unit_err)]
+    async fn read_block(&self, block: u32, buf: &mut [u8; 512]) -> core::result::Result<(), ()>;
+}
+
+/// Read a block, mapping device failures to `AffsError::BlockReadError`.
+async fn read<D: AsyncBlockDevice>(
+    device: &D,
+    block: u32,
+    buf: &mut [u8; BLOCK_SIZE],
+) -> Result<()> {
+    device
+        .read_block(block, buf)
+        .await
+        .map_err(|()| AffsError::BlockReadError)
+}
+
+/// Async AFFS filesystem reader.
+///
+/// Provides read-only access to an AFFS/OFS filesystem image through an
+/// [`AsyncBlockDevice`].
+///
+/// # Example
+///
+/// ```ignore
+/// let reader = AsyncAffsReader::new(&device).await?;
+/// let mut dir = reader.read_dir(reader.root_block()).await?;
+/// while let Some(entry) = dir.next().await {
+///     let entry = entry?;
+///     println!("{:?}: {} bytes", entry.name(), entry.size);
+/// }
+/// ```
+pub struct AsyncAffsReader<'a, D: AsyncBlockDevice> {
+    device: &'a D,
+    /// Boot block info.
+    boot: BootBlock,
+    /// Root block info.
+    root: RootBlock,
+    /// Calculated root block number.
+    root_block: u32,
+    /// Total blocks on device.
+    total_blocks: u32,
+    /// Mount options.
+    options: Options,
+}
+
+impl<'a, D: AsyncBlockDevice> AsyncAffsReader<'a, D> {
+    /// Create a new AFFS reader for a standard DD floppy (880KB).
+    pub async fn new(device: &'a D) -> Result<Self> {
+        Self::with_size(device, FLOPPY_DD_SECTORS).await
+    }
+
+    /// Create a new AFFS reader with a specific block count.
+    pub async fn with_size(device: &'a D, total_blocks: u32) -> Result<Self> {
+        Self::with_options(device, total_blocks, Options::default()).await
+    }
+
+    /// Create a new AFFS reader with a specific block count and options.
+    pub async fn with_options(device: &'a D, total_blocks: u32, options: Options) -> Result<Self> {
+        let mut boot_buf = [0u8; BOOT_BLOCK_SIZE];
+        read(device, 0, array_ref_mut(&mut boot_buf, 0)).await?;
+        read(device, 1, array_ref_mut(&mut boot_buf, BLOCK_SIZE)).await?;
+        let boot = BootBlock::parse_with(&boot_buf, options.verify_checksums)?;
+
+        // Calculate root block position (middle of disk)
+        let root_block = if boot.root_block != 0 {
+            boot.root_block
+        } else {
+            total_blocks / 2
+        };
+        if root_block >= total_blocks {
+            return Err(AffsError::BlockOutOfRange);
+        }
+
+        let mut root_buf = [0u8; BLOCK_SIZE];
+        read(device, root_block, &mut root_buf).await?;
+        let root = RootBlock::parse_with(&root_buf, options.verify_checksums).map_err(|err| {
+            if is_uninitialized_root(&root_buf) {
+                AffsError::EmptyVolume
+            } else {
+                err
+            }
+        })?;
+
+        Ok(Self {
+            device,
+            boot,
+            root,
+            root_block,
+            total_blocks,
+            options,
+        })
+    }
+
+    /// Get the options this reader was opened with.
+    #[inline]
+    pub const fn options(&self) -> Options {
+        self.options
+    }
+
+    /// Get the filesystem type (OFS or FFS).
+    #[inline]
+    pub const fn fs_type(&self) -> FsType {
+        self.boot.fs_type()
+    }
+
+    /// Get the filesystem flags.
+    #[inline]
+    pub const fn fs_flags(&self) -> FsFlags {
+        self.boot.fs_flags()
+    }
+
+    /// Check if international mode is enabled.
+    #[inline]
+    pub const fn is_intl(&self) -> bool {
+        self.boot.fs_flags().intl
+    }
+
+    /// Check if names are hashed with international case folding.
+    ///
+    /// See [`AffsReader::uses_intl_hashing`](crate::AffsReader::uses_intl_hashing).
+    #[inline]
+    pub const fn uses_intl_hashing(&self) -> bool {
+        self.boot.fs_flags().uses_intl_hashing()
+    }
+
+    /// Get the root block number.
+    #[inline]
+    pub const fn root_block(&self) -> u32 {
+        self.root_block
+    }
+
+    /// Get the total number of blocks.
+    #[inline]
+    pub const fn total_blocks(&self) -> u32 {
+        self.total_blocks
+    }
+
+    /// Get the disk name.
+    #[inline]
+    pub fn disk_name(&self) -> &[u8] {
+        self.root.name()
+    }
+
+    /// Get the underlying device.
+    #[inline]
+    pub const fn device(&self) -> &'a D {
+        self.device
+    }
+
+    /// Read an entry block.
+    pub async fn read_entry(&self, block: u32) -> Result<EntryBlock> {
+        let mut buf = [0u8; BLOCK_SIZE];
+        read(self.device, block, &mut buf).await?;
+        EntryBlock::parse_with_flags(&buf, self.options.verify_checksums, self.boot.fs_flags())
+    }
+
+    /// Iterate over entries in a directory.
+    ///
+    /// # Arguments
+    /// * `block` - Block number of the directory entry
+    pub async fn read_dir(&self, block: u32) -> Result<AsyncDirIter<'a, D>> {
+        let hash_table = self.dir_hash_table(block).await?;
+        Ok(AsyncDirIter {
+            device: self.device,
+            hash_table,
+            hash_index: 0,
+            current_chain: 0,
+            long_names: self.boot.fs_flags().long_names,
+            verify_checksums: self.options.verify_checksums,
+        })
+    }
+
+    /// Find an entry by name in a directory.
+    ///
+    /// Names are compared case-insensitively, like
+    /// [`AffsReader::find_entry`](crate::AffsReader::find_entry).
+    ///
+    /// # Arguments
+    /// * `dir_block` - Block number of the directory
+    /// * `name` - Name to search for
+    pub async fn find_entry(&self, dir_block: u32, name: &[u8]) -> Result<DirEntry> {
+        if name.len() > MAX_NAME_LEN {
+            return Err(AffsError::NameTooLong);
+        }
+
+        let intl = self.uses_intl_hashing();
+        let hash_table = self.dir_hash_table(dir_block).await?;
+        let mut block = hash_table[hash_name(name, intl)];
+
+        // Step past rejected entries, as the directory iterator does
+        let mut buf = [0u8; BLOCK_SIZE];
+        while block != 0 {
+            read(self.device, block, &mut buf).await?;
+            let (result, next) = parse_chain_entry(
+                block,
+                &buf,
+                self.options.verify_checksums,
+                self.boot.fs_flags().long_names,
+            );
+            match result {
+                Ok(entry) if names_equal(entry.name(), name, intl) => return Ok(entry),
+                Err(e) if !keeps_chain(e) => return Err(e),
+                _ => {}
+            }
+            block = next;
+        }
+
+        Err(AffsError::EntryNotFound)
+    }
+
+    /// Read a file's contents.
+    ///
+    /// # Arguments
+    /// * `block` - Block number of the file header
+    pub async fn read_file(&self, block: u32) -> Result<AsyncFileReader<'a, D>> {
+        let entry = self.read_entry(block).await?;
+        if !entry.is_file() {
+            return Err(AffsError::NotAFile);
+        }
+
+        Ok(AsyncFileReader {
+            device: self.device,
+            fs_type: self.fs_type(),
+            file_size: entry.byte_size,
+            remaining: entry.byte_size,
+            data_blocks: entry.hash_table,
+            blocks_in_current: entry.high_seq as u32,
+            index_in_current: 0,
+            next_extension: entry.extension,
+            next_data: entry.first_data,
+            offset_in_block: 0,
+            block_len: 0,
+            verify_checksums: self.options.verify_checksums,
+            buf: [0u8; BLOCK_SIZE],
+        })
+    }
+
+    /// Get the hash table of a directory block.
+    ///
+    /// Hard links to directories are resolved to the real directory.
+    async fn dir_hash_table(&self, block: u32) -> Result<[u32; HASH_TABLE_SIZE]> {
+        let mut block = block;
+        let mut hops = LinkHops::new(self.total_blocks);
+
+        loop {
+            if block == self.root_block {
+                return Ok(self.root.hash_table);
+            }
+
+            let entry = self.read_entry(block).await?;
+            if entry.sec_type != ST_LDIR {
+                if !entry.is_dir() {
+                    return Err(AffsError::NotADirectory);
+                }
+                return Ok(entry.hash_table);
+            }
+            block = hops.follow(entry.real_entry)?;
+        }
+    }
+}
+
+/// Async iterator over directory entries.
+///
+/// Call [`next`](Self::next) until it returns `None`.
+pub struct AsyncDirIter<'a, D: AsyncBlockDevice> {
+    device: &'a D,
+    hash_table: [u32; HASH_TABLE_SIZE],
+    hash_index: usize,
+    current_chain: u32,
+    long_names: bool,
+    verify_checksums: bool,
+}
+
+impl<D: AsyncBlockDevice> AsyncDirIter<'_, D> {
+    /// Get the next directory entry.
+    pub async fn next(&mut self) -> Option<Result<DirEntry>> {
+        let mut buf = [0u8; BLOCK_SIZE];
+        loop {
+            // If we're in a hash chain, continue it
+            if self.current_chain != 0 {
+                let block = self.current_chain;
+                // Abandon the chain on error so iteration always makes progress
+                self.current_chain = 0;
+
+                if let Err(e) = read(self.device, block, &mut buf).await {
+                    return Some(Err(e));
+                }
+
+                let (result, next) =
+                    parse_chain_entry(block, &buf, self.verify_checksums, self.long_names);
+                self.current_chain = next;
+                match result {
+                    // Skip invalid entries
+                    Err(e) if is_rejected_entry(e) => continue,
+                    result => return Some(result),
+                }
+            }
+
+            // Find next non-empty hash slot
+            while self.hash_index < HASH_TABLE_SIZE {
+                let block = self.hash_table[self.hash_index];
+                self.hash_index += 1;
+
+                if block != 0 {
+                    self.current_chain = block;
+                    break;
+                }
+            }
+
+            // No more entries
+            if self.current_chain == 0 {
+                return None;
+            }
+        }
+    }
+}
+
+/// Async streaming file reader.
+///
+/// Reads file data sequentially, one block at a time, for both OFS and FFS.
+pub struct AsyncFileReader<'a, D: AsyncBlockDevice> {
+    device: &'a D,
+    fs_type: FsType,
+    /// Total file size in bytes.
+    file_size: u32,
+    /// Bytes remaining to read.
+    remaining: u32,
+    /// Current data block pointers (from header or extension).
+    data_blocks: [u32; MAX_DATABLK],
+    /// Number of data blocks in the current header/ext block.
+    blocks_in_current: u32,
+    /// Index within current header/extension block.
+    index_in_current: u32,
+    /// Next extension block.
+    next_extension: u32,
+    /// Next OFS data block.
+    next_data: u32,
+    /// Offset within the loaded block's data.
+    offset_in_block: usize,
+    /// Valid data bytes in the loaded block.
+    block_len: usize,
+    verify_checksums: bool,
+    buf: [u8; BLOCK_SIZE],
+}
+
+impl<D: AsyncBlockDevice> AsyncFileReader<'_, D> {
+    /// Get the total file size in bytes.
+    #[inline]
+    pub const fn size(&self) -> u32 {
+        self.file_size
+    }
+
+    /// Get the number of bytes remaining to read.
+    #[inline]
+    pub const fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    /// Check if we've reached end of file.
+    #[inline]
+    pub const fn is_eof(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// Read data into a buffer.
+    ///
+    /// Returns the number of bytes read. Returns 0 at end of file.
+    pub async fn read(&mut self, out: &mut [u8]) -> Result<usize> {
+        let mut total_read = 0;
+
+        while total_read < out.len() && self.remaining > 0 {
+            if self.offset_in_block >= self.block_len {
+                self.load_next_block().await?;
+            }
+
+            let to_read = (self.block_len - self.offset_in_block)
+                .min(out.len() - total_read)
+                .min(self.remaining as usize);
+
+            let start = self.data_offset() + self.offset_in_block;
+            out[total_read..total_read + to_read]
+                .copy_from_slice(&self.buf[start..start + to_read]);
+
+            total_read += to_read;
+            self.offset_in_block += to_read;
+            self.remaining -= to_read as u32;
+        }
+
+        Ok(total_read)
+    }
+
+    /// Read the entire file into a buffer.
+    ///
+    /// The buffer must be at least as large as the file size.
+    /// Returns the number of bytes read.
+    pub async fn read_all(&mut self, out: &mut [u8]) -> Result<usize> {
+        if out.len() < self.remaining as usize {
+            return Err(AffsError::BufferTooSmall);
+        }
+        self.read(out).await
+    }
+
+    /// Get the data offset within a block.
+    #[inline]
+    const fn data_offset(&self) -> usize {
+        match self.fs_type {
+            FsType::Ofs => OfsDataBlock::HEADER_SIZE,
+            FsType::Ffs => 0,
+        }
+    }
+
+    /// Load the next data block into the buffer.
+    async fn load_next_block(&mut self) -> Result<()> {
+        let block = match self.fs_type {
+            FsType::Ofs => self.next_data,
+            FsType::Ffs => self.next_ffs_block().await?,
+        };
+        if block == 0 {
+            // Only called while bytes remain, so the file is shorter than its header claims
+            return Err(AffsError::TruncatedFile);
+        }
+
+        read(self.device, block, &mut self.buf).await?;
+
+        self.block_len = match self.fs_type {
+            FsType::Ofs => {
+                let header = OfsDataBlock::parse_with(&self.buf, self.verify_checksums)?;
+                self.next_data = header.next_data;
+                (header.data_size as usize).min(OFS_DATA_SIZE)
+            }
+            FsType::Ffs => FFS_DATA_SIZE,
+        };
+        if self.block_len == 0 {
+            return Err(AffsError::TruncatedFile);
+        }
+        self.offset_in_block = 0;
+        Ok(())
+    }
+
+    /// Get the next FFS data block number, loading extension blocks as needed.
+    async fn next_ffs_block(&mut self) -> Result<u32> {
+        if self.index_in_current >= self.blocks_in_current {
+            if self.next_extension == 0 {
+                return Ok(0);
+            }
+            read(self.device, self.next_extension, &mut self.buf).await?;
+            let ext = FileExtBlock::parse_with(&self.buf, self.verify_checksums)?;
+            self.data_blocks = ext.data_blocks;
+            self.blocks_in_current = ext.high_seq as u32;
+            self.next_extension = ext.extension;
+            self.index_in_current = 0;
+        }
+
+        let idx = self.index_in_current as usize;
+        if idx >= self.blocks_in_current as usize || idx >= MAX_DATABLK {
+            return Ok(0);
+        }
+
+        self.index_in_current += 1;
+        // Pointers are stored in reverse order
+        Ok(self.data_blocks[MAX_DATABLK - 1 - idx])
+    }
+}
@@ -0,0 +1,105 @@
+//! Bitmap page enumeration.
+
+use crate::block::BitmapExtBlock;
+use crate::constants::*;
+use crate::error::{AffsError, Result};
+use crate::reader::AffsReader;
+use crate::types::BlockDevice;
+
+/// Iterator over the block numbers of a volume's bitmap pages.
+///
+/// Yields the pages listed in the root block, then those in each bitmap
+/// extension block. Iteration ends at the first empty slot.
+pub struct BitmapBlockIter<'a, D: BlockDevice + ?Sized> {
+    device: &'a D,
+    total_blocks: u32,
+    /// Pages of the current table (root or extension block).
+    pages: [u32; BM_PAGES_EXT_SIZE],
+    /// Number of valid slots in `pages`.
+    len: usize,
+    index: usize,
+    /// Next bitmap extension block.
+    next_ext: u32,
+    /// Extension blocks followed so far, capped to avoid looping forever.
+    hops: u32,
+    done: bool,
+}
+
+impl<D: BlockDevice + ?Sized> BitmapBlockIter<'_, D> {
+    /// Load the next extension block's pages.
+    fn load_ext(&mut self) -> Result<()> {
+        if self.next_ext >= self.total_blocks {
+            return Err(AffsError::BlockOutOfRange);
+        }
+        if self.hops >= self.total_blocks {
+            return Err(AffsError::InvalidState);
+        }
+        self.hops += 1;
+
+        let mut buf = [0u8; BLOCK_SIZE];
+        self.device
+            .read_block(self.next_ext, &mut buf)
+            .map_err(|()| AffsError::BlockReadError)?;
+        let ext = BitmapExtBlock::parse(&buf);
+
+        self.pages = ext.bm_pages;
+        self.len = BM_PAGES_EXT_SIZE;
+        self.index = 0;
+        self.next_ext = ext.next;
+        Ok(())
+    }
+}
+
+impl<D: BlockDevice + ?Sized> Iterator for BitmapBlockIter<'_, D> {
+    type Item = Result<u32>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        if self.index >= self.len {
+            if self.next_ext == 0 {
+                self.done = true;
+                return None;
+            }
+            if let Err(e) = self.load_ext() {
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+
+        let page = self.pages[self.index];
+        self.index += 1;
+        if page == 0 {
+            self.done = true;
+            return None;
+        }
+        Some(Ok(page))
+    }
+}
+
+impl<D: BlockDevice + ?Sized> AffsReader<'_, D> {
+    /// Iterate over the block numbers of all bitmap pages.
+    ///
+    /// Starts with the root block's `bm_pages` and follows the `bm_ext`
+    /// chain. A chain pointer past the end of the volume yields
+    /// `AffsError::BlockOutOfRange`, and a chain longer than the volume
+    /// yields `AffsError::InvalidState`.
+    pub fn bitmap_blocks(&self) -> Result<BitmapBlockIter<'_, D>> {
+        let root = self.root();
+        let mut pages = [0u32; BM_PAGES_EXT_SIZE];
+        pages[..BM_PAGES_ROOT_SIZE].copy_from_slice(&root.bm_pages);
+
+        Ok(BitmapBlockIter {
+            device: self.device(),
+            total_blocks: self.total_blocks(),
+            pages,
+            len: BM_PAGES_ROOT_SIZE,
+            index: 0,
+            next_ext: root.bm_ext,
+            hops: 0,
+            done: false,
+        })
+    }
+}
@@ -1,10 +1,15 @@
 //! Block structure parsing.
 
-use crate::checksum::{boot_sum, normal_sum, read_i32_be, read_u32_be, read_u32_be_slice};
+use crate::checksum::{
+    bitmap_sum, boot_sum, normal_sum, read_i32_be, read_u32_be, read_u32_be_slice,
+};
 use crate::constants::*;
 use crate::date::AmigaDate;
 use crate::error::{AffsError, Result};
-use crate::types::{EntryType, FsFlags, FsType};
+use crate::types::{BlockType, EntryType, FsFlags, FsType};
+
+#[cfg(feature = "simd")]
+use wide::u8x16;
 
 /// Parsed boot block.
 #[derive(Debug, Clone)]
@@ -15,11 +20,19 @@ pub struct BootBlock {
     pub checksum: u32,
     /// Root block number.
     pub root_block: u32,
+    /// Boot code is present and the checksum matches.
+    bootable: bool,
 }
 
 impl BootBlock {
     /// Parse boot block from raw data (1024 bytes).
     pub fn parse(buf: &[u8; BOOT_BLOCK_SIZE]) -> Result<Self> {
+        Self::parse_with(buf, true)
+    }
+
+    /// Like [`parse`](Self::parse), with checksums checked as
+    /// [`Options::verify_checksums`](crate::Options::verify_checksums) says.
+    pub fn parse_with(buf: &[u8; BOOT_BLOCK_SIZE], verify_checksums: bool) -> Result<Self> {
         let dos_type = [buf[0], buf[1], buf[2], buf[3]];
 
         // Check for "DOS" signature
@@ -30,21 +43,42 @@ impl BootBlock {
         let checksum = read_u32_be_slice(buf, 4);
         let root_block = read_u32_be_slice(buf, 8);
 
+        let calculated = boot_sum(buf);
+
         // Verify checksum if boot code is present
-        if buf[12] != 0 {
-            let calculated = boot_sum(buf);
-            if checksum != calculated {
-                return Err(AffsError::ChecksumMismatch);
-            }
+        if buf[BOOT_CODE_OFFSET] != 0 && verify_checksums && checksum != calculated {
+            warn!(
+                "boot block checksum mismatch: {=u32:#x} != {=u32:#x}",
+                checksum, calculated
+            );
+            return Err(AffsError::ChecksumMismatch);
         }
 
+        let has_code = Self::boot_code(buf).iter().any(|&b| b != 0);
+
         Ok(Self {
             dos_type,
             checksum,
             root_block,
+            bootable: has_code && checksum == calculated,
         })
     }
 
+    /// Get the boot code region (everything after the 12-byte header).
+    #[inline]
+    pub fn boot_code(buf: &[u8; BOOT_BLOCK_SIZE]) -> &[u8] {
+        &buf[BOOT_CODE_OFFSET..]
+    }
+
+    /// Check if the disk is bootable.
+    ///
+    /// True when the boot code region is not all zeros and the boot block
+    /// checksum is valid.
+    #[inline]
+    pub const fn is_bootable(&self) -> bool {
+        self.bootable
+    }
+
     /// Get filesystem type (OFS or FFS).
     #[inline]
     pub const fn fs_type(&self) -> FsType {
@@ -63,7 +97,13 @@ impl BootBlock {
 }
 
 /// Parsed root block.
+///
+/// Built by [`parse`](Self::parse). Besides the public fields it keeps the
+/// unclamped name length byte, read with
+/// [`raw_name_len`](Self::raw_name_len), so it cannot be built with a
+/// struct literal outside this crate.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct RootBlock {
     /// Block type (should be T_HEADER).
     pub block_type: i32,
@@ -79,14 +119,21 @@ pub struct RootBlock {
     pub bm_pages: [u32; BM_PAGES_ROOT_SIZE],
     /// Bitmap extension block.
     pub bm_ext: u32,
-    /// Creation date.
+    /// Root block date, at offset 0x1A4.
+    ///
+    /// The AFFS spec calls this the last root alteration date; it matches
+    /// the volume creation date until the root directory is modified.
     pub creation_date: AmigaDate,
     /// Disk name length.
     pub name_len: u8,
     /// Disk name (up to 30 chars).
     pub disk_name: [u8; MAX_NAME_LEN],
-    /// Last modification date.
+    /// Name length byte as stored on disk, before clamping.
+    raw_name_len: u8,
+    /// Last disk alteration date, at offset 0x1D8.
     pub last_modified: AmigaDate,
+    /// Filesystem creation date, at offset 0x1E4.
+    pub fs_creation_date: AmigaDate,
     /// Directory cache extension (FFS only).
     pub extension: u32,
     /// Secondary type (should be ST_ROOT).
@@ -95,7 +142,16 @@ pub struct RootBlock {
 
 impl RootBlock {
     /// Parse root block from raw data.
+    ///
+    /// Returns `AffsError::InvalidState` if the hash table size is not 72,
+    /// the only size that fits a 512-byte block.
     pub fn parse(buf: &[u8; BLOCK_SIZE]) -> Result<Self> {
+        Self::parse_with(buf, true)
+    }
+
+    /// Like [`parse`](Self::parse), with checksums checked as
+    /// [`Options::verify_checksums`](crate::Options::verify_checksums) says.
+    pub fn parse_with(buf: &[u8; BLOCK_SIZE], verify_checksums: bool) -> Result<Self> {
         let block_type = read_i32_be(buf, 0);
         if block_type != T_HEADER {
             return Err(AffsError::InvalidBlockType);
@@ -103,16 +159,23 @@ impl RootBlock {
 
         let sec_type = read_i32_be(buf, 508);
         if sec_type != ST_ROOT {
+            warn!("root block has secondary type {=i32}", sec_type);
             return Err(AffsError::InvalidSecType);
         }
 
         let checksum = read_u32_be(buf, 20);
         let calculated = normal_sum(buf, 20);
-        if checksum != calculated {
+        if verify_checksums && checksum != calculated {
+            warn!("root block checksum mismatch");
             return Err(AffsError::ChecksumMismatch);
         }
 
+        // The fixed 512-byte layout only has room for a 72-entry table
         let hash_table_size = read_i32_be(buf, 12);
+        if hash_table_size != HASH_TABLE_SIZE as i32 {
+            warn!("root block hash table size {=i32}", hash_table_size);
+            return Err(AffsError::InvalidState);
+        }
 
         let mut hash_table = [0u32; HASH_TABLE_SIZE];
         for (i, entry) in hash_table.iter_mut().enumerate() {
@@ -134,7 +197,8 @@ impl RootBlock {
             read_i32_be(buf, 0x1AC),
         );
 
-        let name_len = buf[0x1B0].min(MAX_NAME_LEN as u8);
+        let raw_name_len = buf[0x1B0];
+        let name_len = raw_name_len.min(MAX_NAME_LEN as u8);
         let mut disk_name = [0u8; MAX_NAME_LEN];
         disk_name[..name_len as usize].copy_from_slice(&buf[0x1B1..0x1B1 + name_len as usize]);
 
@@ -144,6 +208,12 @@ impl RootBlock {
             read_i32_be(buf, 0x1E0),
         );
 
+        let fs_creation_date = AmigaDate::new(
+            read_i32_be(buf, 0x1E4),
+            read_i32_be(buf, 0x1E8),
+            read_i32_be(buf, 0x1EC),
+        );
+
         let extension = read_u32_be(buf, 0x1F8);
 
         Ok(Self {
@@ -157,18 +227,35 @@ impl RootBlock {
             creation_date,
             name_len,
             disk_name,
+            raw_name_len,
             last_modified,
+            fs_creation_date,
             extension,
             sec_type,
         })
     }
 
+    /// Get the block type as a [`BlockType`].
+    #[inline]
+    pub const fn block_type_enum(&self) -> Option<BlockType> {
+        BlockType::from_i32(self.block_type)
+    }
+
     /// Get disk name as string slice.
     #[inline]
     pub fn name(&self) -> &[u8] {
         &self.disk_name[..self.name_len as usize]
     }
 
+    /// Get the name length byte as stored on disk.
+    ///
+    /// Parsing clamps the length to 30; a larger raw value indicates a
+    /// corrupt block.
+    #[inline]
+    pub const fn raw_name_len(&self) -> u8 {
+        self.raw_name_len
+    }
+
     /// Check if bitmap is valid.
     #[inline]
     pub const fn bitmap_valid(&self) -> bool {
@@ -177,7 +264,13 @@ impl RootBlock {
 }
 
 /// Parsed entry block (file header or directory).
+///
+/// Built by [`parse`](Self::parse). Besides the public fields it keeps the
+/// unclamped name length byte, read with
+/// [`raw_name_len`](Self::raw_name_len), so it cannot be built with a
+/// struct literal outside this crate.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct EntryBlock {
     /// Block type (should be T_HEADER).
     pub block_type: i32,
@@ -205,6 +298,8 @@ pub struct EntryBlock {
     pub name_len: u8,
     /// Entry name (up to 30 chars).
     pub name: [u8; MAX_NAME_LEN],
+    /// Name length byte as stored on disk, before clamping.
+    raw_name_len: u8,
     /// Real entry (for hard links).
     pub real_entry: u32,
     /// Next link in chain.
@@ -222,18 +317,54 @@ pub struct EntryBlock {
 impl EntryBlock {
     /// Parse entry block from raw data.
     pub fn parse(buf: &[u8; BLOCK_SIZE]) -> Result<Self> {
+        Self::parse_with(buf, true)
+    }
+
+    /// Like [`parse`](Self::parse), with checksums checked as
+    /// [`Options::verify_checksums`](crate::Options::verify_checksums) says.
+    pub fn parse_with(buf: &[u8; BLOCK_SIZE], verify_checksums: bool) -> Result<Self> {
+        Self::parse_with_flags(buf, verify_checksums, FsFlags::default())
+    }
+
+    /// Like [`parse_with`](Self::parse_with), for a volume with the given
+    /// flags.
+    ///
+    /// On long filename volumes (`DOS\6` and `DOS\7`) the name and comment
+    /// are read from the combined area at 0x148 and the date from 0x1C4.
+    /// Names there can be up to 107 bytes; one longer than 30 bytes returns
+    /// `AffsError::NameTooLong` rather than being truncated. A comment
+    /// stored in a separate block is not read and comes back empty.
+    pub fn parse_with_flags(
+        buf: &[u8; BLOCK_SIZE],
+        verify_checksums: bool,
+        flags: FsFlags,
+    ) -> Result<Self> {
         let block_type = read_i32_be(buf, 0);
         if block_type != T_HEADER {
             return Err(AffsError::InvalidBlockType);
         }
 
+        let header_key = read_u32_be(buf, 4);
         let checksum = read_u32_be(buf, 20);
         let calculated = normal_sum(buf, 20);
-        if checksum != calculated {
+        if verify_checksums && checksum != calculated {
+            warn!("entry block {=u32} checksum mismatch", header_key);
             return Err(AffsError::ChecksumMismatch);
         }
 
-        let header_key = read_u32_be(buf, 4);
+        // Reject blocks that carry a valid type and checksum but nonsense fields
+        if let Err(e) = check_entry_fields(buf, flags.long_names) {
+            warn!("entry block {=u32} rejected: {}", header_key, e);
+            return Err(e);
+        }
+        let sec_type = read_i32_be(buf, 0x1FC);
+        let layout = EntryLayout::new(flags.long_names);
+        let raw_name_len = buf[layout.name];
+        if flags.long_names && raw_name_len as usize > MAX_NAME_LEN {
+            warn!("entry block {=u32} has a long name", header_key);
+            return Err(AffsError::NameTooLong);
+        }
+
         let high_seq = read_i32_be(buf, 8);
         let first_data = read_u32_be(buf, 16);
 
@@ -245,26 +376,30 @@ impl EntryBlock {
         let access = read_u32_be(buf, 0x140);
         let byte_size = read_u32_be(buf, 0x144);
 
-        let comment_len = buf[0x148].min(MAX_COMMENT_LEN as u8);
+        let comment_at = layout.comment(raw_name_len);
+        let comment_len = buf[comment_at]
+            .min(MAX_COMMENT_LEN as u8)
+            .min((layout.comment_end - comment_at - 1) as u8);
         let mut comment = [0u8; MAX_COMMENT_LEN];
-        comment[..comment_len as usize].copy_from_slice(&buf[0x149..0x149 + comment_len as usize]);
+        comment[..comment_len as usize]
+            .copy_from_slice(&buf[comment_at + 1..comment_at + 1 + comment_len as usize]);
 
         let date = AmigaDate::new(
-            read_i32_be(buf, 0x1A4),
-            read_i32_be(buf, 0x1A8),
-            read_i32_be(buf, 0x1AC),
+            read_i32_be(buf, layout.date),
+            read_i32_be(buf, layout.date + 4),
+            read_i32_be(buf, layout.date + 8),
         );
 
-        let name_len = buf[0x1B0].min(MAX_NAME_LEN as u8);
+        let name_len = raw_name_len.min(MAX_NAME_LEN as u8);
         let mut name = [0u8; MAX_NAME_LEN];
-        name[..name_len as usize].copy_from_slice(&buf[0x1B1..0x1B1 + name_len as usize]);
+        name[..name_len as usize]
+            .copy_from_slice(&buf[layout.name + 1..layout.name + 1 + name_len as usize]);
 
         let real_entry = read_u32_be(buf, 0x1D4);
         let next_link = read_u32_be(buf, 0x1D8);
         let next_same_hash = read_u32_be(buf, 0x1F0);
         let parent = read_u32_be(buf, 0x1F4);
         let extension = read_u32_be(buf, 0x1F8);
-        let sec_type = read_i32_be(buf, 0x1FC);
 
         Ok(Self {
             block_type,
@@ -280,6 +415,7 @@ impl EntryBlock {
             date,
             name_len,
             name,
+            raw_name_len,
             real_entry,
             next_link,
             next_same_hash,
@@ -289,12 +425,28 @@ impl EntryBlock {
         })
     }
 
+    /// Get the block type as a [`BlockType`].
+    #[inline]
+    pub const fn block_type_enum(&self) -> Option<BlockType> {
+        BlockType::from_i32(self.block_type)
+    }
+
     /// Get entry name as byte slice.
     #[inline]
     pub fn name(&self) -> &[u8] {
         &self.name[..self.name_len as usize]
     }
 
+    /// Get the name length byte as stored on disk.
+    ///
+    /// Parsing clamps the length to 30; a larger raw value indicates a
+    /// corrupt block. Long filename volumes return `AffsError::NameTooLong`
+    /// for such entries instead.
+    #[inline]
+    pub const fn raw_name_len(&self) -> u8 {
+        self.raw_name_len
+    }
+
     /// Get comment as byte slice.
     #[inline]
     pub fn comment(&self) -> &[u8] {
@@ -330,6 +482,77 @@ impl EntryBlock {
             0
         }
     }
+
+    /// Get the first data block of a file.
+    ///
+    /// OFS data blocks form a linked list headed by `first_data`. FFS
+    /// only needs the pointer table, where `first_data` is often 0, so
+    /// the first table entry is returned instead.
+    #[inline]
+    pub const fn first_data_block(&self, fs_type: FsType) -> u32 {
+        match fs_type {
+            FsType::Ofs => self.first_data,
+            FsType::Ffs => self.data_block(0),
+        }
+    }
+}
+
+/// Offsets of the name, comment and date fields in a 512-byte entry block.
+struct EntryLayout {
+    /// Name length byte, followed by the name.
+    name: usize,
+    /// Comment length byte, or `None` if the comment follows the name.
+    comment: Option<usize>,
+    /// End of the area the comment must fit in.
+    comment_end: usize,
+    /// Modification date.
+    date: usize,
+}
+
+impl EntryLayout {
+    const fn new(long_names: bool) -> Self {
+        if long_names {
+            // Name and comment share one area, each with a length byte
+            Self {
+                name: 0x148,
+                comment: None,
+                comment_end: 0x1B8,
+                date: 0x1C4,
+            }
+        } else {
+            Self {
+                name: 0x1B0,
+                comment: Some(0x148),
+                comment_end: 0x198,
+                date: 0x1A4,
+            }
+        }
+    }
+
+    /// Offset of the comment length byte for a name of `name_len` bytes.
+    const fn comment(&self, name_len: u8) -> usize {
+        match self.comment {
+            Some(offset) => offset,
+            None => self.name + 1 + name_len as usize,
+        }
+    }
+}
+
+/// Check the contents [`EntryBlock::parse`] rejects in a block whose type
+/// and checksum are valid.
+///
+/// Returns `AffsError::InvalidSecType` for an unknown secondary type and
+/// `AffsError::EmptyName` for a non-root entry without a name. The name is
+/// looked for where `long_names` volumes keep it if that is set.
+pub(crate) fn check_entry_fields(buf: &[u8; BLOCK_SIZE], long_names: bool) -> Result<()> {
+    let sec_type = read_i32_be(buf, 0x1FC);
+    if EntryType::from_sec_type(sec_type).is_none() {
+        return Err(AffsError::InvalidSecType);
+    }
+    if buf[EntryLayout::new(long_names).name] == 0 && sec_type != ST_ROOT {
+        return Err(AffsError::EmptyName);
+    }
+    Ok(())
 }
 
 /// Parsed file extension block.
@@ -356,18 +579,25 @@ pub struct FileExtBlock {
 impl FileExtBlock {
     /// Parse file extension block from raw data.
     pub fn parse(buf: &[u8; BLOCK_SIZE]) -> Result<Self> {
+        Self::parse_with(buf, true)
+    }
+
+    /// Like [`parse`](Self::parse), with checksums checked as
+    /// [`Options::verify_checksums`](crate::Options::verify_checksums) says.
+    pub fn parse_with(buf: &[u8; BLOCK_SIZE], verify_checksums: bool) -> Result<Self> {
         let block_type = read_i32_be(buf, 0);
         if block_type != T_LIST {
             return Err(AffsError::InvalidBlockType);
         }
 
+        let header_key = read_u32_be(buf, 4);
         let checksum = read_u32_be(buf, 20);
         let calculated = normal_sum(buf, 20);
-        if checksum != calculated {
+        if verify_checksums && checksum != calculated {
+            warn!("extension block {=u32} checksum mismatch", header_key);
             return Err(AffsError::ChecksumMismatch);
         }
 
-        let header_key = read_u32_be(buf, 4);
         let high_seq = read_i32_be(buf, 8);
 
         let mut data_blocks = [0u32; MAX_DATABLK];
@@ -391,6 +621,12 @@ impl FileExtBlock {
         })
     }
 
+    /// Get the block type as a [`BlockType`].
+    #[inline]
+    pub const fn block_type_enum(&self) -> Option<BlockType> {
+        BlockType::from_i32(self.block_type)
+    }
+
     /// Get data block pointer at index.
     #[inline]
     pub const fn data_block(&self, index: usize) -> u32 {
@@ -426,6 +662,12 @@ impl OfsDataBlock {
 
     /// Parse OFS data block header from raw data.
     pub fn parse(buf: &[u8; BLOCK_SIZE]) -> Result<Self> {
+        Self::parse_with(buf, true)
+    }
+
+    /// Like [`parse`](Self::parse), with checksums checked as
+    /// [`Options::verify_checksums`](crate::Options::verify_checksums) says.
+    pub fn parse_with(buf: &[u8; BLOCK_SIZE], verify_checksums: bool) -> Result<Self> {
         let block_type = read_i32_be(buf, 0);
         if block_type != T_DATA {
             return Err(AffsError::InvalidBlockType);
@@ -433,7 +675,12 @@ impl OfsDataBlock {
 
         let checksum = read_u32_be(buf, 20);
         let calculated = normal_sum(buf, 20);
-        if checksum != calculated {
+        if verify_checksums && checksum != calculated {
+            warn!(
+                "data block {=u32} of file {=u32} checksum mismatch",
+                read_u32_be(buf, 8),
+                read_u32_be(buf, 4)
+            );
             return Err(AffsError::ChecksumMismatch);
         }
 
@@ -447,11 +694,313 @@ impl OfsDataBlock {
         })
     }
 
+    /// Get the block type as a [`BlockType`].
+    #[inline]
+    pub const fn block_type_enum(&self) -> Option<BlockType> {
+        BlockType::from_i32(self.block_type)
+    }
+
     /// Get data portion of the block.
     #[inline]
     pub fn data(buf: &[u8; BLOCK_SIZE]) -> &[u8] {
         &buf[Self::HEADER_SIZE..]
     }
+
+    /// Get the valid data of the block.
+    ///
+    /// Parses the header and returns the first `data_size` bytes of the data
+    /// portion. Returns `AffsError::InvalidState` if `data_size` exceeds
+    /// the 488 bytes available.
+    pub fn valid_data(buf: &[u8; BLOCK_SIZE]) -> Result<&[u8]> {
+        let header = Self::parse(buf)?;
+        let size = header.data_size as usize;
+        if size > OFS_DATA_SIZE {
+            return Err(AffsError::InvalidState);
+        }
+        Ok(&buf[Self::HEADER_SIZE..Self::HEADER_SIZE + size])
+    }
+}
+
+/// Check whether `buf` looks like the first OFS data block of a file.
+///
+/// True when the block starts with `T_DATA`, this file's header block and
+/// sequence number 1. The checksum is not consulted, so a damaged OFS block
+/// still reads as OFS and its checksum error is reported on its own.
+pub(crate) fn looks_like_ofs_data(buf: &[u8; BLOCK_SIZE], header_block: u32) -> bool {
+    read_i32_be(buf, 0) == T_DATA && read_u32_be(buf, 4) == header_block && read_u32_be(buf, 8) == 1
+}
+
+/// Parsed bitmap block.
+///
+/// Each bit of the map tracks one block; a set bit means the block is free.
+#[derive(Debug, Clone)]
+pub struct BitmapBlock {
+    /// Checksum.
+    pub checksum: u32,
+    /// Allocation map longwords.
+    pub map: [u32; BM_MAP_SIZE],
+}
+
+impl BitmapBlock {
+    /// Parse bitmap block from raw data.
+    pub fn parse(buf: &[u8; BLOCK_SIZE]) -> Result<Self> {
+        Self::parse_with(buf, true)
+    }
+
+    /// Like [`parse`](Self::parse), with checksums checked as
+    /// [`Options::verify_checksums`](crate::Options::verify_checksums) says.
+    pub fn parse_with(buf: &[u8; BLOCK_SIZE], verify_checksums: bool) -> Result<Self> {
+        let checksum = read_u32_be(buf, 0);
+        let calculated = bitmap_sum(buf);
+        if verify_checksums && checksum != calculated {
+            warn!("bitmap block checksum mismatch");
+            return Err(AffsError::ChecksumMismatch);
+        }
+
+        let mut map = [0u32; BM_MAP_SIZE];
+        for (i, word) in map.iter_mut().enumerate() {
+            *word = read_u32_be(buf, 4 + i * 4);
+        }
+
+        Ok(Self { checksum, map })
+    }
+
+    /// Check if the block at bit `index` is marked free.
+    #[inline]
+    pub const fn is_free(&self, index: usize) -> bool {
+        if index < BM_MAP_SIZE * 32 {
+            (self.map[index / 32] & (1 << (index % 32))) != 0
+        } else {
+            false
+        }
+    }
+
+    /// Count free blocks among the first `bits` entries of the map.
+    pub fn count_free(&self, bits: usize) -> u32 {
+        let bits = bits.min(BM_MAP_SIZE * 32);
+        let full_words = bits / 32;
+
+        let mut free: u32 = self.map[..full_words]
+            .iter()
+            .map(|word| word.count_ones())
+            .sum();
+
+        let tail_bits = bits % 32;
+        if tail_bits != 0 {
+            let mask = (1u32 << tail_bits) - 1;
+            free += (self.map[full_words] & mask).count_ones();
+        }
+
+        free
+    }
+}
+
+/// Parsed bitmap extension block.
+///
+/// Lists further bitmap pages once the root block's 25 slots are used up.
+/// These blocks have no type or checksum.
+#[derive(Debug, Clone)]
+pub struct BitmapExtBlock {
+    /// Bitmap page block numbers.
+    pub bm_pages: [u32; BM_PAGES_EXT_SIZE],
+    /// Next bitmap extension block (0 = none).
+    pub next: u32,
+}
+
+impl BitmapExtBlock {
+    /// Parse bitmap extension block from raw data.
+    pub fn parse(buf: &[u8; BLOCK_SIZE]) -> Self {
+        let mut bm_pages = [0u32; BM_PAGES_EXT_SIZE];
+        for (i, page) in bm_pages.iter_mut().enumerate() {
+            *page = read_u32_be(buf, i * 4);
+        }
+        let next = read_u32_be(buf, BM_PAGES_EXT_SIZE * 4);
+
+        Self { bm_pages, next }
+    }
+}
+
+/// Parsed directory cache block (DIRCACHE mode).
+///
+/// Holds packed [`DirCacheRecord`]s describing the entries of one directory.
+#[derive(Debug, Clone)]
+pub struct DirCacheBlock {
+    /// Block type (should be T_DIRC).
+    pub block_type: i32,
+    /// This block's sector number.
+    pub header_key: u32,
+    /// Parent directory block.
+    pub parent: u32,
+    /// Number of records in this block.
+    pub records_nb: u32,
+    /// Next directory cache block (0 if last).
+    pub next_dirc: u32,
+    /// Checksum.
+    pub checksum: u32,
+    /// Packed record data.
+    pub records: [u8; DirCacheBlock::RECORDS_SIZE],
+}
+
+impl DirCacheBlock {
+    /// Offset of the record area within the block.
+    pub const RECORDS_OFFSET: usize = 24;
+
+    /// Size of the record area in bytes.
+    pub const RECORDS_SIZE: usize = BLOCK_SIZE - Self::RECORDS_OFFSET;
+
+    /// Parse directory cache block from raw data.
+    pub fn parse(buf: &[u8; BLOCK_SIZE]) -> Result<Self> {
+        Self::parse_with(buf, true)
+    }
+
+    /// Like [`parse`](Self::parse), with checksums checked as
+    /// [`Options::verify_checksums`](crate::Options::verify_checksums) says.
+    pub fn parse_with(buf: &[u8; BLOCK_SIZE], verify_checksums: bool) -> Result<Self> {
+        let block_type = read_i32_be(buf, 0);
+        if block_type != T_DIRC {
+            return Err(AffsError::InvalidBlockType);
+        }
+
+        let checksum = read_u32_be(buf, 20);
+        let calculated = normal_sum(buf, 20);
+        if verify_checksums && checksum != calculated {
+            return Err(AffsError::ChecksumMismatch);
+        }
+
+        let mut records = [0u8; Self::RECORDS_SIZE];
+        records.copy_from_slice(&buf[Self::RECORDS_OFFSET..]);
+
+        Ok(Self {
+            block_type,
+            header_key: read_u32_be(buf, 4),
+            parent: read_u32_be(buf, 8),
+            records_nb: read_u32_be(buf, 12),
+            next_dirc: read_u32_be(buf, 16),
+            checksum,
+            records,
+        })
+    }
+
+    /// Get the block type as a [`BlockType`].
+    #[inline]
+    pub const fn block_type_enum(&self) -> Option<BlockType> {
+        BlockType::from_i32(self.block_type)
+    }
+
+    /// Iterate over the records in this block.
+    #[inline]
+    pub fn iter(&self) -> DirCacheRecords<'_> {
+        DirCacheRecords {
+            data: &self.records,
+            remaining: self.records_nb,
+        }
+    }
+}
+
+/// One packed entry in a [`DirCacheBlock`].
+#[derive(Debug, Clone, Copy)]
+pub struct DirCacheRecord<'a> {
+    /// Block number of the entry's header.
+    pub header: u32,
+    /// File size in bytes.
+    pub size: u32,
+    /// Access flags.
+    pub protect: u32,
+    /// Last modification date.
+    pub date: AmigaDate,
+    /// Secondary type of the entry.
+    pub sec_type: i32,
+    /// Entry name.
+    pub name: &'a [u8],
+    /// Entry comment.
+    pub comment: &'a [u8],
+}
+
+impl<'a> DirCacheRecord<'a> {
+    /// Fixed-size part of a record, before the name.
+    pub const HEADER_SIZE: usize = 24;
+
+    /// Parse a record from the start of `data`.
+    ///
+    /// Returns the record and its padded length, or `None` if the record is
+    /// truncated or its name or comment is too long.
+    pub fn parse(data: &'a [u8]) -> Option<(Self, usize)> {
+        let field = |offset: usize| -> Option<u32> {
+            let bytes = data.get(offset..offset + 4)?;
+            Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        };
+        let short = |offset: usize| -> Option<i32> {
+            let bytes = data.get(offset..offset + 2)?;
+            Some(u16::from_be_bytes([bytes[0], bytes[1]]) as i32)
+        };
+
+        let header = field(0)?;
+        let size = field(4)?;
+        let protect = field(8)?;
+        let date = AmigaDate::new(short(16)?, short(18)?, short(20)?);
+        let sec_type = *data.get(22)? as i8 as i32;
+
+        let name_len = *data.get(23)? as usize;
+        if name_len > MAX_NAME_LEN {
+            return None;
+        }
+        let name_start = Self::HEADER_SIZE;
+        let name = data.get(name_start..name_start + name_len)?;
+
+        let comment_len = *data.get(name_start + name_len)? as usize;
+        if comment_len > MAX_COMMENT_LEN {
+            return None;
+        }
+        let comment_start = name_start + name_len + 1;
+        let comment = data.get(comment_start..comment_start + comment_len)?;
+
+        // Records are padded to an even length
+        let len = (comment_start + comment_len + 1) & !1;
+
+        Some((
+            Self {
+                header,
+                size,
+                protect,
+                date,
+                sec_type,
+                name,
+                comment,
+            },
+            len,
+        ))
+    }
+
+    /// Get the entry type.
+    #[inline]
+    pub const fn entry_type(&self) -> Option<EntryType> {
+        EntryType::from_sec_type(self.sec_type)
+    }
+}
+
+/// Iterator over the records of a [`DirCacheBlock`].
+///
+/// Stops early if a record is malformed.
+pub struct DirCacheRecords<'a> {
+    data: &'a [u8],
+    remaining: u32,
+}
+
+impl<'a> Iterator for DirCacheRecords<'a> {
+    type Item = DirCacheRecord<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let Some((record, len)) = DirCacheRecord::parse(self.data) else {
+            self.remaining = 0;
+            return None;
+        };
+        self.remaining -= 1;
+        self.data = self.data.get(len..).unwrap_or(&[]);
+        Some(record)
+    }
 }
 
 /// Compute hash value for a name.
@@ -459,6 +1008,26 @@ impl OfsDataBlock {
 /// This implements the Amiga filename hashing algorithm.
 #[inline]
 pub fn hash_name(name: &[u8], intl: bool) -> usize {
+    #[cfg(feature = "simd")]
+    {
+        hash_name_simd(name, intl)
+    }
+
+    #[cfg(not(feature = "simd"))]
+    {
+        hash_name_scalar(name, intl)
+    }
+}
+
+/// One step of the hash recurrence for an already uppercased character.
+#[inline]
+const fn hash_step(hash: u32, upper: u8) -> u32 {
+    (hash.wrapping_mul(13).wrapping_add(upper as u32)) & 0x7FF
+}
+
+/// Scalar implementation of hash_name.
+#[inline]
+fn hash_name_scalar(name: &[u8], intl: bool) -> usize {
     let mut hash = name.len() as u32;
 
     for &c in name {
@@ -467,11 +1036,62 @@ pub fn hash_name(name: &[u8], intl: bool) -> usize {
         } else {
             ascii_to_upper(c)
         };
-        hash = (hash.wrapping_mul(13).wrapping_add(upper as u32)) & 0x7FF;
+        hash = hash_step(hash, upper);
+    }
+    (hash % HASH_TABLE_SIZE as u32) as usize
+}
+
+/// SIMD-assisted implementation of hash_name.
+///
+/// The `& 0x7FF` after each step makes the recurrence serial, so only the
+/// case folding is vectorized, 16 bytes at a time. Names shorter than one
+/// vector use the scalar path.
+#[cfg(feature = "simd")]
+#[inline]
+fn hash_name_simd(name: &[u8], intl: bool) -> usize {
+    if name.len() < 16 {
+        return hash_name_scalar(name, intl);
+    }
+
+    let mut hash = name.len() as u32;
+    let mut chunks = name.chunks_exact(16);
+    for chunk in &mut chunks {
+        let chunk: [u8; 16] = chunk.try_into().expect("chunk size mismatch");
+        for upper in to_upper_x16(chunk, intl) {
+            hash = hash_step(hash, upper);
+        }
+    }
+    for &c in chunks.remainder() {
+        let upper = if intl {
+            intl_to_upper(c)
+        } else {
+            ascii_to_upper(c)
+        };
+        hash = hash_step(hash, upper);
     }
     (hash % HASH_TABLE_SIZE as u32) as usize
 }
 
+/// Uppercase 16 bytes at once, matching `ascii_to_upper` / `intl_to_upper`.
+#[cfg(feature = "simd")]
+#[inline]
+fn to_upper_x16(chunk: [u8; 16], intl: bool) -> [u8; 16] {
+    /// All-ones lanes where `lo <= c <= hi`.
+    #[inline]
+    fn in_range(c: u8x16, lo: u8, hi: u8) -> u8x16 {
+        let offset = c - u8x16::splat(lo);
+        offset.min(u8x16::splat(hi - lo)).simd_eq(offset)
+    }
+
+    let c = u8x16::new(chunk);
+    let mut lower = in_range(c, b'a', b'z');
+    if intl {
+        // Latin-1 lowercase 224-254, excluding the division sign (247)
+        lower = lower | in_range(c, 224, 246) | in_range(c, 248, 254);
+    }
+    (c - (lower & u8x16::splat(32))).to_array()
+}
+
 /// Convert ASCII character to uppercase using branchless operation.
 #[inline]
 const fn ascii_to_upper(c: u8) -> u8 {
@@ -485,18 +1105,19 @@ const fn ascii_to_upper(c: u8) -> u8 {
 
 /// Convert character to uppercase with international support.
 ///
-/// Handles Latin-1 characters (192-254) excluding multiplication sign (247).
-/// Range 224-254 covers lowercase accented letters (à-þ) that map to
-/// uppercase equivalents (À-Þ) by subtracting 32.
+/// Follows the AmigaDOS international case folding: lowercase accented
+/// letters 224-254 (à-þ) map to 192-222 (À-Þ) by subtracting 32, except the
+/// division sign (247). `ß` (223) has no uppercase form and `ÿ` (255) is
+/// left as is, since its uppercase form is outside Latin-1.
 #[inline]
 pub const fn intl_to_upper(c: u8) -> u8 {
     const ASCII_CASE_DIFF: u8 = 32;
     const LATIN1_LOWER_START: u8 = 224;
     const LATIN1_LOWER_END: u8 = 254;
-    const MULTIPLICATION_SIGN: u8 = 247;
+    const DIVISION_SIGN: u8 = 247;
 
     if (c >= b'a' && c <= b'z')
-        || (c >= LATIN1_LOWER_START && c <= LATIN1_LOWER_END && c != MULTIPLICATION_SIGN)
+        || (c >= LATIN1_LOWER_START && c <= LATIN1_LOWER_END && c != DIVISION_SIGN)
     {
         c.wrapping_sub(ASCII_CASE_DIFF)
     } else {
@@ -531,10 +1152,110 @@ pub fn names_equal(a: &[u8], b: &[u8], intl: bool) -> bool {
     true
 }
 
+/// Order two names case-insensitively.
+///
+/// Names are compared by their uppercased bytes, as in [`names_equal`];
+/// names that are equal ignoring case are ordered by their raw bytes.
+pub fn compare_names(a: &[u8], b: &[u8], intl: bool) -> core::cmp::Ordering {
+    let fold = |c: u8| {
+        if intl {
+            intl_to_upper(c)
+        } else {
+            ascii_to_upper(c)
+        }
+    };
+    a.iter()
+        .map(|&c| fold(c))
+        .cmp(b.iter().map(|&c| fold(c)))
+        .then_with(|| a.cmp(b))
+}
+
+/// Match a name against a wildcard pattern (case-insensitive).
+///
+/// `*` matches any run of characters (including none) and `?` matches
+/// exactly one character. All other bytes match themselves, ignoring
+/// case as in [`names_equal`].
+pub fn glob_match(pattern: &[u8], name: &[u8], intl: bool) -> bool {
+    let fold = |c: u8| {
+        if intl {
+            intl_to_upper(c)
+        } else {
+            ascii_to_upper(c)
+        }
+    };
+
+    let (mut p, mut n) = (0, 0);
+    // Position after the most recent `*`, and the name position it is tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                p += 1;
+                backtrack = Some((p, n));
+            }
+            Some(&c) if c == b'?' || fold(c) == fold(name[n]) => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` absorb one more character
+                Some((bp, bn)) => {
+                    p = bp;
+                    n = bn + 1;
+                    backtrack = Some((bp, bn + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[cfg(feature = "simd")]
+    #[test]
+    fn test_hash_name_simd_matches_scalar() {
+        let mut name = [0u8; 40];
+        for intl in [false, true] {
+            // Every byte value in every lane position, at several lengths
+            for byte in 0..=255u8 {
+                for len in [16, 17, 30, 31, 32, 40] {
+                    for (i, c) in name[..len].iter_mut().enumerate() {
+                        *c = byte.wrapping_add((i as u8).wrapping_mul(37));
+                    }
+                    assert_eq!(
+                        hash_name_simd(&name[..len], intl),
+                        hash_name_scalar(&name[..len], intl),
+                        "byte {byte} len {len} intl {intl}"
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            hash_name_simd(b"\xe0\xe9\xf7\xfe\xffabcdefghijklmnop", true),
+            hash_name_scalar(b"\xe0\xe9\xf7\xfe\xffabcdefghijklmnop", true)
+        );
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(b"*.info", b"Disk.info", false));
+        assert!(glob_match(b"*.INFO", b"disk.info", false));
+        assert!(!glob_match(b"*.info", b"Disk.inf", false));
+        assert!(glob_match(b"?at", b"cat", false));
+        assert!(!glob_match(b"?at", b"at", false));
+        assert!(glob_match(b"*", b"", false));
+        assert!(glob_match(b"a*b*c", b"aXXbYYbc", false));
+        assert!(!glob_match(b"a*b*c", b"aXXbYY", false));
+        assert!(glob_match(b"\xe0*", b"\xc0x", true));
+        assert!(!glob_match(b"\xe0*", b"\xc0x", false));
+    }
+
     #[test]
     fn test_hash_name() {
         // These are known hash values from the AFFS spec
@@ -548,6 +1269,38 @@ mod tests {
         assert_eq!(intl_to_upper(b'z'), b'Z');
         assert_eq!(intl_to_upper(b'A'), b'A');
         assert_eq!(intl_to_upper(224), 192); // à -> À
+        assert_eq!(intl_to_upper(254), 222); // þ -> Þ
+        assert_eq!(intl_to_upper(0xDF), 0xDF); // ß has no uppercase
+        assert_eq!(intl_to_upper(0xF7), 0xF7); // ÷ is not a letter
+        assert_eq!(intl_to_upper(0xFF), 0xFF); // ÿ is not folded to ß
+        assert_eq!(intl_to_upper(0xD7), 0xD7); // ×
+    }
+
+    #[test]
+    fn test_intl_edge_cases_hash_and_compare() {
+        // Bytes that must not fold, including where the SIMD path kicks in
+        let mut name = [b'x'; 20];
+        for &c in &[0xDFu8, 0xF7, 0xFF] {
+            name[19] = c;
+            let mut other = name;
+            other[19] = c.wrapping_sub(32);
+            assert!(!names_equal(&name, &other, true));
+            assert_eq!(hash_name(&name, true), hash_name_scalar(&name, true));
+        }
+        assert!(names_equal(b"\xE0\xFE", b"\xC0\xDE", true));
+        assert!(!names_equal(b"\xE0", b"\xC0", false));
+    }
+
+    #[test]
+    fn test_compare_names() {
+        use core::cmp::Ordering;
+        assert_eq!(compare_names(b"apple", b"Banana", false), Ordering::Less);
+        assert_eq!(compare_names(b"Apple", b"apple", false), Ordering::Less);
+        assert_eq!(compare_names(b"abc", b"ab", false), Ordering::Greater);
+        // \xE9 folds to \xC9 only in international mode
+        assert_eq!(compare_names(b"\xE9", b"\xC9", true), Ordering::Greater);
+        assert_eq!(compare_names(b"\xE9", b"\xCA", true), Ordering::Less);
+        assert_eq!(compare_names(b"\xE9", b"\xCA", false), Ordering::Greater);
     }
 
     #[test]
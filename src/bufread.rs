@@ -0,0 +1,152 @@
+//! Buffered file reading.
+
+use crate::error::Result;
+use crate::file::FileReader;
+use crate::types::BlockDevice;
+
+/// Default [`BufReader`] capacity in bytes.
+pub const DEFAULT_BUF_SIZE: usize = 4096;
+
+/// Buffer storage for a [`BufReader`].
+///
+/// Without `alloc` this is an inline array, so the reader itself is `N`
+/// bytes larger. With `alloc` the buffer is boxed, which keeps large
+/// buffers off the stack.
+#[cfg(feature = "alloc")]
+type Storage<const N: usize> = alloc::boxed::Box<[u8; N]>;
+#[cfg(not(feature = "alloc"))]
+type Storage<const N: usize> = [u8; N];
+
+/// Create a zeroed buffer.
+#[inline]
+fn new_storage<const N: usize>() -> Storage<N> {
+    #[cfg(feature = "alloc")]
+    {
+        // Allocate directly on the heap rather than building the array on the stack
+        alloc::vec![0u8; N]
+            .into_boxed_slice()
+            .try_into()
+            .expect("slice size mismatch")
+    }
+    #[cfg(not(feature = "alloc"))]
+    {
+        [0u8; N]
+    }
+}
+
+/// A [`FileReader`] with an `N`-byte read buffer.
+///
+/// Small reads are served from the buffer, which is refilled with one large
+/// read of the underlying file. This avoids the per-call overhead (and OFS
+/// block header parsing) of many tiny reads. Reads at least as large as the
+/// buffer bypass it.
+pub struct BufReader<'a, D: BlockDevice + ?Sized, const N: usize = DEFAULT_BUF_SIZE> {
+    inner: FileReader<'a, D>,
+    buf: Storage<N>,
+    /// Next unread byte in `buf`.
+    pos: usize,
+    /// Number of valid bytes in `buf`.
+    filled: usize,
+}
+
+impl<'a, D: BlockDevice + ?Sized, const N: usize> BufReader<'a, D, N> {
+    /// Wrap a file reader.
+    pub fn new(inner: FileReader<'a, D>) -> Self {
+        Self {
+            inner,
+            buf: new_storage(),
+            pos: 0,
+            filled: 0,
+        }
+    }
+
+    /// Get the underlying file reader.
+    ///
+    /// Its position is past any buffered data.
+    #[inline]
+    pub const fn get_ref(&self) -> &FileReader<'a, D> {
+        &self.inner
+    }
+
+    /// Unwrap the reader, discarding buffered data.
+    ///
+    /// The returned reader is positioned at [`position`](Self::position).
+    pub fn into_inner(mut self) -> Result<FileReader<'a, D>> {
+        let position = self.position();
+        self.inner.seek(position)?;
+        Ok(self.inner)
+    }
+
+    /// Get the buffer capacity in bytes.
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Get the buffered data that has not been read yet.
+    #[inline]
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..self.filled]
+    }
+
+    /// Get the total file size in bytes.
+    #[inline]
+    pub const fn size(&self) -> u32 {
+        self.inner.size()
+    }
+
+    /// Get the current position in the file.
+    #[inline]
+    pub const fn position(&self) -> u32 {
+        self.inner.position() - (self.filled - self.pos) as u32
+    }
+
+    /// Get the number of bytes remaining to read.
+    #[inline]
+    pub const fn remaining(&self) -> u32 {
+        self.size() - self.position()
+    }
+
+    /// Check if we've reached end of file.
+    #[inline]
+    pub const fn is_eof(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Read data into a buffer.
+    ///
+    /// Returns the number of bytes read. Returns 0 at end of file.
+    pub fn read(&mut self, out: &mut [u8]) -> Result<usize> {
+        if self.pos == self.filled && out.len() >= N {
+            return self.inner.read(out);
+        }
+
+        if self.pos == self.filled {
+            self.filled = self.inner.read(&mut self.buf[..])?;
+            self.pos = 0;
+        }
+
+        let n = out.len().min(self.filled - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+
+    /// Seek to a specific position in the file.
+    ///
+    /// Positions inside the buffered data are reached without touching the
+    /// file; otherwise the buffer is discarded and the file reader seeks.
+    pub fn seek(&mut self, position: u32) -> Result<()> {
+        let end = self.inner.position();
+        let start = end - self.filled as u32;
+        if position >= start && position <= end {
+            self.pos = (position - start) as usize;
+            return Ok(());
+        }
+
+        self.inner.seek(position)?;
+        self.pos = 0;
+        self.filled = 0;
+        Ok(())
+    }
+}
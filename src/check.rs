@@ -0,0 +1,152 @@
+//! Volume consistency checking.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::block::FileExtBlock;
+use crate::constants::*;
+use crate::error::{AffsError, Result};
+use crate::reader::AffsReader;
+use crate::types::{BlockDevice, EntryType};
+
+/// Maximum number of issues recorded in a [`CheckReport`].
+pub const MAX_CHECK_ISSUES: usize = 64;
+
+/// A single problem found by [`AffsReader::check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckIssue {
+    /// Block where the problem was found.
+    ///
+    /// This is the entry block for problems with an entry, or the directory
+    /// being scanned if the entry could not be read.
+    pub block: u32,
+    /// What went wrong.
+    pub error: AffsError,
+}
+
+/// Result of a volume consistency check.
+#[derive(Debug, Clone, Default)]
+pub struct CheckReport {
+    /// Number of files (including hard links to files).
+    pub files: u32,
+    /// Number of directories (including hard links to directories).
+    pub dirs: u32,
+    /// Number of soft links.
+    pub symlinks: u32,
+    /// Total number of problems found.
+    pub errors: u32,
+    /// The first [`MAX_CHECK_ISSUES`] problems found.
+    #[cfg(feature = "alloc")]
+    pub issues: Vec<CheckIssue>,
+}
+
+impl CheckReport {
+    /// Returns true if no problems were found.
+    #[inline]
+    pub const fn is_ok(&self) -> bool {
+        self.errors == 0
+    }
+
+    fn record(&mut self, block: u32, error: AffsError) {
+        self.errors += 1;
+        #[cfg(feature = "alloc")]
+        if self.issues.len() < MAX_CHECK_ISSUES {
+            self.issues.push(CheckIssue { block, error });
+        }
+        #[cfg(not(feature = "alloc"))]
+        let _ = (block, error);
+    }
+}
+
+impl<D: BlockDevice + ?Sized> AffsReader<'_, D> {
+    /// Check that the volume is self-consistent.
+    ///
+    /// Walks every directory from the root, parsing each entry block and
+    /// verifying that:
+    /// - block checksums and types are valid,
+    /// - each entry's parent pointer refers to the directory containing it,
+    /// - each file's data block count (the sum of `high_seq` over its header
+    ///   and extension blocks) matches its size.
+    ///
+    /// Problems do not stop the check; they are counted in
+    /// [`CheckReport::errors`] and, with the `alloc` feature, the first
+    /// [`MAX_CHECK_ISSUES`] are recorded in [`CheckReport::issues`].
+    pub fn check(&self) -> Result<CheckReport> {
+        let mut report = CheckReport::default();
+        let mut walk = self.walk(self.root_block());
+
+        while let Some(item) = walk.next() {
+            let dir = walk.current_dir();
+            let entry = match item {
+                Ok((_, entry)) => entry,
+                Err(e) => {
+                    report.record(dir, e);
+                    continue;
+                }
+            };
+
+            if entry.is_symlink() {
+                report.symlinks += 1;
+            } else if entry.is_dir() {
+                report.dirs += 1;
+            } else if entry.is_file() {
+                report.files += 1;
+            }
+
+            if entry.parent != dir {
+                report.record(entry.block, AffsError::InvalidState);
+            }
+
+            let data = match entry.entry_type {
+                EntryType::File => self.check_file_blocks(entry.block),
+                _ => Ok(()),
+            };
+            if let Err(e) = data {
+                report.record(entry.block, e);
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Verify that a file's data block pointers cover its size.
+    fn check_file_blocks(&self, header_block: u32) -> Result<()> {
+        let header = self.read_entry(header_block)?;
+
+        let data_size = self.fs_type().data_block_size() as u64;
+        let expected = (header.byte_size as u64).div_ceil(data_size);
+
+        let mut total = 0u64;
+        let mut high_seq = header.high_seq;
+        let mut next = header.extension;
+        let mut hops = 0u32;
+        let mut buf = [0u8; BLOCK_SIZE];
+
+        loop {
+            if !(0..=MAX_DATABLK as i32).contains(&high_seq) {
+                return Err(AffsError::InvalidDataSequence);
+            }
+            total += high_seq as u64;
+
+            if next == 0 {
+                break;
+            }
+            if next >= self.total_blocks() || hops >= self.total_blocks() {
+                return Err(AffsError::BlockOutOfRange);
+            }
+            hops += 1;
+
+            self.device()
+                .read_block(next, &mut buf)
+                .map_err(|()| AffsError::BlockReadError)?;
+            let ext = FileExtBlock::parse_with(&buf, self.verify_checksums())?;
+            high_seq = ext.high_seq;
+            next = ext.extension;
+        }
+
+        if total != expected {
+            return Err(AffsError::InvalidDataSequence);
+        }
+        Ok(())
+    }
+}
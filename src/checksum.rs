@@ -19,13 +19,13 @@ pub fn normal_sum(buf: &[u8; BLOCK_SIZE], checksum_offset: usize) -> u32 {
 /// Calculate the normal checksum for a variable-size block.
 ///
 /// Used for root blocks, entry blocks, etc. with variable block sizes.
+/// Returns 0 if the buffer length is not a multiple of 4, since no valid
+/// block has such a size.
 #[inline]
 pub fn normal_sum_slice(buf: &[u8], checksum_offset: usize) -> u32 {
-    let len = buf.len();
-    debug_assert!(
-        len.is_multiple_of(4),
-        "Buffer length must be divisible by 4"
-    );
+    if !buf.len().is_multiple_of(4) {
+        return 0;
+    }
     debug_assert!(
         checksum_offset.is_multiple_of(4),
         "Checksum offset must be aligned to 4 bytes"
@@ -235,6 +235,21 @@ pub fn bitmap_sum(buf: &[u8; BLOCK_SIZE]) -> u32 {
     }
 }
 
+/// Verify the normal checksum stored at offset 20 of a block.
+///
+/// This is the checksum used by root, entry, extension, OFS data and
+/// directory cache blocks.
+#[inline]
+pub fn verify_block_checksum(buf: &[u8; BLOCK_SIZE]) -> bool {
+    read_u32_be(buf, 20) == normal_sum(buf, 20)
+}
+
+/// Verify the checksum stored at offset 4 of a boot block.
+#[inline]
+pub fn verify_boot_checksum(buf: &[u8; 1024]) -> bool {
+    u32::from_be_bytes([buf[4], buf[5], buf[6], buf[7]]) == boot_sum(buf)
+}
+
 /// Scalar implementation of bitmap_sum.
 #[inline]
 fn bitmap_sum_scalar(buf: &[u8; BLOCK_SIZE]) -> u32 {
@@ -343,6 +358,40 @@ pub const fn read_u16_be(buf: &[u8; BLOCK_SIZE], offset: usize) -> u16 {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_verify_block_checksum() {
+        let mut buf = [0u8; BLOCK_SIZE];
+        for (i, b) in buf.iter_mut().enumerate() {
+            *b = (i * 7) as u8;
+        }
+        let sum = normal_sum(&buf, 20);
+        buf[20..24].copy_from_slice(&sum.to_be_bytes());
+        assert!(verify_block_checksum(&buf));
+
+        buf[100] ^= 0x01;
+        assert!(!verify_block_checksum(&buf));
+    }
+
+    #[test]
+    fn test_verify_boot_checksum() {
+        let mut buf = [0u8; 1024];
+        buf[0..4].copy_from_slice(b"DOS\x01");
+        buf[12] = 0x4E;
+        let sum = boot_sum(&buf);
+        buf[4..8].copy_from_slice(&sum.to_be_bytes());
+        assert!(verify_boot_checksum(&buf));
+
+        buf[600] ^= 0x80;
+        assert!(!verify_boot_checksum(&buf));
+    }
+
+    #[test]
+    fn test_normal_sum_slice_unaligned_len() {
+        let buf = [0xFFu8; 510];
+        assert_eq!(normal_sum_slice(&buf, 20), 0);
+        assert_eq!(normal_sum_slice(&buf[..3], 0), 0);
+    }
+
     #[test]
     fn test_read_u32_be() {
         let mut buf = [0u8; BLOCK_SIZE];
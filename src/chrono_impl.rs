@@ -0,0 +1,29 @@
+//! Conversions between [`AmigaDate`] and `chrono` types.
+
+use chrono::{DateTime, Utc};
+
+use crate::date::{AmigaDate, DateOutOfRange, NANOS_PER_TICK};
+
+impl From<AmigaDate> for DateTime<Utc> {
+    /// Convert via [`AmigaDate::to_unix_timestamp`], keeping sub-second ticks.
+    ///
+    /// Dates beyond chrono's range saturate to [`DateTime::<Utc>::MAX_UTC`].
+    fn from(date: AmigaDate) -> Self {
+        let nanos = date.ticks.rem_euclid(50) as u32 * NANOS_PER_TICK;
+        DateTime::from_timestamp(date.to_unix_timestamp(), nanos)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+}
+
+impl TryFrom<DateTime<Utc>> for AmigaDate {
+    type Error = DateOutOfRange;
+
+    /// Convert to an Amiga date, truncating to whole ticks.
+    fn try_from(dt: DateTime<Utc>) -> Result<Self, Self::Error> {
+        // Leap seconds report more than 10^9 nanoseconds; keep them in the same second
+        let ticks = (dt.timestamp_subsec_nanos() / NANOS_PER_TICK).min(49) as i32;
+        let mut date = AmigaDate::from_unix_timestamp(dt.timestamp()).ok_or(DateOutOfRange)?;
+        date.ticks += ticks;
+        Ok(date)
+    }
+}
@@ -6,6 +6,9 @@ pub const BLOCK_SIZE: usize = 512;
 /// Boot block size (2 blocks).
 pub const BOOT_BLOCK_SIZE: usize = 1024;
 
+/// Offset of the boot code within the boot block.
+pub const BOOT_CODE_OFFSET: usize = 12;
+
 /// Hash table size (entries per directory).
 pub const HASH_TABLE_SIZE: usize = 72;
 
@@ -27,12 +30,36 @@ pub const BM_PAGES_EXT_SIZE: usize = 127;
 /// Bitmap map entries.
 pub const BM_MAP_SIZE: usize = 127;
 
+/// Number of blocks tracked by a single bitmap block (127 longwords of 32 bits).
+pub const BM_BLOCKS_PER_PAGE: u32 = (BM_MAP_SIZE * 32) as u32;
+
+/// Number of reserved blocks at the start of the volume (boot block).
+///
+/// The allocation bitmap starts tracking at this block number.
+pub const RESERVED_BLOCKS: u32 = 2;
+
 /// Standard floppy disk sector count (DD: 880KB).
 pub const FLOPPY_DD_SECTORS: u32 = 1760;
 
 /// Standard floppy disk sector count (HD: 1.76MB).
 pub const FLOPPY_HD_SECTORS: u32 = 3520;
 
+/// Volume sizes probed by `AffsReader::auto_size`, smallest first.
+///
+/// Floppies followed by common hardfile sizes (5 MB to 500 MB).
+pub const AUTO_SIZE_CANDIDATES: [u32; 10] = [
+    FLOPPY_DD_SECTORS,
+    FLOPPY_HD_SECTORS,
+    5 * 2048,
+    10 * 2048,
+    20 * 2048,
+    40 * 2048,
+    80 * 2048,
+    100 * 2048,
+    200 * 2048,
+    500 * 2048,
+];
+
 /// Sectors per track (DD).
 pub const SECTORS_PER_TRACK_DD: u32 = 11;
 
@@ -54,6 +81,11 @@ pub const DOSFS_FFS: u8 = 1;
 pub const DOSFS_INTL: u8 = 2;
 /// Directory cache mode.
 pub const DOSFS_DIRCACHE: u8 = 4;
+/// Long filename mode (`DOS\6` and `DOS\7`).
+///
+/// This is a value rather than a flag: both INTL and DIRCACHE bits are set,
+/// but the volume uses international hashing and has no directory cache.
+pub const DOSFS_LONGNAME: u8 = 6;
 
 // Block types
 /// Header block type.
@@ -97,6 +129,15 @@ pub const ACC_SCRIPT: u32 = 1 << 6;
 /// Hidden.
 pub const ACC_HOLD: u32 = 1 << 7;
 
+/// Maximum number of hard links followed when resolving a link chain.
+pub const MAX_LINK_DEPTH: usize = 16;
+
+/// Maximum number of parent directories followed when building a path.
+pub const MAX_PATH_DEPTH: usize = 64;
+
+/// Default maximum directory depth for tree walking.
+pub const DEFAULT_WALK_DEPTH: usize = 64;
+
 /// Valid bitmap flag value.
 pub const BM_VALID: i32 = -1;
 
@@ -106,6 +147,10 @@ pub const OFS_DATA_SIZE: usize = 488;
 /// FFS data block payload size (full block).
 pub const FFS_DATA_SIZE: usize = 512;
 
+/// Maximum number of contiguous FFS data blocks fetched with one
+/// [`BlockDevice::read_blocks`](crate::BlockDevice::read_blocks) call.
+pub const MAX_READ_BLOCKS: usize = 8;
+
 // Variable block size constants (GRUB parity)
 /// Maximum log2 block size (512 << 4 = 8192 bytes).
 pub const MAX_LOG_BLOCK_SIZE: u8 = 4;
@@ -1,10 +1,23 @@
 //! Date/time handling for Amiga format.
 
+/// Seconds from the Unix epoch (1970-01-01) to the Amiga epoch (1978-01-01).
+///
+/// 8 years including the leap years 1972 and 1976: 2922 days.
+pub(crate) const AMIGA_EPOCH_UNIX: i64 = 2922 * 86400;
+
+/// Nanoseconds per tick (1/50 second).
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub(crate) const NANOS_PER_TICK: u32 = 20_000_000;
+
 /// Amiga date representation.
 ///
 /// Amiga stores dates as days since January 1, 1978,
 /// minutes since midnight, and ticks (1/50 second).
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+///
+/// Dates are ordered by `(days, mins, ticks)`, which matches chronological
+/// order for dates where [`is_valid`](Self::is_valid) holds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AmigaDate {
     /// Days since January 1, 1978.
     pub days: i32,
@@ -21,6 +34,14 @@ impl AmigaDate {
         Self { days, mins, ticks }
     }
 
+    /// Check that all fields are in range.
+    ///
+    /// Fields must be non-negative, with `mins < 1440` and `ticks < 3000`.
+    #[inline]
+    pub const fn is_valid(self) -> bool {
+        self.days >= 0 && self.mins >= 0 && self.mins < 1440 && self.ticks >= 0 && self.ticks < 3000
+    }
+
     /// Convert to a more usable date format.
     #[inline]
     pub fn to_date_time(self) -> DateTime {
@@ -39,6 +60,12 @@ impl AmigaDate {
         }
     }
 
+    /// Get the day of the week.
+    #[inline]
+    pub const fn weekday(self) -> Weekday {
+        Weekday::from_days(self.days as i64)
+    }
+
     /// Convert to Unix timestamp (seconds since 1970-01-01 00:00:00 UTC).
     ///
     /// This matches GRUB's `aftime2ctime()` behavior:
@@ -51,19 +78,35 @@ impl AmigaDate {
         const SECONDS_PER_DAY: i64 = 86400;
         const SECONDS_PER_MINUTE: i64 = 60;
         const TICKS_PER_SECOND: i64 = 50;
-        // 8 years from 1970 to 1978 (including leap years 1972, 1976)
-        // = 365 * 8 + 2 = 2922 days
-        const EPOCH_OFFSET: i64 = 2922 * SECONDS_PER_DAY;
 
         (self.days as i64) * SECONDS_PER_DAY
             + (self.mins as i64) * SECONDS_PER_MINUTE
             + (self.ticks as i64) / TICKS_PER_SECOND
-            + EPOCH_OFFSET
+            + AMIGA_EPOCH_UNIX
+    }
+
+    /// Build a date from a Unix timestamp (the inverse of
+    /// [`to_unix_timestamp`](Self::to_unix_timestamp)).
+    ///
+    /// Returns `None` for instants before 1978-01-01 or past the last
+    /// representable day.
+    pub const fn from_unix_timestamp(secs: i64) -> Option<Self> {
+        let secs = secs - AMIGA_EPOCH_UNIX;
+        if secs < 0 || secs / 86400 > i32::MAX as i64 {
+            return None;
+        }
+        let rem = (secs % 86400) as i32;
+        Some(Self {
+            days: (secs / 86400) as i32,
+            mins: rem / 60,
+            ticks: (rem % 60) * 50,
+        })
     }
 }
 
 /// Decoded date and time.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DateTime {
     /// Year (e.g., 1978-2100).
     pub year: u16,
@@ -79,11 +122,162 @@ pub struct DateTime {
     pub second: u8,
 }
 
+impl DateTime {
+    /// Length of the text written by [`fmt_iso8601`](Self::fmt_iso8601).
+    pub const ISO8601_LEN: usize = 19;
+
+    /// Write the date as `YYYY-MM-DDTHH:MM:SS` into `out`.
+    ///
+    /// Years past 9999 are clamped to 9999 so the output is always 19
+    /// ASCII bytes. If `out` is shorter, the text is truncated. Returns
+    /// the number of bytes written.
+    pub fn fmt_iso8601(&self, out: &mut [u8]) -> usize {
+        let year = self.year.min(9999);
+        let mut text = [0u8; Self::ISO8601_LEN];
+        write_digits(&mut text[0..4], year);
+        text[4] = b'-';
+        write_digits(&mut text[5..7], self.month as u16);
+        text[7] = b'-';
+        write_digits(&mut text[8..10], self.day as u16);
+        text[10] = b'T';
+        write_digits(&mut text[11..13], self.hour as u16);
+        text[13] = b':';
+        write_digits(&mut text[14..16], self.minute as u16);
+        text[16] = b':';
+        write_digits(&mut text[17..19], self.second as u16);
+
+        let len = out.len().min(Self::ISO8601_LEN);
+        out[..len].copy_from_slice(&text[..len]);
+        len
+    }
+}
+
+impl DateTime {
+    /// Get the day of the week.
+    #[inline]
+    pub const fn weekday(&self) -> Weekday {
+        Weekday::from_days(days_from_civil(self.year, self.month, self.day))
+    }
+}
+
+impl core::fmt::Display for DateTime {
+    /// Formats as ISO 8601, see [`DateTime::fmt_iso8601`].
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut text = [0u8; Self::ISO8601_LEN];
+        let len = self.fmt_iso8601(&mut text);
+        // Always ASCII
+        f.write_str(core::str::from_utf8(&text[..len]).map_err(|_| core::fmt::Error)?)
+    }
+}
+
+/// Error returned when an instant cannot be stored as an [`AmigaDate`].
+///
+/// Amiga dates cannot represent instants before 1978-01-01.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateOutOfRange;
+
+impl core::fmt::Display for DateOutOfRange {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("date out of Amiga range")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DateOutOfRange {}
+
+/// Day of the week.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Weekday {
+    /// Monday.
+    Mon,
+    /// Tuesday.
+    Tue,
+    /// Wednesday.
+    Wed,
+    /// Thursday.
+    Thu,
+    /// Friday.
+    Fri,
+    /// Saturday.
+    Sat,
+    /// Sunday.
+    Sun,
+}
+
+impl Weekday {
+    /// Weekday for a day count since 1978-01-01, which was a Sunday.
+    const fn from_days(days: i64) -> Self {
+        match days.rem_euclid(7) {
+            0 => Self::Sun,
+            1 => Self::Mon,
+            2 => Self::Tue,
+            3 => Self::Wed,
+            4 => Self::Thu,
+            5 => Self::Fri,
+            _ => Self::Sat,
+        }
+    }
+
+    /// Full English name of the day.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Mon => "Monday",
+            Self::Tue => "Tuesday",
+            Self::Wed => "Wednesday",
+            Self::Thu => "Thursday",
+            Self::Fri => "Friday",
+            Self::Sat => "Saturday",
+            Self::Sun => "Sunday",
+        }
+    }
+}
+
+impl core::fmt::Display for Weekday {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Convert a calendar date to days since 1978-01-01.
+const fn days_from_civil(year: u16, month: u8, day: u8) -> i64 {
+    // Count from March so the leap day falls at the end of the year
+    let y = year as i64 - if month <= 2 { 1 } else { 0 };
+    let m = month as i64;
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = if m > 2 { m - 3 } else { m + 9 };
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    // 1978-01-01 is day 2922 after 1970-01-01, which is day 719468 after 0000-03-01
+    era * 146097 + doe - 719468 - 2922
+}
+
+/// Write `value` as zero-padded decimal filling `out`.
+///
+/// Values with more digits than `out` keep only the low digits.
+fn write_digits(out: &mut [u8], mut value: u16) {
+    for byte in out.iter_mut().rev() {
+        *byte = b'0' + (value % 10) as u8;
+        value /= 10;
+    }
+}
+
 /// Convert days since 1978-01-01 to (year, month, day).
-fn days_to_date(mut days: i32) -> (u16, u8, u8) {
+///
+/// Negative day counts (seen on corrupt disks) clamp to the epoch, and
+/// dates past the year 65535 saturate to 65535-12-31.
+fn days_to_date(days: i32) -> (u16, u8, u8) {
     const DAYS_IN_MONTH: [i32; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    /// Days in a 400-year Gregorian cycle.
+    const DAYS_PER_CYCLE: i32 = 146_097;
 
-    let mut year = 1978u16;
+    let mut days = days.max(0);
+
+    // Skip whole 400-year cycles so the year loop runs at most 400 times
+    let cycles = days / DAYS_PER_CYCLE;
+    days %= DAYS_PER_CYCLE;
+    let mut year = 1978u32 + cycles as u32 * 400;
 
     // Find year
     loop {
@@ -95,6 +289,10 @@ fn days_to_date(mut days: i32) -> (u16, u8, u8) {
         year += 1;
     }
 
+    if year > u16::MAX as u32 {
+        return (u16::MAX, 12, 31);
+    }
+
     // Find month
     let mut month = 1u8;
     let leap = is_leap_year(year);
@@ -107,12 +305,12 @@ fn days_to_date(mut days: i32) -> (u16, u8, u8) {
         month += 1;
     }
 
-    (year, month, (days + 1) as u8)
+    (year as u16, month, (days + 1) as u8)
 }
 
 /// Check if a year is a leap year.
 #[inline]
-const fn is_leap_year(year: u16) -> bool {
+const fn is_leap_year(year: u32) -> bool {
     if year.is_multiple_of(100) {
         year.is_multiple_of(400)
     } else {
@@ -155,6 +353,182 @@ mod tests {
         assert_eq!(dt.second, 3);
     }
 
+    #[test]
+    fn test_ordering() {
+        let older = AmigaDate::new(100, 1439, 2999);
+        let newer = AmigaDate::new(101, 0, 0);
+        assert!(older < newer);
+        assert!(AmigaDate::new(5, 10, 1) < AmigaDate::new(5, 10, 2));
+        assert!(AmigaDate::new(5, 9, 2999) < AmigaDate::new(5, 10, 0));
+        assert_eq!(older.max(newer), newer);
+
+        let mut dates = [
+            AmigaDate::new(7000, 60, 0),
+            AmigaDate::new(0, 0, 0),
+            AmigaDate::new(7000, 30, 100),
+            AmigaDate::new(6988, 0, 0),
+        ];
+        dates.sort();
+        assert_eq!(
+            dates,
+            [
+                AmigaDate::new(0, 0, 0),
+                AmigaDate::new(6988, 0, 0),
+                AmigaDate::new(7000, 30, 100),
+                AmigaDate::new(7000, 60, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_valid() {
+        assert!(AmigaDate::new(0, 0, 0).is_valid());
+        assert!(AmigaDate::new(6988, 1439, 2999).is_valid());
+        assert!(!AmigaDate::new(-1, 0, 0).is_valid());
+        assert!(!AmigaDate::new(0, 1440, 0).is_valid());
+        assert!(!AmigaDate::new(0, -1, 0).is_valid());
+        assert!(!AmigaDate::new(0, 0, 3000).is_valid());
+        assert!(!AmigaDate::new(0, 0, -1).is_valid());
+    }
+
+    #[test]
+    fn test_iso8601_epoch() {
+        let dt = AmigaDate::new(0, 0, 0).to_date_time();
+        let mut out = [0u8; 32];
+        let len = dt.fmt_iso8601(&mut out);
+        assert_eq!(len, 19);
+        assert_eq!(&out[..len], b"1978-01-01T00:00:00");
+    }
+
+    #[test]
+    fn test_iso8601_known_date() {
+        // 2001-02-03 is day 8434
+        let dt = AmigaDate::new(8434, 4 * 60 + 5, 6 * 50).to_date_time();
+        let mut out = [0u8; 19];
+        assert_eq!(dt.fmt_iso8601(&mut out), 19);
+        assert_eq!(&out, b"2001-02-03T04:05:06");
+    }
+
+    #[test]
+    fn test_iso8601_clamps_year() {
+        let dt = DateTime {
+            year: 12345,
+            month: 12,
+            day: 31,
+            hour: 23,
+            minute: 59,
+            second: 59,
+        };
+        let mut out = [0u8; 19];
+        dt.fmt_iso8601(&mut out);
+        assert_eq!(&out, b"9999-12-31T23:59:59");
+    }
+
+    #[test]
+    fn test_iso8601_short_buffer() {
+        let dt = AmigaDate::new(0, 0, 0).to_date_time();
+        let mut out = [0u8; 4];
+        assert_eq!(dt.fmt_iso8601(&mut out), 4);
+        assert_eq!(&out, b"1978");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_display() {
+        let dt = AmigaDate::new(8434, 4 * 60 + 5, 6 * 50).to_date_time();
+        assert_eq!(std::format!("{dt}"), "2001-02-03T04:05:06");
+    }
+
+    #[test]
+    fn test_weekday_epoch() {
+        // 1978-01-01 was a Sunday
+        assert_eq!(AmigaDate::new(0, 0, 0).weekday(), Weekday::Sun);
+        assert_eq!(
+            AmigaDate::new(0, 0, 0).to_date_time().weekday(),
+            Weekday::Sun
+        );
+        assert_eq!(AmigaDate::new(1, 0, 0).weekday(), Weekday::Mon);
+    }
+
+    #[test]
+    fn test_weekday_known_dates() {
+        // 1997-02-18 was a Tuesday
+        let date = AmigaDate::new(6988, 0, 0);
+        assert_eq!(date.weekday(), Weekday::Tue);
+        assert_eq!(date.to_date_time().weekday(), Weekday::Tue);
+
+        // 2000-02-29 was a Tuesday
+        let dt = DateTime {
+            year: 2000,
+            month: 2,
+            day: 29,
+            ..DateTime::default()
+        };
+        assert_eq!(dt.weekday(), Weekday::Tue);
+
+        // 2001-02-03 was a Saturday
+        assert_eq!(AmigaDate::new(8434, 0, 0).weekday(), Weekday::Sat);
+        // The day before the epoch was a Saturday
+        assert_eq!(AmigaDate::new(-1, 0, 0).weekday(), Weekday::Sat);
+    }
+
+    #[test]
+    fn test_days_from_civil_roundtrip() {
+        for days in [0, 59, 365, 788, 6988, 8434, 20000] {
+            let dt = AmigaDate::new(days, 0, 0).to_date_time();
+            assert_eq!(days_from_civil(dt.year, dt.month, dt.day), days as i64);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_weekday_display() {
+        assert_eq!(std::format!("{}", Weekday::Sun), "Sunday");
+        assert_eq!(std::format!("{}", Weekday::Wed), "Wednesday");
+    }
+
+    #[test]
+    fn test_negative_days_clamp_to_epoch() {
+        let dt = AmigaDate::new(-5, 0, 0).to_date_time();
+        assert_eq!((dt.year, dt.month, dt.day), (1978, 1, 1));
+        let dt = AmigaDate::new(i32::MIN, 0, 0).to_date_time();
+        assert_eq!((dt.year, dt.month, dt.day), (1978, 1, 1));
+    }
+
+    #[test]
+    fn test_huge_days_saturate() {
+        let dt = AmigaDate::new(i32::MAX, 0, 0).to_date_time();
+        assert_eq!((dt.year, dt.month, dt.day), (u16::MAX, 12, 31));
+    }
+
+    #[test]
+    fn test_days_across_cycles() {
+        // 400 Gregorian years later lands on the same calendar date
+        let dt = AmigaDate::new(6988 + 146_097, 0, 0).to_date_time();
+        assert_eq!((dt.year, dt.month, dt.day), (2397, 2, 18));
+        // Last representable day
+        let last = days_from_civil(u16::MAX, 12, 31) as i32;
+        let dt = AmigaDate::new(last, 0, 0).to_date_time();
+        assert_eq!((dt.year, dt.month, dt.day), (u16::MAX, 12, 31));
+        let dt = AmigaDate::new(last - 364, 0, 0).to_date_time();
+        assert_eq!((dt.year, dt.month, dt.day), (u16::MAX, 1, 1));
+    }
+
+    #[test]
+    fn test_unix_timestamp_roundtrip() {
+        let date = AmigaDate::new(6988, 754, 150);
+        assert_eq!(
+            AmigaDate::from_unix_timestamp(date.to_unix_timestamp()),
+            Some(date)
+        );
+        assert_eq!(
+            AmigaDate::from_unix_timestamp(AMIGA_EPOCH_UNIX),
+            Some(AmigaDate::new(0, 0, 0))
+        );
+        assert_eq!(AmigaDate::from_unix_timestamp(AMIGA_EPOCH_UNIX - 1), None);
+        assert_eq!(AmigaDate::from_unix_timestamp(i64::MAX), None);
+    }
+
     #[test]
     fn test_leap_year() {
         assert!(is_leap_year(2000));
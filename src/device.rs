@@ -0,0 +1,272 @@
+//! Ready-made block device implementations.
+
+use crate::constants::BLOCK_SIZE;
+use crate::types::BlockDevice;
+
+#[cfg(feature = "std")]
+use core::cell::RefCell;
+#[cfg(feature = "std")]
+use std::fs::File;
+#[cfg(feature = "std")]
+use std::io::{self, Read, Seek, SeekFrom};
+#[cfg(feature = "std")]
+use std::path::Path;
+
+/// Block device backed by an in-memory byte slice.
+///
+/// Blocks are read from `block * 512`. A trailing partial block is
+/// returned zero-filled; blocks past the end of the slice fail.
+///
+/// # Example
+///
+/// ```ignore
+/// use affs_read::{AffsReader, SliceDevice};
+///
+/// let data = std::fs::read("disk.adf")?;
+/// let device = SliceDevice::new(&data);
+/// let reader = AffsReader::new(&device)?;
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SliceDevice<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> SliceDevice<'a> {
+    /// Create a device over the given image data.
+    #[inline]
+    pub const fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Get the underlying image data.
+    #[inline]
+    pub const fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Number of readable blocks, including a trailing partial block.
+    #[inline]
+    pub const fn num_blocks(&self) -> u32 {
+        self.data.len().div_ceil(BLOCK_SIZE) as u32
+    }
+}
+
+impl BlockDevice for SliceDevice<'_> {
+    fn read_block(&self, block: u32, buf: &mut [u8; 512]) -> Result<(), ()> {
+        let offset = (block as usize).checked_mul(BLOCK_SIZE).ok_or(())?;
+        if offset >= self.data.len() {
+            return Err(());
+        }
+
+        let available = (self.data.len() - offset).min(BLOCK_SIZE);
+        buf[..available].copy_from_slice(&self.data[offset..offset + available]);
+        // Zero-fill a short final block
+        buf[available..].fill(0);
+        Ok(())
+    }
+}
+
+/// Block device that views another device starting at a block offset.
+///
+/// Block `n` is read from block `offset + n` of the inner device, so a
+/// volume inside a partition can be mounted as if it started at block 0.
+///
+/// # Example
+///
+/// ```ignore
+/// use affs_read::{AffsReader, OffsetDevice, SliceDevice};
+///
+/// let disk = SliceDevice::new(&image);
+/// let partition = OffsetDevice::new(&disk, 2 * 11 * 80);
+/// let reader = AffsReader::new(&partition)?;
+/// ```
+#[derive(Debug)]
+pub struct OffsetDevice<'a, D: BlockDevice + ?Sized> {
+    inner: &'a D,
+    offset: u32,
+}
+
+impl<'a, D: BlockDevice + ?Sized> OffsetDevice<'a, D> {
+    /// Create a device whose block 0 is block `offset` of `inner`.
+    #[inline]
+    pub const fn new(inner: &'a D, offset: u32) -> Self {
+        Self { inner, offset }
+    }
+
+    /// Get the underlying device.
+    #[inline]
+    pub const fn inner(&self) -> &'a D {
+        self.inner
+    }
+
+    /// Get the block offset.
+    #[inline]
+    pub const fn offset(&self) -> u32 {
+        self.offset
+    }
+}
+
+impl<D: BlockDevice + ?Sized> Clone for OffsetDevice<'_, D> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D: BlockDevice + ?Sized> Copy for OffsetDevice<'_, D> {}
+
+impl<D: BlockDevice + ?Sized> BlockDevice for OffsetDevice<'_, D> {
+    fn read_block(&self, block: u32, buf: &mut [u8; 512]) -> Result<(), ()> {
+        let block = self.offset.checked_add(block).ok_or(())?;
+        self.inner.read_block(block, buf)
+    }
+
+    fn read_blocks(&self, start: u32, bufs: &mut [[u8; 512]]) -> Result<(), ()> {
+        let start = self.offset.checked_add(start).ok_or(())?;
+        self.inner.read_blocks(start, bufs)
+    }
+
+    fn prefetch(&self, block: u32) {
+        if let Some(block) = self.offset.checked_add(block) {
+            self.inner.prefetch(block);
+        }
+    }
+}
+
+/// Block device backed by an image file on disk.
+///
+/// Each read seeks to `block * 512` and reads exactly one block.
+/// Short reads and IO errors are reported as `Err(())`.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct FileDevice {
+    file: RefCell<File>,
+}
+
+#[cfg(feature = "std")]
+impl FileDevice {
+    /// Open an image file for reading.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        File::open(path).map(Self::new)
+    }
+
+    /// Wrap an already opened file.
+    #[inline]
+    pub fn new(file: File) -> Self {
+        Self {
+            file: RefCell::new(file),
+        }
+    }
+
+    /// Number of whole blocks in the file.
+    pub fn num_blocks(&self) -> io::Result<u32> {
+        let len = self.file.borrow().metadata()?.len();
+        Ok((len / BLOCK_SIZE as u64).min(u32::MAX as u64) as u32)
+    }
+
+    /// Consume the device, returning the underlying file.
+    #[inline]
+    pub fn into_inner(self) -> File {
+        self.file.into_inner()
+    }
+}
+
+#[cfg(feature = "std")]
+impl BlockDevice for FileDevice {
+    fn read_block(&self, block: u32, buf: &mut [u8; 512]) -> Result<(), ()> {
+        let mut file = self.file.try_borrow_mut().map_err(|_| ())?;
+        file.seek(SeekFrom::Start(block as u64 * BLOCK_SIZE as u64))
+            .map_err(|_| ())?;
+        file.read_exact(buf).map_err(|_| ())
+    }
+}
+
+/// Block device over any seekable reader.
+///
+/// Each read seeks to `block * 512` and reads one block from the
+/// wrapped source. This suits in-memory sources such as
+/// [`std::io::Cursor`]; on sources where seeking is expensive (pipes
+/// buffered to disk, network-backed readers) random block access can be
+/// slow, so prefer [`SliceDevice`] when the whole image fits in memory.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct IoDevice<R: Read + Seek> {
+    inner: RefCell<R>,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read + Seek> IoDevice<R> {
+    /// Wrap a seekable reader.
+    #[inline]
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner: RefCell::new(inner),
+        }
+    }
+
+    /// Consume the device, returning the underlying reader.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner.into_inner()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read + Seek> BlockDevice for IoDevice<R> {
+    fn read_block(&self, block: u32, buf: &mut [u8; 512]) -> Result<(), ()> {
+        let mut inner = self.inner.try_borrow_mut().map_err(|_| ())?;
+        inner
+            .seek(SeekFrom::Start(block as u64 * BLOCK_SIZE as u64))
+            .map_err(|_| ())?;
+        inner.read_exact(buf).map_err(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slice_device_in_range() {
+        let mut data = [0u8; 1024];
+        data[512] = 0xAB;
+        data[1023] = 0xCD;
+        let device = SliceDevice::new(&data);
+        assert_eq!(device.num_blocks(), 2);
+
+        let mut buf = [0xFFu8; 512];
+        device.read_block(0, &mut buf).unwrap();
+        assert!(buf.iter().all(|&b| b == 0));
+        device.read_block(1, &mut buf).unwrap();
+        assert_eq!(buf[0], 0xAB);
+        assert_eq!(buf[511], 0xCD);
+    }
+
+    #[test]
+    fn test_slice_device_out_of_range() {
+        let data = [0u8; 1024];
+        let device = SliceDevice::new(&data);
+        let mut buf = [0u8; 512];
+        assert!(device.read_block(2, &mut buf).is_err());
+        assert!(device.read_block(u32::MAX, &mut buf).is_err());
+    }
+
+    #[test]
+    fn test_slice_device_partial_block() {
+        let data = [0x11u8; 600];
+        let device = SliceDevice::new(&data);
+        assert_eq!(device.num_blocks(), 2);
+
+        let mut buf = [0xFFu8; 512];
+        device.read_block(1, &mut buf).unwrap();
+        assert!(buf[..88].iter().all(|&b| b == 0x11));
+        assert!(buf[88..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_slice_device_empty() {
+        let device = SliceDevice::new(&[]);
+        assert_eq!(device.num_blocks(), 0);
+        let mut buf = [0u8; 512];
+        assert!(device.read_block(0, &mut buf).is_err());
+    }
+}
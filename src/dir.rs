@@ -1,10 +1,14 @@
 //! Directory traversal.
 
-use crate::block::{EntryBlock, hash_name, names_equal};
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, string::String};
+
+use crate::block::{DirCacheBlock, DirCacheRecord, EntryBlock, glob_match, hash_name, names_equal};
+use crate::checksum::read_u32_be;
 use crate::constants::*;
 use crate::date::AmigaDate;
 use crate::error::{AffsError, Result};
-use crate::types::{Access, BlockDevice, EntryType};
+use crate::types::{Access, BlockDevice, EntryType, FsFlags};
 
 /// Directory entry information.
 #[derive(Debug, Clone)]
@@ -61,6 +65,31 @@ impl DirEntry {
         })
     }
 
+    /// Create from a directory cache record.
+    pub(crate) fn from_cache_record(record: &DirCacheRecord<'_>, parent: u32) -> Option<Self> {
+        let entry_type = record.entry_type()?;
+
+        let mut name = [0u8; MAX_NAME_LEN];
+        name[..record.name.len()].copy_from_slice(record.name);
+
+        let mut comment = [0u8; MAX_COMMENT_LEN];
+        comment[..record.comment.len()].copy_from_slice(record.comment);
+
+        Some(Self {
+            name,
+            name_len: record.name.len() as u8,
+            entry_type,
+            block: record.header,
+            parent,
+            size: record.size,
+            access: Access::new(record.protect),
+            date: record.date,
+            real_entry: 0,
+            comment,
+            comment_len: record.comment.len() as u8,
+        })
+    }
+
     /// Get entry name as byte slice.
     #[inline]
     pub fn name(&self) -> &[u8] {
@@ -73,6 +102,23 @@ impl DirEntry {
         crate::utf8::from_utf8(self.name())
     }
 
+    /// Get the hash table bucket this entry's name hashes to.
+    #[inline]
+    pub fn hash_bucket(&self, intl: bool) -> usize {
+        hash_name(self.name(), intl)
+    }
+
+    /// Convert the entry name from Latin-1 to UTF-8.
+    ///
+    /// Writes into `out` and returns the number of bytes written. The output
+    /// is at most `2 * name_len` bytes, so a buffer of
+    /// `max_utf8_len(MAX_NAME_LEN)` always suffices; shorter buffers are
+    /// truncated at a character boundary.
+    #[inline]
+    pub fn name_utf8(&self, out: &mut [u8]) -> usize {
+        crate::utf8::latin1_to_utf8(self.name(), out)
+    }
+
     /// Get comment as byte slice.
     #[inline]
     pub fn comment(&self) -> &[u8] {
@@ -85,6 +131,16 @@ impl DirEntry {
         crate::utf8::from_utf8(self.comment())
     }
 
+    /// Convert the comment from Latin-1 to UTF-8.
+    ///
+    /// Writes into `out` and returns the number of bytes written. The output
+    /// is at most `2 * comment_len` bytes, so a buffer of
+    /// `max_utf8_len(MAX_COMMENT_LEN)` always suffices.
+    #[inline]
+    pub fn comment_utf8(&self, out: &mut [u8]) -> usize {
+        crate::utf8::latin1_to_utf8(self.comment(), out)
+    }
+
     /// Check if this is a directory.
     #[inline]
     pub const fn is_dir(&self) -> bool {
@@ -102,35 +158,147 @@ impl DirEntry {
     pub const fn is_symlink(&self) -> bool {
         matches!(self.entry_type, EntryType::SoftLink)
     }
+
+    /// Check if this is a hard link to a file or directory.
+    ///
+    /// Hard links also count as files or directories for
+    /// [`is_file`](Self::is_file) and [`is_dir`](Self::is_dir).
+    #[inline]
+    pub const fn is_hardlink(&self) -> bool {
+        matches!(
+            self.entry_type,
+            EntryType::HardLinkFile | EntryType::HardLinkDir
+        )
+    }
+
+    /// Get the header block a hard link points to.
+    ///
+    /// Returns `None` for entries that are not hard links. The target may
+    /// itself be a link; see [`AffsReader::resolve_link`](crate::AffsReader::resolve_link).
+    #[inline]
+    pub const fn link_target(&self) -> Option<u32> {
+        if self.is_hardlink() {
+            Some(self.real_entry)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl DirEntry {
+    /// Get the entry name as a string for display.
+    ///
+    /// Borrows the name if it is valid UTF-8 (which includes plain ASCII).
+    /// Otherwise the name is decoded as Latin-1 into a new `String`.
+    pub fn name_cow(&self) -> Cow<'_, str> {
+        match self.name_str() {
+            Some(name) => Cow::Borrowed(name),
+            None => Cow::Owned(self.name().iter().map(|&b| b as char).collect::<String>()),
+        }
+    }
 }
 
 /// Iterator over directory entries.
 ///
 /// This iterator reads entries lazily from the hash table.
-pub struct DirIter<'a, D: BlockDevice> {
+pub struct DirIter<'a, D: BlockDevice + ?Sized> {
     device: &'a D,
     hash_table: [u32; HASH_TABLE_SIZE],
     hash_index: usize,
     current_chain: u32,
     intl: bool,
+    long_names: bool,
+    verify_checksums: bool,
     buf: [u8; BLOCK_SIZE],
 }
 
-impl<'a, D: BlockDevice> DirIter<'a, D> {
+impl<'a, D: BlockDevice + ?Sized> DirIter<'a, D> {
     /// Create a new directory iterator.
-    pub(crate) fn new(device: &'a D, hash_table: [u32; HASH_TABLE_SIZE], intl: bool) -> Self {
+    pub(crate) fn new(
+        device: &'a D,
+        hash_table: [u32; HASH_TABLE_SIZE],
+        intl: bool,
+        long_names: bool,
+        verify_checksums: bool,
+    ) -> Self {
         Self {
             device,
             hash_table,
             hash_index: 0,
             current_chain: 0,
             intl,
+            long_names,
+            verify_checksums,
             buf: [0u8; BLOCK_SIZE],
         }
     }
 
+    /// Keep only file entries (including hard links to files).
+    ///
+    /// Read errors are still yielded.
+    #[inline]
+    pub fn files(self) -> Files<'a, D> {
+        Files { inner: self }
+    }
+
+    /// Keep only directory entries (including hard links to directories).
+    ///
+    /// Read errors are still yielded.
+    #[inline]
+    pub fn dirs(self) -> Dirs<'a, D> {
+        Dirs { inner: self }
+    }
+
+    /// Yield malformed entries as errors.
+    ///
+    /// By default, entries with an unknown secondary type or an empty name
+    /// are skipped. The returned iterator yields `AffsError::InvalidSecType`
+    /// or `AffsError::EmptyName` for each one instead, so corruption is
+    /// not hidden. Both continue along the hash chain past such entries.
+    #[inline]
+    pub fn strict(self) -> StrictDirIter<'a, D> {
+        StrictDirIter { inner: self }
+    }
+
+    /// Count the remaining entries, consuming the iterator.
+    ///
+    /// Returns the first error encountered instead of counting it.
+    pub fn count_entries(self) -> Result<usize> {
+        let mut count = 0;
+        for entry in self {
+            entry?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Advance to the next entry accepted by `keep`, passing errors through.
+    fn next_matching(&mut self, keep: impl Fn(&DirEntry) -> bool) -> Option<Result<DirEntry>> {
+        loop {
+            match self.next()? {
+                Ok(entry) if !keep(&entry) => continue,
+                result => return Some(result),
+            }
+        }
+    }
+
     /// Find an entry by name in this directory.
-    pub fn find(mut self, name: &[u8]) -> Result<DirEntry> {
+    pub fn find(self, name: &[u8]) -> Result<DirEntry> {
+        let intl = self.intl;
+        self.find_by(name, |candidate| names_equal(candidate, name, intl))
+    }
+
+    /// Find an entry whose name matches `name` byte-for-byte.
+    ///
+    /// Unlike [`find`](Self::find), this does not fold case, so `File`
+    /// and `file` are distinct. The lookup still walks only the hash
+    /// chain for `name`, since hashing is case-insensitive.
+    pub fn find_exact(self, name: &[u8]) -> Result<DirEntry> {
+        self.find_by(name, |candidate| candidate == name)
+    }
+
+    fn find_by(mut self, name: &[u8], matches: impl Fn(&[u8]) -> bool) -> Result<DirEntry> {
         if name.len() > MAX_NAME_LEN {
             return Err(AffsError::NameTooLong);
         }
@@ -143,42 +311,67 @@ impl<'a, D: BlockDevice> DirIter<'a, D> {
                 .read_block(block, &mut self.buf)
                 .map_err(|()| AffsError::BlockReadError)?;
 
-            let entry = EntryBlock::parse(&self.buf)?;
-
-            if names_equal(entry.name(), name, self.intl) {
-                return DirEntry::from_entry_block(block, &entry).ok_or(AffsError::InvalidSecType);
+            // Step past entries that cannot match, as the iterator does
+            let (result, next) =
+                parse_chain_entry(block, &self.buf, self.verify_checksums, self.long_names);
+            match result {
+                Ok(entry) if matches(entry.name()) => return Ok(entry),
+                Err(e) if !keeps_chain(e) => return Err(e),
+                _ => {}
             }
 
-            block = entry.next_same_hash;
+            block = next;
         }
 
         Err(AffsError::EntryNotFound)
     }
 }
 
-impl<D: BlockDevice> Iterator for DirIter<'_, D> {
+impl<D: BlockDevice + ?Sized> Iterator for DirIter<'_, D> {
     type Item = Result<DirEntry>;
 
+    #[inline]
     fn next(&mut self) -> Option<Self::Item> {
+        self.next_entry(false)
+    }
+
+    /// Hash chains can be arbitrarily long (or cyclic on a damaged disk),
+    /// so no upper bound is known until every chain has been visited.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = self.hash_table[self.hash_index..].iter().any(|&b| b != 0);
+        if self.current_chain == 0 && !pending {
+            (0, Some(0))
+        } else {
+            (0, None)
+        }
+    }
+}
+
+impl<D: BlockDevice + ?Sized> DirIter<'_, D> {
+    /// Advance to the next entry.
+    ///
+    /// Entries rejected by [`EntryBlock::parse`] for their contents are
+    /// skipped, or yielded as errors when `strict` is set. Either way the
+    /// rest of their hash chain is still visited.
+    fn next_entry(&mut self, strict: bool) -> Option<Result<DirEntry>> {
         loop {
             // If we're in a hash chain, continue it
             if self.current_chain != 0 {
-                let result = self.device.read_block(self.current_chain, &mut self.buf);
+                let block = self.current_chain;
+                // Abandon the chain on error so iteration always makes progress
+                self.current_chain = 0;
+
+                let result = self.device.read_block(block, &mut self.buf);
                 if result.is_err() {
                     return Some(Err(AffsError::BlockReadError));
                 }
 
-                match EntryBlock::parse(&self.buf) {
-                    Ok(entry) => {
-                        let block = self.current_chain;
-                        self.current_chain = entry.next_same_hash;
-
-                        match DirEntry::from_entry_block(block, &entry) {
-                            Some(dir_entry) => return Some(Ok(dir_entry)),
-                            None => continue, // Skip invalid entries
-                        }
-                    }
-                    Err(e) => return Some(Err(e)),
+                let (result, next) =
+                    parse_chain_entry(block, &self.buf, self.verify_checksums, self.long_names);
+                self.current_chain = next;
+                match result {
+                    Err(e) if is_rejected_entry(e) && !strict => continue,
+                    result => return Some(result),
                 }
             }
 
@@ -201,6 +394,359 @@ impl<D: BlockDevice> Iterator for DirIter<'_, D> {
     }
 }
 
+/// Parse one block of a hash chain into a directory entry.
+///
+/// Returns the entry and the next block in the chain. A block with a valid
+/// type and checksum that fails for its contents (see [`keeps_chain`])
+/// still links to the rest of the chain, so its `next_same_hash` is
+/// returned; after any other error the chain cannot be trusted and 0 is
+/// returned.
+pub(crate) fn parse_chain_entry(
+    block: u32,
+    buf: &[u8; BLOCK_SIZE],
+    verify_checksums: bool,
+    long_names: bool,
+) -> (Result<DirEntry>, u32) {
+    let flags = FsFlags {
+        long_names,
+        ..FsFlags::default()
+    };
+    let result = EntryBlock::parse_with_flags(buf, verify_checksums, flags).and_then(|entry| {
+        DirEntry::from_entry_block(block, &entry).ok_or(AffsError::InvalidSecType)
+    });
+    let next = match result {
+        Ok(_) => read_u32_be(buf, 0x1F0),
+        Err(e) if keeps_chain(e) => read_u32_be(buf, 0x1F0),
+        Err(_) => 0,
+    };
+    (result, next)
+}
+
+/// Check if an entry parse error is about the entry's contents.
+///
+/// These are the unknown secondary type and empty name checks of
+/// [`EntryBlock::parse`], which directory iterators skip by default.
+#[inline]
+pub(crate) const fn is_rejected_entry(error: AffsError) -> bool {
+    matches!(error, AffsError::InvalidSecType | AffsError::EmptyName)
+}
+
+/// Check if an entry parse error still leaves its hash chain intact.
+///
+/// Besides [`is_rejected_entry`] errors, this is the case for
+/// `AffsError::NameTooLong` from a long filename volume: the block is
+/// sound, but its name does not fit in a [`DirEntry`]. Lookups step past
+/// both, since no name they accept can match.
+#[inline]
+pub(crate) const fn keeps_chain(error: AffsError) -> bool {
+    is_rejected_entry(error) || matches!(error, AffsError::NameTooLong)
+}
+
+/// Iterator over directory entries read from a DIRCACHE chain.
+///
+/// Entries are built from the packed cache records, so `real_entry` is
+/// always 0.
+pub struct DirCacheIter<'a, D: BlockDevice + ?Sized> {
+    device: &'a D,
+    /// Directory the cache belongs to.
+    dir_block: u32,
+    /// Next cache block to load (0 if none).
+    next_block: u32,
+    /// Total blocks on device (bounds the chain length).
+    total_blocks: u32,
+    /// Number of cache blocks loaded so far.
+    hops: u32,
+    /// Records left in the current block.
+    remaining: u32,
+    /// Offset of the next record within the record area.
+    offset: usize,
+    verify_checksums: bool,
+    buf: [u8; BLOCK_SIZE],
+}
+
+impl<'a, D: BlockDevice + ?Sized> DirCacheIter<'a, D> {
+    /// Create an iterator starting at the first cache block of a directory.
+    pub(crate) fn new(
+        device: &'a D,
+        dir_block: u32,
+        first_block: u32,
+        total_blocks: u32,
+        verify_checksums: bool,
+    ) -> Self {
+        Self {
+            device,
+            dir_block,
+            next_block: first_block,
+            total_blocks,
+            hops: 0,
+            remaining: 0,
+            offset: 0,
+            verify_checksums,
+            buf: [0u8; BLOCK_SIZE],
+        }
+    }
+
+    /// Load the next cache block in the chain.
+    fn load_next(&mut self) -> Result<()> {
+        let block = self.next_block;
+        self.next_block = 0;
+
+        self.hops += 1;
+        if self.hops > self.total_blocks || block >= self.total_blocks {
+            return Err(AffsError::BlockOutOfRange);
+        }
+
+        self.device
+            .read_block(block, &mut self.buf)
+            .map_err(|()| AffsError::BlockReadError)?;
+
+        let cache = DirCacheBlock::parse_with(&self.buf, self.verify_checksums)?;
+        if cache.parent != self.dir_block {
+            return Err(AffsError::InvalidState);
+        }
+
+        self.next_block = cache.next_dirc;
+        self.remaining = cache.records_nb;
+        self.offset = 0;
+        Ok(())
+    }
+}
+
+impl<D: BlockDevice + ?Sized> Iterator for DirCacheIter<'_, D> {
+    type Item = Result<DirEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.remaining > 0 {
+                self.remaining -= 1;
+
+                let records = &self.buf[DirCacheBlock::RECORDS_OFFSET..];
+                let Some((record, len)) =
+                    records.get(self.offset..).and_then(DirCacheRecord::parse)
+                else {
+                    self.remaining = 0;
+                    return Some(Err(AffsError::InvalidState));
+                };
+                self.offset += len;
+
+                match DirEntry::from_cache_record(&record, self.dir_block) {
+                    Some(entry) => return Some(Ok(entry)),
+                    None => continue, // Skip invalid entries
+                }
+            }
+
+            if self.next_block == 0 {
+                return None;
+            }
+
+            if let Err(e) = self.load_next() {
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+/// Directory listing from either a DIRCACHE chain or the hash table.
+///
+/// Returned by [`AffsReader::read_dir_cached`](crate::AffsReader::read_dir_cached).
+#[allow(clippy::large_enum_variant)] // no_std: boxing is not an option
+pub enum CachedDirIter<'a, D: BlockDevice + ?Sized> {
+    /// Entries read from the directory cache.
+    Cache(DirCacheIter<'a, D>),
+    /// Entries read from the hash table (no cache present).
+    HashTable(DirIter<'a, D>),
+}
+
+impl<D: BlockDevice + ?Sized> Iterator for CachedDirIter<'_, D> {
+    type Item = Result<DirEntry>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Cache(iter) => iter.next(),
+            Self::HashTable(iter) => iter.next(),
+        }
+    }
+}
+
+/// Iterator over the file entries of a directory.
+///
+/// Returned by [`DirIter::files`].
+pub struct Files<'a, D: BlockDevice + ?Sized> {
+    inner: DirIter<'a, D>,
+}
+
+impl<D: BlockDevice + ?Sized> Iterator for Files<'_, D> {
+    type Item = Result<DirEntry>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_matching(DirEntry::is_file)
+    }
+}
+
+/// Iterator over directory entries that reports malformed entries.
+///
+/// Returned by [`DirIter::strict`].
+pub struct StrictDirIter<'a, D: BlockDevice + ?Sized> {
+    inner: DirIter<'a, D>,
+}
+
+impl<D: BlockDevice + ?Sized> Iterator for StrictDirIter<'_, D> {
+    type Item = Result<DirEntry>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_entry(true)
+    }
+}
+
+/// Iterator over the subdirectory entries of a directory.
+///
+/// Returned by [`DirIter::dirs`].
+pub struct Dirs<'a, D: BlockDevice + ?Sized> {
+    inner: DirIter<'a, D>,
+}
+
+impl<D: BlockDevice + ?Sized> Iterator for Dirs<'_, D> {
+    type Item = Result<DirEntry>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_matching(DirEntry::is_dir)
+    }
+}
+
+/// Iterator over directory entries whose names match a wildcard pattern.
+///
+/// Returned by [`AffsReader::glob`](crate::AffsReader::glob). Matching
+/// follows [`glob_match`]; read errors are passed through.
+pub struct GlobIter<'a, D: BlockDevice + ?Sized> {
+    inner: DirIter<'a, D>,
+    pattern: &'a [u8],
+}
+
+impl<'a, D: BlockDevice + ?Sized> GlobIter<'a, D> {
+    /// Filter a directory iterator by `pattern`.
+    pub fn new(inner: DirIter<'a, D>, pattern: &'a [u8]) -> Self {
+        Self { inner, pattern }
+    }
+}
+
+impl<D: BlockDevice + ?Sized> Iterator for GlobIter<'_, D> {
+    type Item = Result<DirEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (pattern, intl) = (self.pattern, self.inner.intl);
+        self.inner
+            .next_matching(|entry| glob_match(pattern, entry.name(), intl))
+    }
+}
+
+/// Handle to a directory's hash table.
+///
+/// Returned by [`AffsReader::dir`](crate::AffsReader::dir). The handle is
+/// cheap to keep around; each iteration starts a fresh [`DirIter`].
+///
+/// ```ignore
+/// for entry in reader.dir(block)? {
+///     let entry = entry?;
+/// }
+/// ```
+pub struct Directory<'a, D: BlockDevice + ?Sized> {
+    device: &'a D,
+    hash_table: [u32; HASH_TABLE_SIZE],
+    intl: bool,
+    long_names: bool,
+    verify_checksums: bool,
+}
+
+impl<'a, D: BlockDevice + ?Sized> Directory<'a, D> {
+    /// Create a handle over a directory hash table.
+    pub(crate) const fn new(
+        device: &'a D,
+        hash_table: [u32; HASH_TABLE_SIZE],
+        intl: bool,
+        long_names: bool,
+        verify_checksums: bool,
+    ) -> Self {
+        Self {
+            device,
+            hash_table,
+            intl,
+            long_names,
+            verify_checksums,
+        }
+    }
+
+    /// Iterate over the entries.
+    #[inline]
+    pub fn iter(&self) -> DirIter<'a, D> {
+        DirIter::new(
+            self.device,
+            self.hash_table,
+            self.intl,
+            self.long_names,
+            self.verify_checksums,
+        )
+    }
+
+    /// Count the entries by walking every hash chain.
+    pub fn len(&self) -> Result<usize> {
+        let mut count = 0;
+        for entry in self.iter() {
+            entry?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Check if the directory has no entries.
+    ///
+    /// This only inspects the hash table and does no I/O.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.hash_table.iter().all(|&block| block == 0)
+    }
+
+    /// Find an entry by name.
+    pub fn find(&self, name: &[u8]) -> Result<DirEntry> {
+        self.iter().find(name)
+    }
+
+    /// Get the raw hash table.
+    #[inline]
+    pub const fn hash_table(&self) -> &[u32; HASH_TABLE_SIZE] {
+        &self.hash_table
+    }
+}
+
+impl<D: BlockDevice + ?Sized> Clone for Directory<'_, D> {
+    fn clone(&self) -> Self {
+        Self { ..*self }
+    }
+}
+
+impl<'a, D: BlockDevice + ?Sized> IntoIterator for Directory<'a, D> {
+    type Item = Result<DirEntry>;
+    type IntoIter = DirIter<'a, D>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, D: BlockDevice + ?Sized> IntoIterator for &Directory<'a, D> {
+    type Item = Result<DirEntry>;
+    type IntoIter = DirIter<'a, D>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -4,6 +4,7 @@ use core::fmt;
 
 /// Error type for AFFS operations.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum AffsError {
     /// Block read failed.
     BlockReadError,
@@ -37,6 +38,18 @@ pub enum AffsError {
     NotASymlink,
     /// Symlink target too long.
     SymlinkTooLong,
+    /// Too many symlinks followed while resolving a path.
+    SymlinkLoop,
+    /// File data ends before the size given in its header.
+    TruncatedFile,
+    /// Data is not valid UTF-8.
+    InvalidUtf8,
+    /// The volume has a boot block but its root block was never written.
+    EmptyVolume,
+    /// File data does not match the volume's OFS or FFS type.
+    FsTypeMismatch,
+    /// Entry block has an empty name.
+    EmptyName,
 }
 
 impl fmt::Display for AffsError {
@@ -58,6 +71,12 @@ impl fmt::Display for AffsError {
             Self::InvalidDataSequence => write!(f, "invalid data block sequence"),
             Self::NotASymlink => write!(f, "not a symlink"),
             Self::SymlinkTooLong => write!(f, "symlink target too long"),
+            Self::SymlinkLoop => write!(f, "too many levels of symlinks"),
+            Self::TruncatedFile => write!(f, "file data truncated"),
+            Self::InvalidUtf8 => write!(f, "invalid UTF-8"),
+            Self::EmptyVolume => write!(f, "empty volume (root block not initialized)"),
+            Self::FsTypeMismatch => write!(f, "file data does not match filesystem type"),
+            Self::EmptyName => write!(f, "entry has an empty name"),
         }
     }
 }
@@ -65,5 +84,20 @@ impl fmt::Display for AffsError {
 #[cfg(feature = "std")]
 impl std::error::Error for AffsError {}
 
+#[cfg(feature = "std")]
+impl From<AffsError> for std::io::Error {
+    fn from(err: AffsError) -> Self {
+        use std::io::ErrorKind;
+
+        let kind = match err {
+            AffsError::EntryNotFound => ErrorKind::NotFound,
+            AffsError::EndOfFile | AffsError::TruncatedFile => ErrorKind::UnexpectedEof,
+            AffsError::BlockReadError => ErrorKind::Other,
+            _ => ErrorKind::InvalidData,
+        };
+        std::io::Error::new(kind, err)
+    }
+}
+
 /// Result type for AFFS operations.
 pub type Result<T> = core::result::Result<T, AffsError>;
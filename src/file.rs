@@ -1,9 +1,22 @@
 //! File reading functionality.
 
-use crate::block::{EntryBlock, FileExtBlock, OfsDataBlock};
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec, vec::Vec};
+
+use crate::block::{EntryBlock, FileExtBlock, OfsDataBlock, looks_like_ofs_data};
 use crate::constants::*;
 use crate::error::{AffsError, Result};
-use crate::types::{BlockDevice, FsType};
+use crate::reader::{LinkHops, Options};
+use crate::types::{BlockDevice, FsFlags, FsType};
+#[cfg(feature = "alloc")]
+use crate::utf8::from_utf8;
+
+/// Number of extension blocks remembered for seeking.
+///
+/// 32 extensions cover 2304 data blocks, more than a whole floppy. Files
+/// with more extensions still seek correctly, walking the chain from the
+/// last remembered one.
+const EXT_INDEX_LEN: usize = 32;
 
 /// Streaming file reader.
 ///
@@ -23,7 +36,7 @@ use crate::types::{BlockDevice, FsType};
 ///     // Process buf[..n]
 /// }
 /// ```
-pub struct FileReader<'a, D: BlockDevice> {
+pub struct FileReader<'a, D: BlockDevice + ?Sized> {
     device: &'a D,
     fs_type: FsType,
     /// Block number of file header (for reset/seek).
@@ -54,11 +67,24 @@ pub struct FileReader<'a, D: BlockDevice> {
     current_data_block: u32,
     /// Offset within current data block.
     offset_in_block: usize,
+    /// Data size from the current OFS data block header.
+    ofs_data_size: usize,
+    /// Next data block from the current OFS data block header.
+    ofs_next_data: u32,
+    /// Whether data and extension block checksums are verified.
+    verify_checksums: bool,
+    /// Set during [`read_verified`](Self::read_verified).
+    verify_data: bool,
+    /// Extension blocks loaded so far, as `(block, index of first data
+    /// block)` in file order. Lets seeks skip the extension chain.
+    ext_index: [(u32, u32); EXT_INDEX_LEN],
+    /// Number of valid entries in `ext_index`.
+    ext_index_len: usize,
     /// Block buffer.
     buf: [u8; BLOCK_SIZE],
 }
 
-impl<'a, D: BlockDevice> FileReader<'a, D> {
+impl<'a, D: BlockDevice + ?Sized> FileReader<'a, D> {
     /// Create a new file reader from a file header block.
     ///
     /// # Arguments
@@ -66,12 +92,66 @@ impl<'a, D: BlockDevice> FileReader<'a, D> {
     /// * `fs_type` - Filesystem type (OFS or FFS)
     /// * `header_block` - Block number of the file header
     pub fn new(device: &'a D, fs_type: FsType, header_block: u32) -> Result<Self> {
+        Self::with_options(device, fs_type, header_block, Options::default())
+    }
+
+    /// Create a new file reader with explicit options.
+    ///
+    /// With `verify_checksums` disabled, the header, extension and OFS data
+    /// blocks are accepted even if their checksums are wrong.
+    ///
+    /// If `header_block` is a hard link to a file, its `real_entry` is
+    /// opened instead. Returns `AffsError::InvalidState` if more than
+    /// `MAX_LINK_DEPTH` links are chained.
+    ///
+    /// Header blocks are read with the classic layout; on a long filename
+    /// volume open files through [`AffsReader::read_file`] instead.
+    ///
+    /// [`AffsReader::read_file`]: crate::AffsReader::read_file
+    pub fn with_options(
+        device: &'a D,
+        fs_type: FsType,
+        header_block: u32,
+        options: Options,
+    ) -> Result<Self> {
+        // Reads past the end of the device fail on their own
+        Self::open(
+            device,
+            fs_type,
+            FsFlags::default(),
+            u32::MAX,
+            header_block,
+            options,
+        )
+    }
+
+    /// Like [`with_options`](Self::with_options), reading header blocks
+    /// laid out for a volume with `flags` and `total_blocks` blocks.
+    pub(crate) fn open(
+        device: &'a D,
+        fs_type: FsType,
+        flags: FsFlags,
+        total_blocks: u32,
+        header_block: u32,
+        options: Options,
+    ) -> Result<Self> {
+        let mut header_block = header_block;
         let mut buf = [0u8; BLOCK_SIZE];
-        device
-            .read_block(header_block, &mut buf)
-            .map_err(|()| AffsError::BlockReadError)?;
+        let mut hops = LinkHops::new(total_blocks);
 
-        let entry = EntryBlock::parse(&buf)?;
+        let entry = loop {
+            device
+                .read_block(header_block, &mut buf)
+                .map_err(|()| AffsError::BlockReadError)?;
+
+            let entry = EntryBlock::parse_with_flags(&buf, options.verify_checksums, flags)?;
+            if entry.sec_type != ST_LFILE {
+                break entry;
+            }
+
+            // Hard links carry no data; follow them to the real file
+            header_block = hops.follow(entry.real_entry)?;
+        };
 
         if !entry.is_file() {
             return Err(AffsError::NotAFile);
@@ -98,9 +178,15 @@ impl<'a, D: BlockDevice> FileReader<'a, D> {
             data_blocks,
             initial_extension: entry.extension,
             next_extension: entry.extension,
-            initial_first_data: entry.first_data,
-            current_data_block: entry.first_data,
+            initial_first_data: entry.first_data_block(fs_type),
+            current_data_block: entry.first_data_block(fs_type),
             offset_in_block: 0,
+            ofs_data_size: 0,
+            ofs_next_data: 0,
+            verify_checksums: options.verify_checksums,
+            verify_data: false,
+            ext_index: [(0, 0); EXT_INDEX_LEN],
+            ext_index_len: 0,
             buf,
         })
     }
@@ -144,9 +230,15 @@ impl<'a, D: BlockDevice> FileReader<'a, D> {
             data_blocks,
             initial_extension: entry.extension,
             next_extension: entry.extension,
-            initial_first_data: entry.first_data,
-            current_data_block: entry.first_data,
+            initial_first_data: entry.first_data_block(fs_type),
+            current_data_block: entry.first_data_block(fs_type),
             offset_in_block: 0,
+            ofs_data_size: 0,
+            ofs_next_data: 0,
+            verify_checksums: true,
+            verify_data: false,
+            ext_index: [(0, 0); EXT_INDEX_LEN],
+            ext_index_len: 0,
             buf: [0u8; BLOCK_SIZE],
         })
     }
@@ -193,11 +285,16 @@ impl<'a, D: BlockDevice> FileReader<'a, D> {
         self.next_extension = self.initial_extension;
         self.current_data_block = self.initial_first_data;
         self.offset_in_block = 0;
+        self.ofs_data_size = 0;
+        self.ofs_next_data = 0;
     }
 
     /// Read data into a buffer.
     ///
     /// Returns the number of bytes read. Returns 0 at end of file.
+    ///
+    /// Reading stops at the header's `byte_size`: data block pointers past
+    /// the last block needed for that size are never loaded.
     pub fn read(&mut self, out: &mut [u8]) -> Result<usize> {
         if self.remaining == 0 || out.is_empty() {
             return Ok(0);
@@ -208,6 +305,11 @@ impl<'a, D: BlockDevice> FileReader<'a, D> {
         while total_read < out.len() && self.remaining > 0 {
             // If we need to read a new data block
             if self.offset_in_block == 0 || self.offset_in_block >= self.data_block_size() {
+                let n = self.read_contiguous_ffs(&mut out[total_read..])?;
+                if n > 0 {
+                    total_read += n;
+                    continue;
+                }
                 self.read_next_data_block()?;
             }
 
@@ -219,6 +321,10 @@ impl<'a, D: BlockDevice> FileReader<'a, D> {
                 .min(self.remaining as usize);
 
             if to_read == 0 {
+                // Data ran out before `remaining` reached 0
+                if total_read == 0 {
+                    return Err(AffsError::TruncatedFile);
+                }
                 break;
             }
 
@@ -236,6 +342,58 @@ impl<'a, D: BlockDevice> FileReader<'a, D> {
         Ok(total_read)
     }
 
+    /// Read data into a buffer, validating every block loaded on the way.
+    ///
+    /// Like [`read`](Self::read), but checksums are verified even when the
+    /// reader was opened with `verify_checksums` disabled. Each OFS data
+    /// block must also name this file as its header and carry the expected
+    /// sequence number, otherwise `AffsError::InvalidDataSequence` is
+    /// returned. A corrupted OFS data block is reported as
+    /// `AffsError::ChecksumMismatch` instead of being returned as data.
+    ///
+    /// FFS data blocks have no header or checksum. They are only ever taken
+    /// from the file header and extension block pointer tables, so on FFS
+    /// the check covers the extension block checksums (the header's is
+    /// checked when the file is opened).
+    /// Bytes left in the current block by an earlier [`read`](Self::read)
+    /// are not re-checked.
+    pub fn read_verified(&mut self, out: &mut [u8]) -> Result<usize> {
+        self.verify_data = true;
+        let result = self.read(out);
+        self.verify_data = false;
+        result
+    }
+
+    /// Read the next block of file data without copying it.
+    ///
+    /// Loads the next data block into the internal buffer and returns its
+    /// valid data: up to 512 bytes for FFS (less for the final block) or the
+    /// data payload of an OFS block. If a previous [`read`](Self::read)
+    /// stopped partway through a block, the rest of that block is returned
+    /// first. Returns `Ok(None)` at end of file.
+    pub fn next_block(&mut self) -> Result<Option<&[u8]>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        if self.offset_in_block == 0 || self.offset_in_block >= self.data_block_size() {
+            self.read_next_data_block()?;
+        }
+
+        let len = self
+            .current_block_data_size()
+            .saturating_sub(self.offset_in_block)
+            .min(self.remaining as usize);
+        if len == 0 {
+            return Err(AffsError::TruncatedFile);
+        }
+
+        let start = self.data_offset() + self.offset_in_block;
+        self.offset_in_block += len;
+        self.remaining -= len as u32;
+        Ok(Some(&self.buf[start..start + len]))
+    }
+
     /// Read the entire file into a buffer.
     ///
     /// The buffer must be at least as large as the file size.
@@ -277,12 +435,8 @@ impl<'a, D: BlockDevice> FileReader<'a, D> {
     /// Get actual data size in current block.
     fn current_block_data_size(&self) -> usize {
         match self.fs_type {
-            FsType::Ofs => {
-                // OFS has explicit data size in header
-                // We need to parse it from current buffer
-                let header = OfsDataBlock::parse(&self.buf).ok();
-                header.map(|h| h.data_size as usize).unwrap_or(0)
-            }
+            // OFS has explicit data size in header, cached when the block was loaded
+            FsType::Ofs => self.ofs_data_size,
             FsType::Ffs => {
                 // FFS uses full block, but last block may be partial
                 let block_size = FFS_DATA_SIZE;
@@ -292,27 +446,135 @@ impl<'a, D: BlockDevice> FileReader<'a, D> {
         }
     }
 
+    /// Read a run of whole, physically contiguous FFS data blocks straight into `out`.
+    ///
+    /// Called at a block boundary. Fetches up to `MAX_READ_BLOCKS` blocks
+    /// with a single [`BlockDevice::read_blocks`] call when the next pointers
+    /// in the current table are consecutive and `out` has room for all of
+    /// them. Returns the number of bytes read, or 0 if there is no run of
+    /// at least two blocks.
+    fn read_contiguous_ffs(&mut self, out: &mut [u8]) -> Result<usize> {
+        if !matches!(self.fs_type, FsType::Ffs) {
+            return Ok(0);
+        }
+
+        let wanted = (out.len().min(self.remaining as usize) / FFS_DATA_SIZE).min(MAX_READ_BLOCKS);
+        let idx = self.index_in_current as usize;
+        let available = (self.blocks_in_current as usize)
+            .min(MAX_DATABLK)
+            .saturating_sub(idx);
+        let limit = wanted.min(available);
+        if limit < 2 {
+            return Ok(0);
+        }
+
+        // Pointers are stored in reverse order
+        let first = self.data_blocks[MAX_DATABLK - 1 - idx];
+        let run = (1..limit)
+            .take_while(|&k| {
+                self.data_blocks[MAX_DATABLK - 1 - idx - k] == first.wrapping_add(k as u32)
+            })
+            .count()
+            + 1;
+        if first == 0 || run < 2 {
+            return Ok(0);
+        }
+
+        let (chunks, _) = out.as_chunks_mut::<BLOCK_SIZE>();
+        let chunks = &mut chunks[..run];
+        self.device
+            .read_blocks(first, chunks)
+            .map_err(|()| AffsError::BlockReadError)?;
+        if self.block_index == 0 {
+            self.check_first_block(&chunks[0])?;
+        }
+
+        let len = run * FFS_DATA_SIZE;
+        self.index_in_current += run as u32;
+        self.block_index += run as u32;
+        self.remaining -= len as u32;
+        self.offset_in_block = 0;
+        self.prefetch_next();
+        Ok(len)
+    }
+
     /// Read the next data block.
     fn read_next_data_block(&mut self) -> Result<()> {
         let block = self.get_next_data_block()?;
         if block == 0 {
-            return Err(AffsError::EndOfFile);
+            // Only called while bytes remain, so the file is shorter than its header claims
+            return Err(AffsError::TruncatedFile);
         }
 
         self.device
             .read_block(block, &mut self.buf)
             .map_err(|()| AffsError::BlockReadError)?;
+        if self.block_index == 0 {
+            self.check_first_block(&self.buf)?;
+        }
 
-        // Validate OFS data block
+        // Validate OFS data block and cache its header
         if matches!(self.fs_type, FsType::Ofs) {
-            let _ = OfsDataBlock::parse(&self.buf)?;
+            let header =
+                OfsDataBlock::parse_with(&self.buf, self.verify_checksums || self.verify_data)?;
+            if self.verify_data
+                && (header.header_key != self.header_block
+                    || header.seq_num != self.block_index + 1)
+            {
+                warn!(
+                    "data block {=u32} of file {=u32} out of sequence",
+                    block, self.header_block
+                );
+                return Err(AffsError::InvalidDataSequence);
+            }
+            self.ofs_data_size = (header.data_size as usize).min(OFS_DATA_SIZE);
+            self.ofs_next_data = header.next_data;
         }
 
         self.offset_in_block = 0;
         self.block_index += 1;
+        self.prefetch_next();
         Ok(())
     }
 
+    /// Check that the first data block of the file matches the volume type.
+    ///
+    /// On OFS the first block must pass [`looks_like_ofs_data`]; on FFS it
+    /// must not, which would mean the volume is really OFS. Either mismatch
+    /// returns `AffsError::FsTypeMismatch` rather than handing out headers as
+    /// data (or data as headers).
+    fn check_first_block(&self, buf: &[u8; BLOCK_SIZE]) -> Result<()> {
+        let looks_ofs = looks_like_ofs_data(buf, self.header_block);
+        if looks_ofs != matches!(self.fs_type, FsType::Ofs) {
+            warn!(
+                "first data block of file {=u32} does not match filesystem type",
+                self.header_block
+            );
+            return Err(AffsError::FsTypeMismatch);
+        }
+        Ok(())
+    }
+
+    /// Hint the device about the next FFS data block.
+    ///
+    /// At the end of the current table, the next extension block is hinted
+    /// instead, since that is what gets read next.
+    fn prefetch_next(&self) {
+        if !matches!(self.fs_type, FsType::Ffs) || self.remaining == 0 {
+            return;
+        }
+
+        let idx = self.index_in_current as usize;
+        let next = if idx < (self.blocks_in_current as usize).min(MAX_DATABLK) {
+            self.data_blocks[MAX_DATABLK - 1 - idx]
+        } else {
+            self.next_extension
+        };
+        if next != 0 {
+            self.device.prefetch(next);
+        }
+    }
+
     /// Get the next data block number.
     fn get_next_data_block(&mut self) -> Result<u32> {
         match self.fs_type {
@@ -329,10 +591,8 @@ impl<'a, D: BlockDevice> FileReader<'a, D> {
             return Ok(self.current_data_block);
         }
 
-        // Follow the linked list
-        // current buffer should have the previous data block
-        let header = OfsDataBlock::parse(&self.buf)?;
-        self.current_data_block = header.next_data;
+        // Follow the linked list from the previous block's cached header
+        self.current_data_block = self.ofs_next_data;
         Ok(self.current_data_block)
     }
 
@@ -343,19 +603,7 @@ impl<'a, D: BlockDevice> FileReader<'a, D> {
             if self.next_extension == 0 {
                 return Ok(0); // No more blocks
             }
-
-            // Load extension block
-            self.device
-                .read_block(self.next_extension, &mut self.buf)
-                .map_err(|()| AffsError::BlockReadError)?;
-
-            let ext = FileExtBlock::parse(&self.buf)?;
-
-            // Copy data block pointers
-            self.data_blocks.copy_from_slice(&ext.data_blocks);
-            self.blocks_in_current = ext.high_seq as u32;
-            self.next_extension = ext.extension;
-            self.index_in_current = 0;
+            self.load_next_extension()?;
         }
 
         if self.index_in_current >= self.blocks_in_current {
@@ -374,10 +622,121 @@ impl<'a, D: BlockDevice> FileReader<'a, D> {
         Ok(block)
     }
 
+    /// Load the next extension block's data block pointers.
+    ///
+    /// The extension is remembered in the seek index.
+    fn load_next_extension(&mut self) -> Result<()> {
+        let block = self.next_extension;
+        self.load_extension(block)?;
+
+        let len = self.ext_index_len;
+        // After a reset the chain is walked again; only record new extensions
+        if len < EXT_INDEX_LEN && (len == 0 || self.ext_index[len - 1].1 < self.block_index) {
+            self.ext_index[len] = (block, self.block_index);
+            self.ext_index_len += 1;
+        }
+        Ok(())
+    }
+
+    /// Load an extension block's data block pointers.
+    fn load_extension(&mut self, block: u32) -> Result<()> {
+        self.device
+            .read_block(block, &mut self.buf)
+            .map_err(|()| AffsError::BlockReadError)?;
+
+        let ext = FileExtBlock::parse_with(&self.buf, self.verify_checksums || self.verify_data)?;
+
+        // Copy data block pointers
+        self.data_blocks.copy_from_slice(&ext.data_blocks);
+        self.blocks_in_current = ext.high_seq as u32;
+        self.next_extension = ext.extension;
+        self.index_in_current = 0;
+        Ok(())
+    }
+
+    /// Skip forward by up to `n` bytes.
+    ///
+    /// Returns the number of bytes skipped, which is less than `n` only when
+    /// the end of the file is reached.
+    ///
+    /// On FFS the block pointer table is advanced arithmetically, so only
+    /// extension blocks and the block containing the new position are read.
+    /// OFS data blocks form a linked list, so every skipped block is read.
+    pub fn skip(&mut self, n: u32) -> Result<u32> {
+        let n = n.min(self.remaining);
+        if n == 0 {
+            return Ok(0);
+        }
+
+        match self.fs_type {
+            FsType::Ofs => self.skip_by_reading(n)?,
+            FsType::Ffs => self.skip_ffs(n)?,
+        }
+
+        Ok(n)
+    }
+
+    /// Skip `n` bytes (at most `remaining`) by reading and discarding.
+    fn skip_by_reading(&mut self, n: u32) -> Result<()> {
+        let mut discard = [0u8; 512];
+        let mut to_skip = n;
+        while to_skip > 0 {
+            let read = self.read(&mut discard[..to_skip.min(512) as usize])?;
+            if read == 0 {
+                return Err(AffsError::TruncatedFile);
+            }
+            to_skip -= read as u32;
+        }
+        Ok(())
+    }
+
+    /// Skip `n` bytes (at most `remaining`) on FFS without reading data.
+    fn skip_ffs(&mut self, n: u32) -> Result<()> {
+        // Still within the loaded block
+        if self.offset_in_block > 0 && self.offset_in_block + n as usize <= FFS_DATA_SIZE {
+            self.offset_in_block += n as usize;
+            self.remaining -= n;
+            return Ok(());
+        }
+
+        let target = self.position() + n;
+        self.remaining -= n;
+        if self.remaining == 0 {
+            return Ok(());
+        }
+
+        // Advance the pointer table to the block containing `target`
+        let target_block = target / FFS_DATA_SIZE as u32;
+        let mut to_skip = target_block - self.block_index;
+        while to_skip > 0 {
+            if self.index_in_current >= self.blocks_in_current {
+                if self.next_extension == 0 {
+                    return Err(AffsError::TruncatedFile);
+                }
+                self.load_next_extension()?;
+                continue;
+            }
+            let step = to_skip.min(self.blocks_in_current - self.index_in_current);
+            self.index_in_current += step;
+            self.block_index += step;
+            to_skip -= step;
+        }
+
+        let offset = target as usize % FFS_DATA_SIZE;
+        self.offset_in_block = 0;
+        if offset != 0 {
+            self.read_next_data_block()?;
+            self.offset_in_block = offset;
+        }
+        Ok(())
+    }
+
     /// Seek to a specific position in the file.
     ///
-    /// Note: Seeking backwards resets to the beginning and seeks forward,
-    /// which may need to re-read extension blocks for large files.
+    /// Note: Seeking backwards resets to the beginning and seeks forward.
+    /// On FFS, extension blocks already visited are remembered, so the
+    /// seek starts from the nearest one instead of re-reading the chain.
+    /// OFS files are re-read from the start.
     pub fn seek(&mut self, position: u32) -> Result<()> {
         if position > self.file_size {
             return Err(AffsError::EndOfFile);
@@ -392,19 +751,143 @@ impl<'a, D: BlockDevice> FileReader<'a, D> {
             self.reset();
         }
 
-        // Seek forward by reading and discarding
-        let mut discard = [0u8; 512];
-        let mut to_skip = position - self.position();
-        while to_skip > 0 {
-            let n = self.read(&mut discard[..to_skip.min(512) as usize])?;
-            if n == 0 {
-                return Err(AffsError::EndOfFile);
-            }
-            to_skip -= n as u32;
+        if matches!(self.fs_type, FsType::Ffs) {
+            self.jump_to_extension(position / FFS_DATA_SIZE as u32)?;
         }
 
+        self.skip(position - self.position())?;
         Ok(())
     }
+
+    /// Move to the start of the last known extension table at or before
+    /// data block `target_block`.
+    ///
+    /// Does nothing unless that table lies past the current one, in which
+    /// case the extension chain up to it is not re-read. On error the reader
+    /// is reset to the start of the file.
+    fn jump_to_extension(&mut self, target_block: u32) -> Result<()> {
+        let table_start = self.block_index - self.index_in_current;
+        let Some(&(block, start)) = self.ext_index[..self.ext_index_len]
+            .iter()
+            .rev()
+            .find(|&&(_, start)| start <= target_block)
+        else {
+            return Ok(());
+        };
+        let start_pos = start * FFS_DATA_SIZE as u32;
+        if start <= table_start || start_pos < self.position() {
+            return Ok(());
+        }
+
+        if let Err(err) = self.load_extension(block) {
+            self.reset();
+            return Err(err);
+        }
+        self.block_index = start;
+        self.remaining = self.file_size - start_pos;
+        self.offset_in_block = 0;
+        Ok(())
+    }
+
+    /// Seek to `back` bytes before the end of the file.
+    ///
+    /// Returns `AffsError::EndOfFile` if `back` is larger than the file.
+    pub fn seek_from_end(&mut self, back: u32) -> Result<()> {
+        if back > self.file_size {
+            return Err(AffsError::EndOfFile);
+        }
+        self.seek(self.file_size - back)
+    }
+
+    /// Seek by `delta` bytes relative to the current position.
+    ///
+    /// Returns `AffsError::EndOfFile` if the target lies before the start
+    /// or past the end of the file.
+    pub fn seek_relative(&mut self, delta: i64) -> Result<()> {
+        let target = i64::from(self.position()) + delta;
+        if target < 0 || target > i64::from(self.file_size) {
+            return Err(AffsError::EndOfFile);
+        }
+        self.seek(target as u32)
+    }
+
+    /// Read data without advancing the read cursor.
+    ///
+    /// Reads into `out` and then seeks back to the current position. Since
+    /// seeking backwards restarts from the beginning of the file, this costs
+    /// O(position) reads for OFS files; peeking near the start of a file is
+    /// cheap. See [`seek`](Self::seek).
+    ///
+    /// Returns the number of bytes read, which is 0 at end of file.
+    pub fn peek(&mut self, out: &mut [u8]) -> Result<usize> {
+        let saved = self.position();
+        let result = self.read(out);
+        self.seek(saved)?;
+        result
+    }
+
+    /// Read data at an absolute offset without moving the read cursor.
+    ///
+    /// Saves the current position, seeks to `offset`, reads into `out`, then
+    /// seeks back. Because seeks are implemented by reading forward (and
+    /// backward seeks restart from the beginning), this costs O(offset) for
+    /// OFS files. See [`seek`](Self::seek).
+    ///
+    /// Returns the number of bytes read, which is 0 if `offset` is at the
+    /// end of the file.
+    pub fn read_at(&mut self, offset: u32, out: &mut [u8]) -> Result<usize> {
+        let saved = self.position();
+        let result = self.seek(offset).and_then(|()| self.read(out));
+        self.seek(saved)?;
+        result
+    }
+}
+
+impl<D: BlockDevice + ?Sized> Clone for FileReader<'_, D> {
+    fn clone(&self) -> Self {
+        Self { ..*self }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<D: BlockDevice + ?Sized> FileReader<'_, D> {
+    /// Read the rest of the file into a new `Vec`.
+    pub fn read_to_vec(&mut self) -> Result<Vec<u8>> {
+        let mut out = vec![0u8; self.remaining as usize];
+        let n = self.read_all(&mut out)?;
+        out.truncate(n);
+        Ok(out)
+    }
+
+    /// Read the rest of the file into a new `String`.
+    ///
+    /// Returns [`AffsError::InvalidUtf8`] if the contents are not valid UTF-8.
+    pub fn read_to_string(&mut self) -> Result<String> {
+        let bytes = self.read_to_vec()?;
+        let text = from_utf8(&bytes).ok_or(AffsError::InvalidUtf8)?;
+        Ok(String::from(text))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D: BlockDevice + ?Sized> FileReader<'_, D> {
+    /// Stream the rest of the file into a writer.
+    ///
+    /// Copies from the current position to end of file in block-sized
+    /// chunks and returns the number of bytes written. Read errors are
+    /// converted to [`std::io::Error`]. The writer is not flushed.
+    pub fn copy_to<W: std::io::Write>(&mut self, w: &mut W) -> std::io::Result<u64> {
+        let mut chunk = [0u8; BLOCK_SIZE];
+        let mut total = 0u64;
+        loop {
+            let n = self.read(&mut chunk)?;
+            if n == 0 {
+                return Ok(total);
+            }
+            w.write_all(&chunk[..n])?;
+            total += n as u64;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,152 @@
+//! File header summaries and block layout.
+
+use crate::block::{FileExtBlock, looks_like_ofs_data};
+use crate::constants::*;
+use crate::error::{AffsError, Result};
+use crate::reader::AffsReader;
+use crate::types::{BlockDevice, FsType};
+
+/// How a file's blocks are laid out on disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileLayout {
+    /// Number of data blocks listed by the header and extension blocks.
+    pub data_block_count: u32,
+    /// Number of extension blocks in the chain.
+    pub extension_block_count: u32,
+    /// Number of maximal runs of consecutive data block numbers.
+    ///
+    /// 1 for an unfragmented file, 0 for an empty one.
+    pub contiguous_runs: u32,
+}
+
+/// Summary of a file header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileInfo {
+    /// File size in bytes.
+    pub size: u32,
+    /// Number of data block pointers in the header block.
+    pub blocks_in_header: u32,
+    /// First data block (see [`EntryBlock::first_data_block`](crate::EntryBlock::first_data_block)).
+    pub first_data: u32,
+    /// First extension block (0 = none).
+    pub extension: u32,
+    /// The volume is OFS, so data blocks carry a 24-byte header.
+    pub is_ofs: bool,
+}
+
+impl FileLayout {
+    /// Add a table of data block pointers (stored in reverse order).
+    fn add_table(&mut self, table: &[u32; MAX_DATABLK], count: i32, prev: &mut Option<u32>) {
+        for i in 0..count as usize {
+            let block = table[MAX_DATABLK - 1 - i];
+            let continues = matches!(*prev, Some(p) if p.checked_add(1) == Some(block));
+            if !continues {
+                self.contiguous_runs += 1;
+            }
+            *prev = Some(block);
+            self.data_block_count += 1;
+        }
+    }
+}
+
+impl<D: BlockDevice + ?Sized> AffsReader<'_, D> {
+    /// Summarize a file header without starting a read.
+    ///
+    /// # Arguments
+    /// * `header_block` - Block number of the file header
+    pub fn file_info(&self, header_block: u32) -> Result<FileInfo> {
+        let header = self.read_entry(header_block)?;
+        if !header.is_file() {
+            return Err(AffsError::NotAFile);
+        }
+
+        Ok(FileInfo {
+            size: header.byte_size,
+            blocks_in_header: header.high_seq.max(0) as u32,
+            first_data: header.first_data_block(self.fs_type()),
+            extension: header.extension,
+            is_ofs: matches!(self.fs_type(), FsType::Ofs),
+        })
+    }
+
+    /// Check that a file's data matches the declared filesystem type.
+    ///
+    /// Reads the file's first data block and checks whether it is an OFS
+    /// data block (type `T_DATA`, valid checksum and the file as its
+    /// `header_key`). Returns `Ok(false)` if that disagrees with
+    /// [`fs_type`](Self::fs_type), which indicates a mislabeled image.
+    /// Empty files are always consistent.
+    ///
+    /// # Arguments
+    /// * `header_block` - Block number of the file header
+    pub fn verify_fs_type(&self, header_block: u32) -> Result<bool> {
+        let header = self.read_entry(header_block)?;
+        if !header.is_file() {
+            return Err(AffsError::NotAFile);
+        }
+
+        let first = if header.first_data != 0 {
+            header.first_data
+        } else {
+            header.data_block(0)
+        };
+        if header.byte_size == 0 || first == 0 {
+            return Ok(true);
+        }
+        if first >= self.total_blocks() {
+            return Err(AffsError::BlockOutOfRange);
+        }
+
+        let mut buf = [0u8; BLOCK_SIZE];
+        self.device()
+            .read_block(first, &mut buf)
+            .map_err(|()| AffsError::BlockReadError)?;
+        Ok(looks_like_ofs_data(&buf, header_block) == matches!(self.fs_type(), FsType::Ofs))
+    }
+
+    /// Compute the block layout of a file.
+    ///
+    /// Walks the header and extension block tables without reading any
+    /// data blocks.
+    ///
+    /// # Arguments
+    /// * `header_block` - Block number of the file header
+    pub fn file_layout(&self, header_block: u32) -> Result<FileLayout> {
+        let header = self.read_entry(header_block)?;
+        if !header.is_file() {
+            return Err(AffsError::NotAFile);
+        }
+
+        let mut layout = FileLayout::default();
+        let mut prev = None;
+        let mut table = header.hash_table;
+        let mut high_seq = header.high_seq;
+        let mut next = header.extension;
+        let mut buf = [0u8; BLOCK_SIZE];
+
+        loop {
+            if !(0..=MAX_DATABLK as i32).contains(&high_seq) {
+                return Err(AffsError::InvalidDataSequence);
+            }
+            layout.add_table(&table, high_seq, &mut prev);
+
+            if next == 0 {
+                break;
+            }
+            if next >= self.total_blocks() || layout.extension_block_count >= self.total_blocks() {
+                return Err(AffsError::BlockOutOfRange);
+            }
+            layout.extension_block_count += 1;
+
+            self.device()
+                .read_block(next, &mut buf)
+                .map_err(|()| AffsError::BlockReadError)?;
+            let ext = FileExtBlock::parse_with(&buf, self.verify_checksums())?;
+            table = ext.data_blocks;
+            high_seq = ext.high_seq;
+            next = ext.extension;
+        }
+
+        Ok(layout)
+    }
+}
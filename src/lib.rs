@@ -11,8 +11,14 @@
 //! - Zero heap allocations in core functionality
 //! - Support for OFS and FFS filesystems
 //! - Support for INTL and DIRCACHE modes
-//! - Streaming file reading
+//! - Streaming file reading, with optional buffering for small reads
 //! - Directory traversal
+//! - Recursive tree walking
+//! - RDB partition table parsing for hard disk images
+//! - Optional `defmt` logging for embedded targets
+//! - Optional `serde` support for directory entries and dates
+//! - Optional `chrono` and `time` conversions for dates
+//! - Optional `async` reader for asynchronous block devices
 //! - Extensively fuzz-tested for safety and correctness
 //!
 //! See `PERFORMANCE.md` for detailed benchmarks and optimization documentation.
@@ -52,29 +58,75 @@ extern crate std;
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+#[macro_use]
+mod log;
+
+#[cfg(feature = "async")]
+mod async_reader;
+mod bitmap;
 mod block;
+mod bufread;
+mod check;
 mod checksum;
+#[cfg(feature = "chrono")]
+mod chrono_impl;
 mod constants;
 mod date;
+mod device;
 mod dir;
 mod error;
 mod file;
+mod layout;
+mod metadata;
+mod path;
+mod rdb;
 mod reader;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod stats;
 mod symlink;
+#[cfg(feature = "time")]
+mod time_impl;
 mod types;
 mod utf8;
 mod varblock;
+mod volume;
+mod walk;
 
+#[cfg(feature = "async")]
+pub use async_reader::{AsyncAffsReader, AsyncBlockDevice, AsyncDirIter, AsyncFileReader};
+pub use bitmap::BitmapBlockIter;
 pub use block::*;
-pub use checksum::{bitmap_sum, boot_sum, normal_sum, normal_sum_slice, read_u16_be};
+pub use bufread::{BufReader, DEFAULT_BUF_SIZE};
+pub use check::{CheckIssue, CheckReport, MAX_CHECK_ISSUES};
+pub use checksum::{
+    bitmap_sum, boot_sum, normal_sum, normal_sum_slice, read_u16_be, verify_block_checksum,
+    verify_boot_checksum,
+};
 pub use constants::*;
-pub use date::AmigaDate;
-pub use dir::{DirEntry, DirIter};
+pub use date::{AmigaDate, DateOutOfRange, DateTime, Weekday};
+#[cfg(feature = "std")]
+pub use device::{FileDevice, IoDevice};
+pub use device::{OffsetDevice, SliceDevice};
+pub use dir::{
+    CachedDirIter, DirCacheIter, DirEntry, DirIter, Directory, Dirs, Files, GlobIter, StrictDirIter,
+};
 pub use error::AffsError;
 pub use file::FileReader;
-pub use reader::AffsReader;
+pub use layout::{FileInfo, FileLayout};
+pub use metadata::Metadata;
+pub use path::AffsPath;
+pub use rdb::{
+    DosEnvVec, IDNAME_PARTITION, IDNAME_RIGIDDISK, MAX_DRIVE_NAME_LEN, MAX_PARTITIONS,
+    PartitionBlock, PartitionInfo, Partitions, RDB_END_OF_LIST, RDB_LOCATION_LIMIT, RigidDiskBlock,
+    parse_rdb,
+};
+pub use reader::{AffsReader, DynAffsReader, Options};
+pub use stats::FsStats;
 pub use symlink::{
     MAX_SYMLINK_LEN, max_utf8_len, read_symlink_target, read_symlink_target_with_block_size,
 };
 pub use types::*;
-pub use varblock::{AffsReaderVar, MAX_BLOCK_SIZE, VarDirEntry, VarDirIter};
+pub use varblock::{AffsReaderVar, MAX_BLOCK_SIZE, VarDirEntry, VarDirIter, VarFileReader};
+pub use volume::{AffsVolume, VolumeEntry, VolumeInfo};
+pub use walk::{EntriesIter, WalkIter, WalkPathsIter};
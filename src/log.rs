@@ -0,0 +1,28 @@
+//! Optional `defmt` logging.
+//!
+//! These macros forward to `defmt` when the `defmt` feature is enabled and
+//! expand to nothing otherwise, so the default build stays silent.
+
+/// Log a trace message.
+macro_rules! trace {
+    ($fmt:literal $(, $arg:expr)* $(,)?) => {{
+        #[cfg(feature = "defmt")]
+        defmt::trace!($fmt $(, $arg)*);
+        #[cfg(not(feature = "defmt"))]
+        {
+            $(let _ = &$arg;)*
+        }
+    }};
+}
+
+/// Log a warning message.
+macro_rules! warn {
+    ($fmt:literal $(, $arg:expr)* $(,)?) => {{
+        #[cfg(feature = "defmt")]
+        defmt::warn!($fmt $(, $arg)*);
+        #[cfg(not(feature = "defmt"))]
+        {
+            $(let _ = &$arg;)*
+        }
+    }};
+}
@@ -0,0 +1,78 @@
+//! Stat-like entry metadata.
+
+use crate::constants::*;
+use crate::date::AmigaDate;
+use crate::dir::DirEntry;
+use crate::error::Result;
+use crate::reader::AffsReader;
+use crate::types::{Access, BlockDevice, EntryType};
+
+/// Metadata for a single entry, as returned by [`AffsReader::metadata`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metadata {
+    /// Entry type.
+    pub entry_type: EntryType,
+    /// File size in bytes (0 for directories).
+    pub size: u32,
+    /// Access permissions.
+    pub access: Access,
+    /// Last modification date.
+    pub date: AmigaDate,
+    /// Block number of the entry header.
+    pub block: u32,
+    name: [u8; MAX_NAME_LEN],
+    name_len: u8,
+    comment: [u8; MAX_COMMENT_LEN],
+    comment_len: u8,
+}
+
+impl Metadata {
+    /// Get the entry name as a byte slice.
+    #[inline]
+    pub fn name(&self) -> &[u8] {
+        &self.name[..self.name_len as usize]
+    }
+
+    /// Get the comment as a byte slice.
+    #[inline]
+    pub fn comment(&self) -> &[u8] {
+        &self.comment[..self.comment_len as usize]
+    }
+
+    /// Check if this is a directory.
+    #[inline]
+    pub const fn is_dir(&self) -> bool {
+        self.entry_type.is_dir()
+    }
+
+    /// Check if this is a file.
+    #[inline]
+    pub const fn is_file(&self) -> bool {
+        self.entry_type.is_file()
+    }
+}
+
+impl From<&DirEntry> for Metadata {
+    fn from(entry: &DirEntry) -> Self {
+        Self {
+            entry_type: entry.entry_type,
+            size: entry.size,
+            access: entry.access,
+            date: entry.date,
+            block: entry.block,
+            name: entry.name,
+            name_len: entry.name_len,
+            comment: entry.comment,
+            comment_len: entry.comment_len,
+        }
+    }
+}
+
+impl<D: BlockDevice + ?Sized> AffsReader<'_, D> {
+    /// Get metadata for the entry at `path`.
+    ///
+    /// The empty path and `/` return the root directory's metadata.
+    pub fn metadata(&self, path: &[u8]) -> Result<Metadata> {
+        self.lookup(path).map(|entry| Metadata::from(&entry))
+    }
+}
@@ -0,0 +1,121 @@
+//! Fixed-capacity path buffer.
+
+use crate::dir::DirEntry;
+use crate::error::{AffsError, Result};
+use crate::reader::AffsReader;
+use crate::types::BlockDevice;
+
+/// A `/`-separated path stored inline in `N` bytes.
+///
+/// Names are kept as raw Latin-1 bytes, as on disk.
+#[derive(Clone, Copy)]
+pub struct AffsPath<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> AffsPath<N> {
+    /// Create an empty path.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            buf: [0u8; N],
+            len: 0,
+        }
+    }
+
+    /// Get the path as a byte slice.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Get the path as str (if valid UTF-8).
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        crate::utf8::from_utf8(self.as_bytes())
+    }
+
+    /// Get the path length in bytes.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the path is empty.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Append `/` and `name` to the path.
+    ///
+    /// No separator is added when the path is exactly `/`, so pushing onto
+    /// the root gives `/name`. Returns `AffsError::BufferTooSmall` if the result does not fit, in
+    /// which case the path is unchanged.
+    pub fn push_component(&mut self, name: &[u8]) -> Result<()> {
+        let start = if self.as_bytes() == b"/" {
+            self.len
+        } else {
+            self.len + 1
+        };
+        let end = start + name.len();
+        if end > N {
+            return Err(AffsError::BufferTooSmall);
+        }
+        self.buf[start - 1] = b'/';
+        self.buf[start..end].copy_from_slice(name);
+        self.len = end;
+        Ok(())
+    }
+}
+
+impl<const N: usize> AffsPath<N> {
+    /// Shorten the path to `len` bytes.
+    #[inline]
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+}
+
+impl<const N: usize> Default for AffsPath<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> PartialEq for AffsPath<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl<const N: usize> Eq for AffsPath<N> {}
+
+impl<const N: usize> core::fmt::Debug for AffsPath<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "AffsPath(\"{self}\")")
+    }
+}
+
+impl<const N: usize> core::fmt::Display for AffsPath<N> {
+    /// Decodes the bytes as Latin-1.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use core::fmt::Write;
+        for &b in self.as_bytes() {
+            f.write_char(b as char)?;
+        }
+        Ok(())
+    }
+}
+
+impl<D: BlockDevice + ?Sized> AffsReader<'_, D> {
+    /// Build the absolute path of an entry into an [`AffsPath`].
+    ///
+    /// See [`entry_path`](Self::entry_path) for details and errors.
+    pub fn entry_path_buf<const N: usize>(&self, entry: &DirEntry) -> Result<AffsPath<N>> {
+        let mut path = AffsPath::new();
+        path.len = self.entry_path(entry, &mut path.buf)?;
+        Ok(path)
+    }
+}
@@ -0,0 +1,390 @@
+//! Rigid Disk Block (RDB) partition table parsing.
+//!
+//! Amiga hard disks carry a `RigidDiskBlock` in one of the first
+//! [`RDB_LOCATION_LIMIT`] sectors. It points to a linked list of
+//! `PartitionBlock`s, each holding a `DosEnvVec` that describes the
+//! partition's geometry and filesystem DOS type.
+
+use crate::checksum::{normal_sum_slice, read_u32_be};
+use crate::error::{AffsError, Result};
+use crate::types::SectorDevice;
+
+/// Number of sectors searched for the `RDSK` block.
+pub const RDB_LOCATION_LIMIT: u64 = 16;
+
+/// Maximum number of partitions returned by [`parse_rdb`].
+pub const MAX_PARTITIONS: usize = 16;
+
+/// Maximum partition drive name length.
+pub const MAX_DRIVE_NAME_LEN: usize = 31;
+
+/// `RDSK` block identifier.
+pub const IDNAME_RIGIDDISK: u32 = 0x5244_534B;
+
+/// `PART` block identifier.
+pub const IDNAME_PARTITION: u32 = 0x5041_5254;
+
+/// End of list marker for RDB block pointers.
+pub const RDB_END_OF_LIST: u32 = 0xFFFF_FFFF;
+
+/// Byte offset of the `DosEnvVec` inside a `PartitionBlock`.
+const ENVIRONMENT_OFFSET: usize = 0x80;
+
+/// Verify the ID and checksum of an RDB block.
+///
+/// RDB blocks are checksummed over their first `SummedLongs` longwords
+/// rather than the whole sector.
+fn check_rdb_block(buf: &[u8; 512], id: u32) -> Result<()> {
+    if read_u32_be(buf, 0) != id {
+        return Err(AffsError::InvalidBlockType);
+    }
+    let summed_longs = read_u32_be(buf, 4) as usize;
+    if !(3..=128).contains(&summed_longs) {
+        return Err(AffsError::InvalidState);
+    }
+    if normal_sum_slice(&buf[..summed_longs * 4], 8) != read_u32_be(buf, 8) {
+        return Err(AffsError::ChecksumMismatch);
+    }
+    Ok(())
+}
+
+/// Rigid Disk Block (`RDSK`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RigidDiskBlock {
+    /// Size of a disk block in bytes.
+    pub block_bytes: u32,
+    /// RDB flags.
+    pub flags: u32,
+    /// First bad block list block.
+    pub bad_block_list: u32,
+    /// First partition block.
+    pub partition_list: u32,
+    /// First filesystem header block.
+    pub file_sys_header_list: u32,
+    /// Number of cylinders.
+    pub cylinders: u32,
+    /// Sectors per track.
+    pub sectors: u32,
+    /// Number of heads.
+    pub heads: u32,
+}
+
+impl RigidDiskBlock {
+    /// Parse a Rigid Disk Block from raw data.
+    pub fn parse(buf: &[u8; 512]) -> Result<Self> {
+        check_rdb_block(buf, IDNAME_RIGIDDISK)?;
+        Ok(Self {
+            block_bytes: read_u32_be(buf, 0x10),
+            flags: read_u32_be(buf, 0x14),
+            bad_block_list: read_u32_be(buf, 0x18),
+            partition_list: read_u32_be(buf, 0x1C),
+            file_sys_header_list: read_u32_be(buf, 0x20),
+            cylinders: read_u32_be(buf, 0x40),
+            sectors: read_u32_be(buf, 0x44),
+            heads: read_u32_be(buf, 0x48),
+        })
+    }
+}
+
+/// Partition environment vector (`DosEnvVec`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DosEnvVec {
+    /// Sector size in longwords (`de_SizeBlock`).
+    pub size_block: u32,
+    /// Number of heads (`de_Surfaces`).
+    pub surfaces: u32,
+    /// Sectors per filesystem block (`de_SectorPerBlock`).
+    pub sectors_per_block: u32,
+    /// Sectors per track (`de_BlocksPerTrack`).
+    pub blocks_per_track: u32,
+    /// Reserved blocks at the start of the partition (`de_Reserved`).
+    pub reserved: u32,
+    /// First cylinder (`de_LowCyl`).
+    pub low_cyl: u32,
+    /// Last cylinder, inclusive (`de_HighCyl`).
+    pub high_cyl: u32,
+    /// Filesystem DOS type (`de_DosType`).
+    pub dos_type: u32,
+}
+
+impl DosEnvVec {
+    /// Parse an environment vector from the `PartitionBlock` at `offset`.
+    fn parse(buf: &[u8; 512], offset: usize) -> Self {
+        Self {
+            size_block: read_u32_be(buf, offset + 4),
+            surfaces: read_u32_be(buf, offset + 12),
+            sectors_per_block: read_u32_be(buf, offset + 16),
+            blocks_per_track: read_u32_be(buf, offset + 20),
+            reserved: read_u32_be(buf, offset + 24),
+            low_cyl: read_u32_be(buf, offset + 36),
+            high_cyl: read_u32_be(buf, offset + 40),
+            dos_type: read_u32_be(buf, offset + 64),
+        }
+    }
+
+    /// Get the sector size in bytes.
+    #[inline]
+    pub const fn sector_size(&self) -> u64 {
+        self.size_block as u64 * 4
+    }
+
+    /// Get the filesystem block size in bytes.
+    ///
+    /// Returns `AffsError::InvalidState` if the size overflows.
+    pub fn block_size(&self) -> Result<u64> {
+        self.sector_size()
+            .checked_mul(self.sectors_per_block.max(1) as u64)
+            .ok_or(AffsError::InvalidState)
+    }
+
+    /// Get the number of 512-byte sectors per cylinder.
+    ///
+    /// Returns `AffsError::InvalidState` if any geometry field is zero, the
+    /// sector size is not a multiple of 512 bytes, or the product overflows.
+    pub fn sectors_per_cylinder(&self) -> Result<u64> {
+        let sector_size = self.sector_size();
+        if self.surfaces == 0
+            || self.blocks_per_track == 0
+            || sector_size == 0
+            || !sector_size.is_multiple_of(512)
+        {
+            return Err(AffsError::InvalidState);
+        }
+        (self.surfaces as u64)
+            .checked_mul(self.blocks_per_track as u64)
+            .and_then(|sectors| sectors.checked_mul(sector_size / 512))
+            .ok_or(AffsError::InvalidState)
+    }
+
+    /// Get the first 512-byte sector of the partition.
+    ///
+    /// Returns `AffsError::InvalidState` if the geometry is invalid or the
+    /// sector number overflows.
+    pub fn start_sector(&self) -> Result<u64> {
+        (self.low_cyl as u64)
+            .checked_mul(self.sectors_per_cylinder()?)
+            .ok_or(AffsError::InvalidState)
+    }
+
+    /// Get the partition size in 512-byte sectors.
+    ///
+    /// Returns `AffsError::InvalidState` if `high_cyl` is below `low_cyl`,
+    /// the geometry is invalid, or the size overflows.
+    pub fn num_sectors(&self) -> Result<u64> {
+        let cylinders = self
+            .high_cyl
+            .checked_sub(self.low_cyl)
+            .ok_or(AffsError::InvalidState)? as u64
+            + 1;
+        cylinders
+            .checked_mul(self.sectors_per_cylinder()?)
+            .ok_or(AffsError::InvalidState)
+    }
+}
+
+/// Partition Block (`PART`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartitionBlock {
+    /// Next partition block.
+    pub next: u32,
+    /// Partition flags.
+    pub flags: u32,
+    /// Drive name length.
+    pub drive_name_len: u8,
+    /// Drive name (e.g. `DH0`).
+    pub drive_name: [u8; MAX_DRIVE_NAME_LEN],
+    /// Environment vector.
+    pub environment: DosEnvVec,
+}
+
+impl PartitionBlock {
+    /// Parse a Partition Block from raw data.
+    pub fn parse(buf: &[u8; 512]) -> Result<Self> {
+        check_rdb_block(buf, IDNAME_PARTITION)?;
+
+        let drive_name_len = buf[0x24].min(MAX_DRIVE_NAME_LEN as u8);
+        let mut drive_name = [0u8; MAX_DRIVE_NAME_LEN];
+        drive_name[..drive_name_len as usize]
+            .copy_from_slice(&buf[0x25..0x25 + drive_name_len as usize]);
+
+        Ok(Self {
+            next: read_u32_be(buf, 0x10),
+            flags: read_u32_be(buf, 0x14),
+            drive_name_len,
+            drive_name,
+            environment: DosEnvVec::parse(buf, ENVIRONMENT_OFFSET),
+        })
+    }
+
+    /// Get drive name as byte slice.
+    #[inline]
+    pub fn drive_name(&self) -> &[u8] {
+        &self.drive_name[..self.drive_name_len as usize]
+    }
+}
+
+/// A partition found in the RDB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartitionInfo {
+    /// Sector holding the partition block.
+    pub block: u32,
+    name: [u8; MAX_DRIVE_NAME_LEN],
+    name_len: u8,
+    /// Filesystem DOS type (e.g. `0x444F5301` for `DOS\1`).
+    pub dos_type: u32,
+    /// First 512-byte sector of the partition.
+    pub start_sector: u64,
+    /// Partition size in 512-byte sectors.
+    pub num_sectors: u64,
+    /// Filesystem block size in bytes.
+    pub block_size: u64,
+}
+
+impl PartitionInfo {
+    const EMPTY: Self = Self {
+        block: 0,
+        name: [0; MAX_DRIVE_NAME_LEN],
+        name_len: 0,
+        dos_type: 0,
+        start_sector: 0,
+        num_sectors: 0,
+        block_size: 0,
+    };
+
+    fn from_block(block: u32, part: &PartitionBlock) -> Result<Self> {
+        let env = &part.environment;
+        let start_sector = env.start_sector()?;
+        let num_sectors = env.num_sectors()?;
+        if start_sector.checked_add(num_sectors).is_none() {
+            return Err(AffsError::InvalidState);
+        }
+        Ok(Self {
+            block,
+            name: part.drive_name,
+            name_len: part.drive_name_len,
+            dos_type: env.dos_type,
+            start_sector,
+            num_sectors,
+            block_size: env.block_size()?,
+        })
+    }
+
+    /// Get partition name as byte slice.
+    #[inline]
+    pub fn name(&self) -> &[u8] {
+        &self.name[..self.name_len as usize]
+    }
+
+    /// Get partition name as str (if valid UTF-8).
+    #[inline]
+    pub fn name_str(&self) -> Option<&str> {
+        crate::utf8::from_utf8(self.name())
+    }
+}
+
+/// Partitions listed in an RDB, in list order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Partitions {
+    rdb: RigidDiskBlock,
+    parts: [PartitionInfo; MAX_PARTITIONS],
+    len: usize,
+}
+
+impl Partitions {
+    /// Get the parsed Rigid Disk Block.
+    #[inline]
+    pub const fn rdb(&self) -> &RigidDiskBlock {
+        &self.rdb
+    }
+
+    /// Get the partitions as a slice.
+    #[inline]
+    pub fn as_slice(&self) -> &[PartitionInfo] {
+        &self.parts[..self.len]
+    }
+
+    /// Get the number of partitions.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if no partitions were found.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the partition at `index`.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&PartitionInfo> {
+        self.as_slice().get(index)
+    }
+
+    /// Iterate over the partitions.
+    #[inline]
+    pub fn iter(&self) -> core::slice::Iter<'_, PartitionInfo> {
+        self.as_slice().iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Partitions {
+    type Item = &'a PartitionInfo;
+    type IntoIter = core::slice::Iter<'a, PartitionInfo>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Read a 512-byte sector, mapping failure to `BlockReadError`.
+fn read_sector<D: SectorDevice + ?Sized>(
+    device: &D,
+    sector: u64,
+    buf: &mut [u8; 512],
+) -> Result<()> {
+    device
+        .read_sector(sector, buf)
+        .map_err(|()| AffsError::BlockReadError)
+}
+
+/// Parse the RDB partition table of a hard disk image.
+///
+/// The `RDSK` block is searched for in the first [`RDB_LOCATION_LIMIT`]
+/// sectors. Returns `AffsError::InvalidBlockType` if none is found, and
+/// `AffsError::InvalidState` if the partition list is longer than
+/// [`MAX_PARTITIONS`] (which also catches cycles) or a partition's geometry
+/// is invalid or overflows.
+pub fn parse_rdb<D: SectorDevice + ?Sized>(device: &D) -> Result<Partitions> {
+    let mut buf = [0u8; 512];
+    let mut rdb = None;
+    for sector in 0..RDB_LOCATION_LIMIT {
+        read_sector(device, sector, &mut buf)?;
+        if read_u32_be(&buf, 0) == IDNAME_RIGIDDISK {
+            rdb = Some(RigidDiskBlock::parse(&buf)?);
+            break;
+        }
+    }
+    let rdb = rdb.ok_or(AffsError::InvalidBlockType)?;
+    if rdb.block_bytes != 512 {
+        return Err(AffsError::InvalidState);
+    }
+
+    let mut partitions = Partitions {
+        rdb,
+        parts: [PartitionInfo::EMPTY; MAX_PARTITIONS],
+        len: 0,
+    };
+    let mut next = rdb.partition_list;
+    while next != RDB_END_OF_LIST && next != 0 {
+        if partitions.len == MAX_PARTITIONS {
+            return Err(AffsError::InvalidState);
+        }
+        read_sector(device, next as u64, &mut buf)?;
+        let part = PartitionBlock::parse(&buf)?;
+        partitions.parts[partitions.len] = PartitionInfo::from_block(next, &part)?;
+        partitions.len += 1;
+        next = part.next;
+    }
+    Ok(partitions)
+}
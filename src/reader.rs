@@ -1,12 +1,26 @@
 //! Main AFFS reader interface.
 
-use crate::block::{BootBlock, EntryBlock, RootBlock};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+#[cfg(feature = "alloc")]
+use crate::block::compare_names;
+use crate::block::{
+    BitmapBlock, BitmapExtBlock, BootBlock, EntryBlock, RootBlock, check_entry_fields, hash_name,
+    names_equal,
+};
+use crate::checksum::{read_i32_be, read_u32_be};
 use crate::constants::*;
-use crate::dir::{DirEntry, DirIter};
+use crate::dir::{
+    CachedDirIter, DirCacheIter, DirEntry, DirIter, Directory, GlobIter, keeps_chain,
+};
 use crate::error::{AffsError, Result};
 use crate::file::FileReader;
-use crate::symlink::read_symlink_target;
-use crate::types::{BlockDevice, EntryType, FsFlags, FsType};
+use crate::symlink::{MAX_SYMLINK_LEN, max_utf8_len, read_symlink_target, symlink_target_utf8_len};
+use crate::types::{BlockDevice, BlockNum, EntryType, FsFlags, FsType};
+use crate::utf8::latin1_to_utf8;
+use crate::walk::WalkIter;
 
 /// Main AFFS filesystem reader.
 ///
@@ -40,7 +54,7 @@ use crate::types::{BlockDevice, EntryType, FsFlags, FsType};
 ///     println!("{:?}: {} bytes", entry.name(), entry.size);
 /// }
 /// ```
-pub struct AffsReader<'a, D: BlockDevice> {
+pub struct AffsReader<'a, D: BlockDevice + ?Sized> {
     device: &'a D,
     /// Boot block info.
     boot: BootBlock,
@@ -50,9 +64,57 @@ pub struct AffsReader<'a, D: BlockDevice> {
     root_block: u32,
     /// Total blocks on device.
     total_blocks: u32,
+    /// Mount options.
+    options: Options,
+    /// Most recent block that failed to read or parse (`NO_BLOCK` if none).
+    last_error_block: AtomicU32,
+}
+
+/// An [`AffsReader`] over a type-erased block device.
+///
+/// Lets readers over different device types be stored in one variable or
+/// collection. See [`AffsReader::new_dyn`].
+pub type DynAffsReader<'a> = AffsReader<'a, dyn BlockDevice + 'a>;
+
+/// Sentinel for "no failed block recorded".
+const NO_BLOCK: u32 = u32::MAX;
+
+impl<'a> AffsReader<'a, dyn BlockDevice + 'a> {
+    /// Create a new AFFS reader for a standard DD floppy over a `dyn`
+    /// device.
+    ///
+    /// Same as [`new`](Self::new), but any `&impl BlockDevice` coerces to
+    /// the argument, so the result is a [`DynAffsReader`] whatever the
+    /// device type.
+    pub fn new_dyn(device: &'a (dyn BlockDevice + 'a)) -> Result<Self> {
+        Self::new(device)
+    }
+}
+
+/// Options controlling how a volume is read.
+///
+/// The default is strict: every block checksum is verified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// Reject blocks whose checksum does not match.
+    ///
+    /// Set to `false` to recover data from damaged disks. Block types and
+    /// other structural checks are still enforced. The `parse_with`
+    /// constructors of the block types, such as
+    /// [`EntryBlock::parse_with`], take this flag directly: with it unset a
+    /// checksum mismatch is not an error.
+    pub verify_checksums: bool,
 }
 
-impl<'a, D: BlockDevice> AffsReader<'a, D> {
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            verify_checksums: true,
+        }
+    }
+}
+
+impl<'a, D: BlockDevice + ?Sized> AffsReader<'a, D> {
     /// Create a new AFFS reader for a standard DD floppy (880KB).
     pub fn new(device: &'a D) -> Result<Self> {
         Self::with_size(device, FLOPPY_DD_SECTORS)
@@ -64,17 +126,19 @@ impl<'a, D: BlockDevice> AffsReader<'a, D> {
     }
 
     /// Create a new AFFS reader with a specific block count.
+    ///
+    /// Returns `AffsError::EmptyVolume` if the boot block is valid but the
+    /// root block was never initialized (such as on an unformatted disk).
     pub fn with_size(device: &'a D, total_blocks: u32) -> Result<Self> {
-        // Read boot block (2 sectors)
-        let mut boot_buf = [0u8; BOOT_BLOCK_SIZE];
-        device
-            .read_block(0, array_ref_mut(&mut boot_buf, 0))
-            .map_err(|()| AffsError::BlockReadError)?;
-        device
-            .read_block(1, array_ref_mut(&mut boot_buf, BLOCK_SIZE))
-            .map_err(|()| AffsError::BlockReadError)?;
+        Self::with_options(device, total_blocks, Options::default())
+    }
 
-        let boot = BootBlock::parse(&boot_buf)?;
+    /// Create a new AFFS reader with a specific block count and options.
+    ///
+    /// With `verify_checksums` disabled, the volume can be opened and read
+    /// even if the root block or other blocks have bad checksums.
+    pub fn with_options(device: &'a D, total_blocks: u32, options: Options) -> Result<Self> {
+        let boot = Self::read_boot(device, options)?;
 
         // Calculate root block position (middle of disk)
         let root_block = if boot.root_block != 0 {
@@ -83,8 +147,70 @@ impl<'a, D: BlockDevice> AffsReader<'a, D> {
             total_blocks / 2
         };
 
+        Self::mount(device, boot, root_block, total_blocks, options)
+    }
+
+    /// Create a new AFFS reader, inferring the volume size.
+    ///
+    /// Each size in [`AUTO_SIZE_CANDIDATES`] is tried in turn: the device
+    /// must be able to read the last block, and the block in the middle must
+    /// be a root block with a valid checksum. The first match is mounted.
+    /// The boot block's root pointer is ignored here, since many HD images
+    /// carry the DD value of 880.
+    ///
+    /// Returns `AffsError::InvalidState` if no candidate size matches.
+    pub fn auto_size(device: &'a D) -> Result<Self> {
+        let options = Options::default();
+        let boot = Self::read_boot(device, options)?;
+
+        let mut buf = [0u8; BLOCK_SIZE];
+        for &total_blocks in AUTO_SIZE_CANDIDATES.iter() {
+            if device.read_block(total_blocks - 1, &mut buf).is_err() {
+                continue;
+            }
+            let root_block = total_blocks / 2;
+            if device.read_block(root_block, &mut buf).is_err() {
+                continue;
+            }
+            if RootBlock::parse(&buf).is_ok() {
+                trace!("auto size: {=u32} blocks", total_blocks);
+                return Self::mount(device, boot, root_block, total_blocks, options);
+            }
+        }
+
+        warn!("auto size: no candidate size has a valid root block");
+        Err(AffsError::InvalidState)
+    }
+
+    /// Read and parse the boot block (2 sectors).
+    fn read_boot(device: &D, options: Options) -> Result<BootBlock> {
+        let mut boot_buf = [0u8; BOOT_BLOCK_SIZE];
+        device
+            .read_block(0, array_ref_mut(&mut boot_buf, 0))
+            .map_err(|()| AffsError::BlockReadError)?;
+        device
+            .read_block(1, array_ref_mut(&mut boot_buf, BLOCK_SIZE))
+            .map_err(|()| AffsError::BlockReadError)?;
+
+        let boot = BootBlock::parse_with(&boot_buf, options.verify_checksums)?;
+        trace!("boot block: root block {=u32}", boot.root_block);
+        Ok(boot)
+    }
+
+    /// Read the root block and build the reader.
+    fn mount(
+        device: &'a D,
+        boot: BootBlock,
+        root_block: u32,
+        total_blocks: u32,
+        options: Options,
+    ) -> Result<Self> {
         // Validate root block is in range
         if root_block >= total_blocks {
+            warn!(
+                "root block {=u32} out of range ({=u32} blocks)",
+                root_block, total_blocks
+            );
             return Err(AffsError::BlockOutOfRange);
         }
 
@@ -94,7 +220,20 @@ impl<'a, D: BlockDevice> AffsReader<'a, D> {
             .read_block(root_block, &mut root_buf)
             .map_err(|()| AffsError::BlockReadError)?;
 
-        let root = RootBlock::parse(&root_buf)?;
+        let root = RootBlock::parse_with(&root_buf, options.verify_checksums).map_err(|err| {
+            if is_uninitialized_root(&root_buf) {
+                warn!("root block {=u32} is not initialized", root_block);
+                AffsError::EmptyVolume
+            } else {
+                err
+            }
+        })?;
+        trace!(
+            "mounted root block {=u32}, {=u32} blocks, {}",
+            root_block,
+            total_blocks,
+            boot.fs_type()
+        );
 
         Ok(Self {
             device,
@@ -102,9 +241,51 @@ impl<'a, D: BlockDevice> AffsReader<'a, D> {
             root,
             root_block,
             total_blocks,
+            options,
+            last_error_block: AtomicU32::new(NO_BLOCK),
         })
     }
 
+    /// Get the options this reader was opened with.
+    #[inline]
+    pub const fn options(&self) -> Options {
+        self.options
+    }
+
+    /// Whether block checksums are verified.
+    #[inline]
+    pub(crate) const fn verify_checksums(&self) -> bool {
+        self.options.verify_checksums
+    }
+
+    /// Get the parsed root block.
+    #[inline]
+    pub(crate) const fn root(&self) -> &RootBlock {
+        &self.root
+    }
+
+    /// Get the block that caused the most recent read or parse error.
+    ///
+    /// Recorded by [`read_entry`](Self::read_entry) and everything built on
+    /// it, including [`find_path`](Self::find_path),
+    /// [`read_dir`](Self::read_dir) and [`read_file`](Self::read_file).
+    /// Errors raised later by the returned iterators and file readers are
+    /// not recorded. Returns `None` if no such error has occurred.
+    pub fn last_error_block(&self) -> Option<u32> {
+        match self.last_error_block.load(Ordering::Relaxed) {
+            NO_BLOCK => None,
+            block => Some(block),
+        }
+    }
+
+    /// Record `block` as the failed block if `result` is an error.
+    fn track<T>(&self, block: u32, result: Result<T>) -> Result<T> {
+        if result.is_err() {
+            self.last_error_block.store(block, Ordering::Relaxed);
+        }
+        result
+    }
+
     /// Get the filesystem type (OFS or FFS).
     #[inline]
     pub const fn fs_type(&self) -> FsType {
@@ -117,12 +298,43 @@ impl<'a, D: BlockDevice> AffsReader<'a, D> {
         self.boot.fs_flags()
     }
 
+    /// Check if the disk is bootable.
+    ///
+    /// See [`BootBlock::is_bootable`].
+    #[inline]
+    pub const fn is_bootable(&self) -> bool {
+        self.boot.is_bootable()
+    }
+
     /// Check if international mode is enabled.
+    ///
+    /// This reports the INTL flag only. Use
+    /// [`uses_intl_hashing`](Self::uses_intl_hashing) for the case folding
+    /// that name lookups apply.
     #[inline]
     pub const fn is_intl(&self) -> bool {
         self.boot.fs_flags().intl
     }
 
+    /// Check if names are hashed with international case folding.
+    ///
+    /// See [`FsFlags::uses_intl_hashing`]. Lookups, globbing and sorting
+    /// all fold case this way.
+    #[inline]
+    pub const fn uses_intl_hashing(&self) -> bool {
+        self.boot.fs_flags().uses_intl_hashing()
+    }
+
+    /// Check if this is a long filename volume (`DOS\6` or `DOS\7`).
+    ///
+    /// Entries are read with the long filename layout. Names longer than 30
+    /// bytes do not fit in a [`DirEntry`] and are returned as
+    /// `AffsError::NameTooLong`.
+    #[inline]
+    pub const fn supports_long_names(&self) -> bool {
+        self.boot.fs_flags().long_names
+    }
+
     /// Get the root block number.
     #[inline]
     pub const fn root_block(&self) -> u32 {
@@ -135,6 +347,23 @@ impl<'a, D: BlockDevice> AffsReader<'a, D> {
         self.total_blocks
     }
 
+    /// Get the block size in bytes.
+    ///
+    /// Always [`BLOCK_SIZE`]; see [`AffsReaderVar`](crate::AffsReaderVar)
+    /// for larger blocks.
+    #[inline]
+    pub const fn block_size(&self) -> usize {
+        BLOCK_SIZE
+    }
+
+    /// Get the log2 block size (relative to 512).
+    ///
+    /// Always 0.
+    #[inline]
+    pub const fn log_blocksize(&self) -> u8 {
+        0
+    }
+
     /// Get the disk name as bytes.
     #[inline]
     pub fn disk_name(&self) -> &[u8] {
@@ -162,17 +391,43 @@ impl<'a, D: BlockDevice> AffsReader<'a, D> {
     }
 
     /// Get the volume creation date.
+    ///
+    /// This is the root block date at offset 0x1A4, which the AFFS spec
+    /// names the last root alteration date.
     #[inline]
     pub fn creation_date(&self) -> crate::date::AmigaDate {
         self.root.creation_date
     }
 
     /// Get the volume last modification date.
+    ///
+    /// Same as [`volume_modified`](Self::volume_modified).
     #[inline]
     pub fn last_modified(&self) -> crate::date::AmigaDate {
         self.root.last_modified
     }
 
+    /// Get the date the volume was last written.
+    ///
+    /// The root block holds three dates: root alteration (0x1A4, see
+    /// [`creation_date`](Self::creation_date)), disk alteration (0x1D8)
+    /// and filesystem creation (0x1E4, see
+    /// [`fs_creation_date`](Self::fs_creation_date)). This returns the disk
+    /// alteration date, which the filesystem updates on every change to
+    /// the volume.
+    #[inline]
+    pub fn volume_modified(&self) -> crate::date::AmigaDate {
+        self.root.last_modified
+    }
+
+    /// Get the filesystem creation date (root offset 0x1E4).
+    ///
+    /// Set when the volume is formatted.
+    #[inline]
+    pub fn fs_creation_date(&self) -> crate::date::AmigaDate {
+        self.root.fs_creation_date
+    }
+
     /// Get the volume modification time as Unix timestamp.
     ///
     /// This matches GRUB's `grub_affs_mtime()` behavior:
@@ -188,6 +443,130 @@ impl<'a, D: BlockDevice> AffsReader<'a, D> {
         self.root.bitmap_valid()
     }
 
+    /// Count the free blocks recorded in the allocation bitmap.
+    ///
+    /// Walks the bitmap pages from [`bitmap_blocks`](Self::bitmap_blocks).
+    /// Returns `AffsError::InvalidState` if the bitmap is not marked valid
+    /// or a required bitmap page is missing.
+    pub fn free_blocks(&self) -> Result<u32> {
+        if !self.bitmap_valid() {
+            return Err(AffsError::InvalidState);
+        }
+
+        let mut remaining = self.total_blocks.saturating_sub(RESERVED_BLOCKS);
+        let mut free = 0u32;
+        let mut buf = [0u8; BLOCK_SIZE];
+
+        for page in self.bitmap_blocks()? {
+            if remaining == 0 {
+                break;
+            }
+            free += self.count_bitmap_page(page?, &mut remaining, &mut buf)?;
+        }
+
+        if remaining > 0 {
+            return Err(AffsError::InvalidState);
+        }
+
+        Ok(free)
+    }
+
+    /// Count the used blocks (total blocks minus free blocks).
+    pub fn used_blocks(&self) -> Result<u32> {
+        Ok(self.total_blocks - self.free_blocks()?)
+    }
+
+    /// Get the free space in bytes.
+    ///
+    /// Computed as `free_blocks() * data_block_size()`, i.e. the file data
+    /// that could still be stored on the volume.
+    pub fn free_bytes(&self) -> Result<u64> {
+        Ok(self.free_blocks()? as u64 * self.fs_type().data_block_size() as u64)
+    }
+
+    /// Check if a block is marked free in the allocation bitmap.
+    ///
+    /// Returns `AffsError::BlockOutOfRange` if `block` is not tracked by the
+    /// bitmap (the boot blocks or past the end of the volume), and
+    /// `AffsError::InvalidState` if the bitmap is not marked valid.
+    pub fn is_block_free(&self, block: u32) -> Result<bool> {
+        if block < RESERVED_BLOCKS || block >= self.total_blocks {
+            return Err(AffsError::BlockOutOfRange);
+        }
+        if !self.bitmap_valid() {
+            return Err(AffsError::InvalidState);
+        }
+
+        let index = block - RESERVED_BLOCKS;
+        let page = self.bitmap_page(index / BM_BLOCKS_PER_PAGE)?;
+        if page == 0 || page >= self.total_blocks {
+            return Err(AffsError::InvalidState);
+        }
+
+        let mut buf = [0u8; BLOCK_SIZE];
+        self.device
+            .read_block(page, &mut buf)
+            .map_err(|()| AffsError::BlockReadError)?;
+        let bitmap = BitmapBlock::parse_with(&buf, self.verify_checksums())?;
+
+        Ok(bitmap.is_free((index % BM_BLOCKS_PER_PAGE) as usize))
+    }
+
+    /// Get the block number of the bitmap page at `page_index`.
+    ///
+    /// The first pages are listed in the root block, the rest are found by
+    /// following the bitmap extension chain.
+    fn bitmap_page(&self, page_index: u32) -> Result<u32> {
+        let page_index = page_index as usize;
+        if page_index < BM_PAGES_ROOT_SIZE {
+            return Ok(self.root.bm_pages[page_index]);
+        }
+
+        let ext_index = (page_index - BM_PAGES_ROOT_SIZE) / BM_PAGES_EXT_SIZE;
+        let slot = (page_index - BM_PAGES_ROOT_SIZE) % BM_PAGES_EXT_SIZE;
+
+        let mut buf = [0u8; BLOCK_SIZE];
+        let mut ext = self.root.bm_ext;
+        for _ in 0..ext_index {
+            if ext == 0 {
+                return Err(AffsError::InvalidState);
+            }
+            self.device
+                .read_block(ext, &mut buf)
+                .map_err(|()| AffsError::BlockReadError)?;
+            ext = BitmapExtBlock::parse(&buf).next;
+        }
+
+        if ext == 0 {
+            return Err(AffsError::InvalidState);
+        }
+        self.device
+            .read_block(ext, &mut buf)
+            .map_err(|()| AffsError::BlockReadError)?;
+        Ok(BitmapExtBlock::parse(&buf).bm_pages[slot])
+    }
+
+    /// Read one bitmap page and count the free blocks it tracks.
+    fn count_bitmap_page(
+        &self,
+        page: u32,
+        remaining: &mut u32,
+        buf: &mut [u8; BLOCK_SIZE],
+    ) -> Result<u32> {
+        if page == 0 || page >= self.total_blocks {
+            return Err(AffsError::InvalidState);
+        }
+
+        self.device
+            .read_block(page, buf)
+            .map_err(|()| AffsError::BlockReadError)?;
+        let bitmap = BitmapBlock::parse_with(buf, self.verify_checksums())?;
+
+        let bits = (*remaining).min(BM_BLOCKS_PER_PAGE);
+        *remaining -= bits;
+        Ok(bitmap.count_free(bits as usize))
+    }
+
     /// Get the root directory hash table.
     #[inline]
     pub fn root_hash_table(&self) -> &[u32; HASH_TABLE_SIZE] {
@@ -202,30 +581,209 @@ impl<'a, D: BlockDevice> AffsReader<'a, D> {
 
     /// Iterate over entries in the root directory.
     pub fn read_root_dir(&self) -> DirIter<'_, D> {
-        DirIter::new(self.device, self.root.hash_table, self.is_intl())
+        DirIter::new(
+            self.device,
+            self.root.hash_table,
+            self.uses_intl_hashing(),
+            self.supports_long_names(),
+            self.verify_checksums(),
+        )
     }
 
     /// Iterate over entries in a directory.
     ///
+    /// If `block` is a hard link to a directory, the real directory it
+    /// points to is listed.
+    ///
     /// # Arguments
     /// * `block` - Block number of the directory entry
-    pub fn read_dir(&self, block: u32) -> Result<DirIter<'_, D>> {
-        if block == self.root_block {
-            return Ok(self.read_root_dir());
+    pub fn read_dir(&self, block: impl Into<BlockNum>) -> Result<DirIter<'_, D>> {
+        let hash_table = self.dir_hash_table(block.into().get())?;
+        Ok(DirIter::new(
+            self.device,
+            hash_table,
+            self.uses_intl_hashing(),
+            self.supports_long_names(),
+            self.verify_checksums(),
+        ))
+    }
+
+    /// Get a handle to a directory.
+    ///
+    /// The handle implements [`IntoIterator`], so it can be used directly in
+    /// a `for` loop.
+    ///
+    /// # Arguments
+    /// * `block` - Block number of the directory entry
+    pub fn dir(&self, block: u32) -> Result<Directory<'_, D>> {
+        let hash_table = self.dir_hash_table(block)?;
+        Ok(Directory::new(
+            self.device,
+            hash_table,
+            self.uses_intl_hashing(),
+            self.supports_long_names(),
+            self.verify_checksums(),
+        ))
+    }
+
+    /// Iterate over entries in a directory whose names match `pattern`.
+    ///
+    /// The pattern supports `*` (any run of characters) and `?` (any single
+    /// character) and is matched case-insensitively, folding case as
+    /// [`uses_intl_hashing`](Self::uses_intl_hashing) says. See
+    /// [`glob_match`](crate::glob_match).
+    pub fn glob<'s>(&'s self, dir_block: u32, pattern: &'s [u8]) -> Result<GlobIter<'s, D>> {
+        Ok(GlobIter::new(self.read_dir(dir_block)?, pattern))
+    }
+
+    /// Iterate over entries in a directory using its DIRCACHE chain.
+    ///
+    /// On DIRCACHE volumes this follows the directory's `extension` pointer
+    /// to the cache blocks and builds entries from the packed records,
+    /// avoiding a block read per entry. If the volume has no DIRCACHE flag or
+    /// the directory has no cache, this falls back to the hash table.
+    ///
+    /// # Arguments
+    /// * `dir_block` - Block number of the directory entry
+    pub fn read_dir_cached(&self, dir_block: u32) -> Result<CachedDirIter<'_, D>> {
+        if !self.fs_flags().dircache {
+            return Ok(CachedDirIter::HashTable(self.read_dir(dir_block)?));
+        }
+
+        let (dir_block, hash_table, cache_block) = self.dir_header(dir_block)?;
+
+        if cache_block == 0 {
+            return Ok(CachedDirIter::HashTable(DirIter::new(
+                self.device,
+                hash_table,
+                self.uses_intl_hashing(),
+                self.supports_long_names(),
+                self.verify_checksums(),
+            )));
+        }
+
+        Ok(CachedDirIter::Cache(DirCacheIter::new(
+            self.device,
+            dir_block,
+            cache_block,
+            self.total_blocks,
+            self.verify_checksums(),
+        )))
+    }
+
+    /// Read the hash table of a directory (root or user directory).
+    ///
+    /// Hard links to directories are resolved to the real directory.
+    pub(crate) fn dir_hash_table(&self, block: u32) -> Result<[u32; HASH_TABLE_SIZE]> {
+        Ok(self.dir_header(block)?.1)
+    }
+
+    /// Read a directory's block number, hash table and extension pointer.
+    ///
+    /// A directory hard link has no hash table of its own, so its
+    /// `real_entry` is followed (up to `MAX_LINK_DEPTH` links) and the real
+    /// directory is returned.
+    fn dir_header(&self, block: u32) -> Result<(u32, [u32; HASH_TABLE_SIZE], u32)> {
+        let mut block = block;
+        let mut hops = LinkHops::new(self.total_blocks);
+
+        loop {
+            if block == self.root_block {
+                return Ok((block, self.root.hash_table, self.root.extension));
+            }
+
+            let entry = self.read_entry(block)?;
+            if entry.sec_type != ST_LDIR {
+                if !entry.is_dir() {
+                    return Err(AffsError::NotADirectory);
+                }
+                return Ok((block, entry.hash_table, entry.extension));
+            }
+            block = hops.follow(entry.real_entry)?;
         }
+    }
+
+    /// Walk the directory tree below `start_block` in depth-first order.
+    ///
+    /// Yields `(depth, entry)` pairs, where direct children of `start_block`
+    /// have depth 1. Only real directories are descended into; symlinks and
+    /// hard-linked directories are yielded but not followed. Nesting deeper
+    /// than 64 levels yields [`AffsError::InvalidState`]; use
+    /// [`WalkIter::new`] to pick a different limit.
+    pub fn walk(&self, start_block: u32) -> WalkIter<'_, D> {
+        WalkIter::new(self, start_block)
+    }
 
+    /// Count the entries in a directory without parsing them.
+    ///
+    /// Follows the hash table and chains reading only each block's type,
+    /// secondary type and `next_same_hash` pointer. Checksums are not
+    /// verified. Blocks with an unknown secondary type or an empty name are
+    /// skipped, matching [`DirIter`]. Returns `AffsError::InvalidState` if the chains visit more
+    /// blocks than the volume holds (which indicates a cycle).
+    pub fn count_entries(&self, dir_block: u32) -> Result<usize> {
+        let hash_table = self.dir_hash_table(dir_block)?;
         let mut buf = [0u8; BLOCK_SIZE];
-        self.device
-            .read_block(block, &mut buf)
-            .map_err(|()| AffsError::BlockReadError)?;
+        let mut count = 0;
+        let mut visited = 0u32;
+
+        for &head in hash_table.iter() {
+            let mut block = head;
+            while block != 0 {
+                visited += 1;
+                if visited > self.total_blocks {
+                    return Err(AffsError::InvalidState);
+                }
+                if block >= self.total_blocks {
+                    return Err(AffsError::BlockOutOfRange);
+                }
 
-        let entry = EntryBlock::parse(&buf)?;
+                self.device
+                    .read_block(block, &mut buf)
+                    .map_err(|()| AffsError::BlockReadError)?;
 
-        if !entry.is_dir() {
-            return Err(AffsError::NotADirectory);
+                if read_i32_be(&buf, 0) != T_HEADER {
+                    return Err(AffsError::InvalidBlockType);
+                }
+                if check_entry_fields(&buf, self.supports_long_names()).is_ok() {
+                    count += 1;
+                }
+
+                block = read_u32_be(&buf, 0x1F0);
+            }
         }
 
-        Ok(DirIter::new(self.device, entry.hash_table, self.is_intl()))
+        Ok(count)
+    }
+
+    /// Check that every entry in a directory sits in the right hash bucket.
+    ///
+    /// Returns `Ok(false)` if any entry is chained from a bucket other than
+    /// the one its name hashes to, which makes it unreachable by name
+    /// lookup. Returns `AffsError::InvalidState` if the chains visit more
+    /// blocks than the volume holds (which indicates a cycle).
+    pub fn verify_hash_placement(&self, dir_block: u32) -> Result<bool> {
+        let hash_table = self.dir_hash_table(dir_block)?;
+        let intl = self.uses_intl_hashing();
+        let mut visited = 0u32;
+
+        for (bucket, &head) in hash_table.iter().enumerate() {
+            let mut block = head;
+            while block != 0 {
+                visited += 1;
+                if visited > self.total_blocks {
+                    return Err(AffsError::InvalidState);
+                }
+
+                let (entry, next) = self.read_chain_entry(block)?;
+                if entry.is_some_and(|entry| hash_name(entry.name(), intl) != bucket) {
+                    return Ok(false);
+                }
+                block = next;
+            }
+        }
+
+        Ok(true)
     }
 
     /// Find an entry by name in a directory.
@@ -233,15 +791,87 @@ impl<'a, D: BlockDevice> AffsReader<'a, D> {
     /// # Arguments
     /// * `dir_block` - Block number of the directory
     /// * `name` - Name to search for
-    pub fn find_entry(&self, dir_block: u32, name: &[u8]) -> Result<DirEntry> {
-        let dir = self.read_dir(dir_block)?;
-        dir.find(name)
+    pub fn find_entry(&self, dir_block: impl Into<BlockNum>, name: &[u8]) -> Result<DirEntry> {
+        let intl = self.uses_intl_hashing();
+        self.find_entry_by(dir_block.into().get(), name, |candidate| {
+            names_equal(candidate, name, intl)
+        })
+    }
+
+    /// Find an entry by exact, case-sensitive name in a directory.
+    ///
+    /// Like [`find_entry`](Self::find_entry), but names are compared
+    /// byte-for-byte instead of case-insensitively.
+    pub fn find_entry_exact(&self, dir_block: u32, name: &[u8]) -> Result<DirEntry> {
+        self.find_entry_by(dir_block, name, |candidate| candidate == name)
+    }
+
+    /// Walk the hash chain for `name` in a directory, reading each entry
+    /// through [`read_entry`](Self::read_entry) so failures are recorded.
+    fn find_entry_by(
+        &self,
+        dir_block: u32,
+        name: &[u8],
+        matches: impl Fn(&[u8]) -> bool,
+    ) -> Result<DirEntry> {
+        if name.len() > MAX_NAME_LEN {
+            return Err(AffsError::NameTooLong);
+        }
+
+        let hash_table = self.dir_hash_table(dir_block)?;
+        let mut block = hash_table[hash_name(name, self.uses_intl_hashing())];
+
+        while block != 0 {
+            let (entry, next) = self.read_chain_entry(block)?;
+            if let Some(entry) = entry.filter(|entry| matches(entry.name())) {
+                return DirEntry::from_entry_block(block, &entry).ok_or(AffsError::InvalidSecType);
+            }
+            block = next;
+        }
+
+        Err(AffsError::EntryNotFound)
+    }
+
+    /// Read an entry in a hash chain along with the next block in the chain.
+    ///
+    /// Like [`read_entry`](Self::read_entry), but an entry rejected for its
+    /// contents or with a name too long to match comes back as `None` with
+    /// its chain pointer, so callers step past it.
+    fn read_chain_entry(&self, block: u32) -> Result<(Option<EntryBlock>, u32)> {
+        let mut buf = [0u8; BLOCK_SIZE];
+        let result = self
+            .device
+            .read_block(block, &mut buf)
+            .map_err(|()| AffsError::BlockReadError)
+            .and_then(|()| self.parse_entry(&buf));
+        match result {
+            Ok(entry) => {
+                let next = entry.next_same_hash;
+                Ok((Some(entry), next))
+            }
+            Err(e) if keeps_chain(e) => Ok((None, read_u32_be(&buf, 0x1F0))),
+            Err(e) => self.track(block, Err(e)),
+        }
     }
 
     /// Find an entry by path from the root.
     ///
     /// Path components are separated by '/'.
     pub fn find_path(&self, path: &[u8]) -> Result<DirEntry> {
+        self.find_path_with(path, false)
+    }
+
+    /// Find an entry by path from the root, checking parent pointers.
+    ///
+    /// Like [`find_path`](Self::find_path), but each entry found must
+    /// record the directory it was found in as its `parent`. Returns
+    /// `AffsError::InvalidState` on a mismatch, which indicates a
+    /// cross-linked tree.
+    pub fn find_path_strict(&self, path: &[u8]) -> Result<DirEntry> {
+        self.find_path_with(path, true)
+    }
+
+    fn find_path_with(&self, path: &[u8], strict: bool) -> Result<DirEntry> {
         let mut current_block = self.root_block;
         let mut final_entry: Option<DirEntry> = None;
 
@@ -254,6 +884,9 @@ impl<'a, D: BlockDevice> AffsReader<'a, D> {
             let component = &path[start..end];
             if !component.is_empty() {
                 let entry = self.find_entry(current_block, component)?;
+                if strict && entry.parent != current_block {
+                    return Err(AffsError::InvalidState);
+                }
 
                 if entry.is_dir() {
                     current_block = entry.block;
@@ -268,21 +901,205 @@ impl<'a, D: BlockDevice> AffsReader<'a, D> {
         final_entry.ok_or(AffsError::EntryNotFound)
     }
 
+    /// Check whether a path exists.
+    ///
+    /// The empty path and `/` name the root directory. Any error during
+    /// lookup, including read errors, is reported as `false`.
+    pub fn exists(&self, path: &[u8]) -> bool {
+        self.lookup(path).is_ok()
+    }
+
+    /// Check whether a path names a directory.
+    ///
+    /// Returns `Ok(false)` if the path does not exist. The empty path and
+    /// `/` name the root directory. Other lookup errors are propagated.
+    pub fn path_is_dir(&self, path: &[u8]) -> Result<bool> {
+        match self.lookup(path) {
+            Ok(entry) => Ok(entry.is_dir()),
+            Err(AffsError::EntryNotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Like [`find_path`](Self::find_path), but a path with no components
+    /// resolves to the root entry.
+    pub(crate) fn lookup(&self, path: &[u8]) -> Result<DirEntry> {
+        if path.iter().all(|&c| c == b'/') {
+            return Ok(self.root_entry());
+        }
+        self.find_path(path)
+    }
+
+    /// Find an entry by path from the root, following symlinks.
+    ///
+    /// Works like [`find_path`](Self::find_path), but when a path component
+    /// is a soft link its target is read and resolution continues from
+    /// there. Targets starting with `/` (or containing an Amiga `volume:`
+    /// prefix) are resolved from the root; other targets are resolved
+    /// relative to the directory containing the symlink.
+    ///
+    /// At most `max_hops` symlinks are followed before
+    /// `AffsError::SymlinkLoop` is returned. Each hop uses a stack frame,
+    /// so keep `max_hops` small on constrained targets.
+    pub fn find_path_resolving(&self, path: &[u8], max_hops: usize) -> Result<DirEntry> {
+        let mut hops = 0;
+        self.resolve_path_from(self.root_block, path, max_hops, &mut hops)
+    }
+
+    /// Resolve `path` starting at `dir_block`, following symlinks.
+    fn resolve_path_from(
+        &self,
+        dir_block: u32,
+        path: &[u8],
+        max_hops: usize,
+        hops: &mut usize,
+    ) -> Result<DirEntry> {
+        let mut current_block = dir_block;
+        let mut final_entry: Option<DirEntry> = None;
+
+        let mut start = 0;
+        while start < path.len() {
+            let end = memchr::memchr(b'/', &path[start..])
+                .map(|pos| start + pos)
+                .unwrap_or(path.len());
+
+            let component = &path[start..end];
+            if !component.is_empty() {
+                let mut entry = self.find_entry(current_block, component)?;
+
+                if entry.is_symlink() {
+                    if *hops >= max_hops {
+                        return Err(AffsError::SymlinkLoop);
+                    }
+                    *hops += 1;
+
+                    let mut target_buf = [0u8; max_utf8_len(MAX_SYMLINK_LEN)];
+                    let len = self.read_symlink(entry.block, &mut target_buf)?;
+                    let target = &target_buf[..len];
+
+                    let (base, target) = if let Some(rest) = target.strip_prefix(b"/") {
+                        (self.root_block, rest)
+                    } else if let Some(pos) = memchr::memchr(b':', target) {
+                        (self.root_block, &target[pos + 1..])
+                    } else {
+                        (current_block, target)
+                    };
+
+                    entry = if target.iter().all(|&c| c == b'/') {
+                        self.dir_entry_at(base)?
+                    } else {
+                        self.resolve_path_from(base, target, max_hops, hops)?
+                    };
+                }
+
+                if entry.is_dir() {
+                    current_block = entry.block;
+                }
+
+                final_entry = Some(entry);
+            }
+
+            start = end + 1;
+        }
+
+        final_entry.ok_or(AffsError::EntryNotFound)
+    }
+
+    /// Get the directory containing `entry`.
+    ///
+    /// The root is its own parent, so `cd ..` at the top stays there.
+    /// Returns `AffsError::BlockOutOfRange` if the parent pointer is 0 or
+    /// outside the volume.
+    pub fn parent_entry(&self, entry: &DirEntry) -> Result<DirEntry> {
+        if entry.block == self.root_block {
+            return Ok(self.root_entry());
+        }
+        if entry.parent == 0 || entry.parent >= self.total_blocks {
+            return Err(AffsError::BlockOutOfRange);
+        }
+        self.dir_entry_at(entry.parent)
+    }
+
+    /// Get a DirEntry for the entry stored at `block`.
+    fn dir_entry_at(&self, block: u32) -> Result<DirEntry> {
+        if block == self.root_block {
+            return Ok(self.root_entry());
+        }
+        let entry = self.read_entry(block)?;
+        DirEntry::from_entry_block(block, &entry).ok_or(AffsError::InvalidSecType)
+    }
+
     /// Read a file's contents.
     ///
     /// # Arguments
     /// * `block` - Block number of the file header
-    pub fn read_file(&self, block: u32) -> Result<FileReader<'_, D>> {
-        FileReader::new(self.device, self.fs_type(), block)
+    pub fn read_file(&self, block: impl Into<BlockNum>) -> Result<FileReader<'_, D>> {
+        let block = block.into().get();
+        let result = FileReader::open(
+            self.device,
+            self.fs_type(),
+            self.fs_flags(),
+            self.total_blocks,
+            block,
+            self.options,
+        );
+        self.track(block, result)
+    }
+
+    /// Read a raw block from the device.
+    ///
+    /// Returns `AffsError::BlockOutOfRange` if `block` is not below
+    /// [`total_blocks`](Self::total_blocks). The block is not parsed or
+    /// checksummed, so this works for any block, including ones the rest of
+    /// the API does not expose.
+    pub fn read_raw_block(&self, block: u32, buf: &mut [u8; BLOCK_SIZE]) -> Result<()> {
+        let result = if block >= self.total_blocks {
+            Err(AffsError::BlockOutOfRange)
+        } else {
+            self.device
+                .read_block(block, buf)
+                .map_err(|()| AffsError::BlockReadError)
+        };
+        self.track(block, result)
+    }
+
+    /// Parse an entry block read from this volume.
+    #[inline]
+    fn parse_entry(&self, buf: &[u8; BLOCK_SIZE]) -> Result<EntryBlock> {
+        EntryBlock::parse_with_flags(buf, self.verify_checksums(), self.fs_flags())
     }
 
     /// Read an entry block.
-    pub fn read_entry(&self, block: u32) -> Result<EntryBlock> {
+    pub fn read_entry(&self, block: impl Into<BlockNum>) -> Result<EntryBlock> {
+        let block = block.into().get();
         let mut buf = [0u8; BLOCK_SIZE];
-        self.device
+        let result = self
+            .device
             .read_block(block, &mut buf)
-            .map_err(|()| AffsError::BlockReadError)?;
-        EntryBlock::parse(&buf)
+            .map_err(|()| AffsError::BlockReadError)
+            .and_then(|()| self.parse_entry(&buf));
+        self.track(block, result)
+    }
+
+    /// Read the comment of an entry.
+    ///
+    /// The comment is converted from Latin-1 to UTF-8 and written into
+    /// `out`. Returns the number of bytes written, which is 0 for an entry
+    /// without a comment, or `AffsError::BufferTooSmall` if the converted
+    /// comment does not fit. A buffer of `max_utf8_len(MAX_COMMENT_LEN)`
+    /// bytes always suffices.
+    ///
+    /// # Arguments
+    /// * `block` - Block number of the entry
+    /// * `out` - Buffer to write the UTF-8 comment into
+    pub fn read_comment(&self, block: u32, out: &mut [u8]) -> Result<usize> {
+        let entry = self.read_entry(block)?;
+        let comment = entry.comment();
+        let len = comment.len() + comment.iter().filter(|&&b| b >= 0x80).count();
+        if len > out.len() {
+            return Err(AffsError::BufferTooSmall);
+        }
+        Ok(latin1_to_utf8(comment, out))
     }
 
     /// Read a symlink target.
@@ -292,13 +1109,14 @@ impl<'a, D: BlockDevice> AffsReader<'a, D> {
     /// * `out` - Buffer to write the UTF-8 symlink target into
     ///
     /// # Returns
-    /// The number of bytes written to `out`.
+    /// The number of bytes written to `out`, or
+    /// `AffsError::SymlinkTooLong` if the converted target does not fit.
     ///
     /// # Notes
     /// - The symlink target is stored as Latin1 and converted to UTF-8
     /// - Leading `:` (Amiga volume reference) is replaced with `/`
-    /// - The output buffer should be at least `MAX_SYMLINK_LEN * 2` bytes
-    ///   to handle worst-case Latin1 to UTF-8 expansion
+    /// - A buffer of `max_utf8_len(MAX_SYMLINK_LEN)` bytes always fits the
+    ///   worst-case Latin1 to UTF-8 expansion
     pub fn read_symlink(&self, block: u32, out: &mut [u8]) -> Result<usize> {
         let mut buf = [0u8; BLOCK_SIZE];
         self.device
@@ -306,11 +1124,14 @@ impl<'a, D: BlockDevice> AffsReader<'a, D> {
             .map_err(|()| AffsError::BlockReadError)?;
 
         // Verify this is a symlink
-        let entry = EntryBlock::parse(&buf)?;
+        let entry = self.parse_entry(&buf)?;
         if entry.entry_type() != Some(EntryType::SoftLink) {
             return Err(AffsError::NotASymlink);
         }
 
+        if symlink_target_utf8_len(&buf, BLOCK_SIZE) > out.len() {
+            return Err(AffsError::SymlinkTooLong);
+        }
         Ok(read_symlink_target(&buf, out))
     }
 
@@ -324,15 +1145,163 @@ impl<'a, D: BlockDevice> AffsReader<'a, D> {
         self.read_symlink(entry.block, out)
     }
 
+    /// Resolve a hard link to the entry it points to.
+    ///
+    /// Follows `real_entry` until a non-link entry is reached, so chained
+    /// links are resolved as well. Non-link entries are returned unchanged.
+    /// Returns `AffsError::InvalidState` if more than `MAX_LINK_DEPTH` links
+    /// are chained (which indicates a cycle).
+    pub fn resolve_link(&self, entry: &DirEntry) -> Result<DirEntry> {
+        let mut current = entry.clone();
+        let mut hops = LinkHops::new(self.total_blocks);
+
+        while matches!(
+            current.entry_type,
+            EntryType::HardLinkFile | EntryType::HardLinkDir
+        ) {
+            let target = hops.follow(current.real_entry)?;
+            let block = self.read_entry(target)?;
+            current =
+                DirEntry::from_entry_block(target, &block).ok_or(AffsError::InvalidSecType)?;
+        }
+
+        Ok(current)
+    }
+
+    /// Build the absolute path of an entry, such as `/subdir/inner`.
+    ///
+    /// Follows `parent` pointers up to the root directory, writing the
+    /// `/`-separated path into `out`. Names are copied as raw bytes. The root
+    /// directory itself is written as `/`.
+    ///
+    /// # Returns
+    /// The number of bytes written to `out`. Returns `AffsError::BufferTooSmall`
+    /// if the path does not fit, or `AffsError::InvalidState` if more than
+    /// `MAX_PATH_DEPTH` parents are followed (which indicates a cycle).
+    pub fn entry_path(&self, entry: &DirEntry, out: &mut [u8]) -> Result<usize> {
+        if entry.block == self.root_block {
+            let slash = out.first_mut().ok_or(AffsError::BufferTooSmall)?;
+            *slash = b'/';
+            return Ok(1);
+        }
+
+        // Build the path backwards from the end of `out`
+        let mut pos = out.len();
+        let mut parent = entry.parent;
+        prepend_component(out, &mut pos, entry.name())?;
+
+        let mut depth = 0;
+        while parent != self.root_block {
+            if depth >= MAX_PATH_DEPTH {
+                return Err(AffsError::InvalidState);
+            }
+            depth += 1;
+
+            if parent == 0 || parent >= self.total_blocks {
+                return Err(AffsError::BlockOutOfRange);
+            }
+
+            let dir = self.read_entry(parent)?;
+            prepend_component(out, &mut pos, dir.name())?;
+            parent = dir.parent;
+        }
+
+        let len = out.len() - pos;
+        out.copy_within(pos.., 0);
+        Ok(len)
+    }
+
     /// Get a DirEntry for the root directory.
     pub fn root_entry(&self) -> DirEntry {
         DirEntry::from_root(&self.root, self.root_block)
     }
 }
 
+#[cfg(feature = "alloc")]
+impl<D: BlockDevice + ?Sized> AffsReader<'_, D> {
+    /// Read all entries of a directory into a `Vec`.
+    ///
+    /// Entries are in hash table order, as yielded by
+    /// [`read_dir`](Self::read_dir). The first error ends the listing and is
+    /// returned. The result does not borrow the reader.
+    pub fn list_dir(&self, block: u32) -> Result<Vec<DirEntry>> {
+        self.read_dir(block)?.collect()
+    }
+
+    /// Read all entries of a directory, sorted by name.
+    ///
+    /// Names are compared case-insensitively, folding case the way the
+    /// volume does (international or ASCII), and names that differ only in
+    /// case are ordered by their raw bytes. This allocates a `Vec` holding
+    /// every entry; use [`read_dir`](Self::read_dir) to stream instead.
+    pub fn read_dir_sorted(&self, block: u32) -> Result<Vec<DirEntry>> {
+        let mut entries = self.list_dir(block)?;
+        let intl = self.uses_intl_hashing();
+        entries.sort_by(|a, b| compare_names(a.name(), b.name(), intl));
+        Ok(entries)
+    }
+}
+
+/// Check whether a root block that failed to parse was never written.
+///
+/// That is the case if it has no block type, no volume name and no valid
+/// bitmap flag, which includes an all-zero block. A block with any type set
+/// is left to report its own parse error.
+pub(crate) fn is_uninitialized_root(buf: &[u8; BLOCK_SIZE]) -> bool {
+    read_i32_be(buf, 0) == 0 && buf[0x1B0] == 0 && read_i32_be(buf, 0x138) != BM_VALID
+}
+
+/// Hop counter for following hard links.
+///
+/// Each call to [`follow`](Self::follow) checks one link's `real_entry`
+/// and returns it as the next block to read. More than `MAX_LINK_DEPTH`
+/// hops return `AffsError::InvalidState`, which indicates a cycle.
+pub(crate) struct LinkHops {
+    depth: usize,
+    total_blocks: u32,
+}
+
+impl LinkHops {
+    /// Start following links on a volume of `total_blocks` blocks.
+    #[inline]
+    pub(crate) const fn new(total_blocks: u32) -> Self {
+        Self {
+            depth: 0,
+            total_blocks,
+        }
+    }
+
+    /// Take one hop to a link's `real_entry`.
+    ///
+    /// Returns `AffsError::BlockOutOfRange` for a target that is 0 or past
+    /// the end of the volume.
+    pub(crate) fn follow(&mut self, real_entry: u32) -> Result<u32> {
+        if self.depth >= MAX_LINK_DEPTH {
+            return Err(AffsError::InvalidState);
+        }
+        self.depth += 1;
+        if real_entry == 0 || real_entry >= self.total_blocks {
+            return Err(AffsError::BlockOutOfRange);
+        }
+        Ok(real_entry)
+    }
+}
+
+/// Write `/name` immediately before `out[*pos..]`.
+#[inline]
+fn prepend_component(out: &mut [u8], pos: &mut usize, name: &[u8]) -> Result<()> {
+    let start = pos
+        .checked_sub(name.len() + 1)
+        .ok_or(AffsError::BufferTooSmall)?;
+    out[start] = b'/';
+    out[start + 1..*pos].copy_from_slice(name);
+    *pos = start;
+    Ok(())
+}
+
 /// Helper to get a mutable array reference from a slice.
 #[inline]
-fn array_ref_mut(slice: &mut [u8], offset: usize) -> &mut [u8; BLOCK_SIZE] {
+pub(crate) fn array_ref_mut(slice: &mut [u8], offset: usize) -> &mut [u8; BLOCK_SIZE] {
     (&mut slice[offset..offset + BLOCK_SIZE])
         .try_into()
         .expect("slice size mismatch")
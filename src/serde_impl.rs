@@ -0,0 +1,156 @@
+//! Serde support for types with fixed-size name buffers.
+//!
+//! Names and comments are serialized as strings when they are valid UTF-8
+//! and as byte sequences otherwise, so that JSON output stays readable.
+
+use core::fmt;
+
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::constants::*;
+use crate::date::AmigaDate;
+use crate::dir::DirEntry;
+use crate::types::{Access, EntryType};
+
+/// Borrowed name or comment, trimmed to its length.
+struct Text<'a>(&'a [u8]);
+
+impl Serialize for Text<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match crate::utf8::from_utf8(self.0) {
+            Some(s) => serializer.serialize_str(s),
+            None => serializer.serialize_bytes(self.0),
+        }
+    }
+}
+
+/// Owned name or comment with a fixed capacity.
+struct TextBuf<const N: usize> {
+    buf: [u8; N],
+    len: u8,
+}
+
+impl<const N: usize> TextBuf<N> {
+    fn from_slice<E: de::Error>(bytes: &[u8]) -> Result<Self, E> {
+        if bytes.len() > N {
+            return Err(E::invalid_length(
+                bytes.len(),
+                &"a name within the AFFS limit",
+            ));
+        }
+        let mut buf = [0u8; N];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        Ok(Self {
+            buf,
+            len: bytes.len() as u8,
+        })
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for TextBuf<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TextVisitor<const N: usize>;
+
+        impl<'de, const N: usize> Visitor<'de> for TextVisitor<N> {
+            type Value = TextBuf<N>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a string or byte sequence of at most {N} bytes")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                TextBuf::from_slice(v.as_bytes())
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                TextBuf::from_slice(v)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut buf = [0u8; N];
+                let mut len = 0;
+                while let Some(byte) = seq.next_element::<u8>()? {
+                    if len == N {
+                        return Err(de::Error::invalid_length(
+                            len + 1,
+                            &"a name within the AFFS limit",
+                        ));
+                    }
+                    buf[len] = byte;
+                    len += 1;
+                }
+                Ok(TextBuf {
+                    buf,
+                    len: len as u8,
+                })
+            }
+        }
+
+        deserializer.deserialize_any(TextVisitor::<N>)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename = "DirEntry")]
+struct DirEntryRef<'a> {
+    name: Text<'a>,
+    entry_type: EntryType,
+    block: u32,
+    parent: u32,
+    size: u32,
+    access: Access,
+    date: AmigaDate,
+    real_entry: u32,
+    comment: Text<'a>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "DirEntry")]
+struct DirEntryOwned {
+    name: TextBuf<MAX_NAME_LEN>,
+    entry_type: EntryType,
+    block: u32,
+    parent: u32,
+    size: u32,
+    access: Access,
+    date: AmigaDate,
+    real_entry: u32,
+    comment: TextBuf<MAX_COMMENT_LEN>,
+}
+
+impl Serialize for DirEntry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        DirEntryRef {
+            name: Text(self.name()),
+            entry_type: self.entry_type,
+            block: self.block,
+            parent: self.parent,
+            size: self.size,
+            access: self.access,
+            date: self.date,
+            real_entry: self.real_entry,
+            comment: Text(self.comment()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DirEntry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let e = DirEntryOwned::deserialize(deserializer)?;
+        Ok(Self {
+            name: e.name.buf,
+            name_len: e.name.len,
+            entry_type: e.entry_type,
+            block: e.block,
+            parent: e.parent,
+            size: e.size,
+            access: e.access,
+            date: e.date,
+            real_entry: e.real_entry,
+            comment: e.comment.buf,
+            comment_len: e.comment.len,
+        })
+    }
+}
@@ -0,0 +1,55 @@
+//! Volume-wide statistics.
+
+use crate::error::Result;
+use crate::reader::AffsReader;
+use crate::types::{BlockDevice, EntryType};
+
+/// Summary statistics for a directory tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FsStats {
+    /// Number of files (including hard links to files).
+    pub total_files: u32,
+    /// Number of directories (including hard links to directories).
+    pub total_dirs: u32,
+    /// Number of soft links.
+    pub total_symlinks: u32,
+    /// Sum of all file sizes in bytes.
+    pub total_bytes: u64,
+    /// Largest file as `(header block, size)`, if any.
+    pub largest_file: Option<(u32, u32)>,
+    /// Deepest nesting level (direct children of the root have depth 1).
+    pub max_depth: u32,
+}
+
+impl<D: BlockDevice + ?Sized> AffsReader<'_, D> {
+    /// Gather statistics for the whole volume.
+    ///
+    /// Walks the tree from the root with [`AffsReader::walk`], so directory
+    /// cycles are bounded by the walk's depth limit. Hard links are counted
+    /// but their sizes are not added to `total_bytes`.
+    pub fn stats(&self) -> Result<FsStats> {
+        let mut stats = FsStats::default();
+
+        for item in self.walk(self.root_block()) {
+            let (depth, entry) = item?;
+            stats.max_depth = stats.max_depth.max(depth);
+
+            if entry.is_symlink() {
+                stats.total_symlinks += 1;
+            } else if entry.is_dir() {
+                stats.total_dirs += 1;
+            } else if entry.is_file() {
+                stats.total_files += 1;
+            }
+
+            if entry.entry_type == EntryType::File {
+                stats.total_bytes += entry.size as u64;
+                if stats.largest_file.is_none_or(|(_, size)| entry.size > size) {
+                    stats.largest_file = Some((entry.block, entry.size));
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+}
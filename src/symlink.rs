@@ -1,6 +1,7 @@
 //! Symlink reading functionality.
 
 use crate::constants::*;
+use crate::utf8::latin1_to_utf8;
 
 /// Maximum symlink target length.
 ///
@@ -39,22 +40,35 @@ pub fn read_symlink_target(buf: &[u8; BLOCK_SIZE], out: &mut [u8]) -> usize {
 /// # Returns
 /// The number of bytes written to `out`.
 pub fn read_symlink_target_with_block_size(buf: &[u8], block_size: usize, out: &mut [u8]) -> usize {
+    // Convert Latin1 to UTF-8 with `:` -> `/` replacement
+    latin1_to_utf8_symlink(symlink_latin1(buf, block_size), out)
+}
+
+/// Number of UTF-8 bytes needed for the symlink target in `buf`.
+///
+/// This is the length [`read_symlink_target_with_block_size`] returns when
+/// `out` is large enough.
+pub(crate) fn symlink_target_utf8_len(buf: &[u8], block_size: usize) -> usize {
+    let latin1 = symlink_latin1(buf, block_size);
+    // Bytes >= 0x80 take two UTF-8 bytes; `:` -> `/` keeps the length
+    latin1.len() + latin1.iter().filter(|&&b| b >= 0x80).count()
+}
+
+/// Get the null-terminated Latin1 symlink target from an entry block.
+fn symlink_latin1(buf: &[u8], block_size: usize) -> &[u8] {
     // Calculate symlink data region
     let symlink_start = SYMLINK_OFFSET;
     let symlink_end = block_size.saturating_sub(FILE_LOCATION);
 
     if symlink_start >= symlink_end || symlink_start >= buf.len() {
-        return 0;
+        return &[];
     }
 
     let symlink_end = symlink_end.min(buf.len());
     let latin1 = &buf[symlink_start..symlink_end];
 
     let len = memchr::memchr(0, latin1).unwrap_or(latin1.len());
-    let latin1 = &latin1[..len];
-
-    // Convert Latin1 to UTF-8 with `:` -> `/` replacement
-    latin1_to_utf8_symlink(latin1, out)
+    &latin1[..len]
 }
 
 /// Convert Latin1 bytes to UTF-8, replacing leading `:` with `/`.
@@ -69,32 +83,14 @@ pub fn read_symlink_target_with_block_size(buf: &[u8], block_size: usize, out: &
 /// # Returns
 /// Number of bytes written to `out`.
 fn latin1_to_utf8_symlink(latin1: &[u8], out: &mut [u8]) -> usize {
-    let mut out_pos = 0;
-
-    for (i, &byte) in latin1.iter().enumerate() {
-        // Replace leading `:` with `/`
-        let byte = if i == 0 && byte == b':' { b'/' } else { byte };
-
-        if byte < 0x80 {
-            // ASCII - direct copy
-            if out_pos >= out.len() {
-                break;
-            }
-            out[out_pos] = byte;
-            out_pos += 1;
-        } else {
-            // Latin1 high byte (0x80-0xFF) -> UTF-8 two-byte sequence
-            // UTF-8: 110xxxxx 10xxxxxx
-            if out_pos + 1 >= out.len() {
-                break;
-            }
-            out[out_pos] = 0xC0 | (byte >> 6);
-            out[out_pos + 1] = 0x80 | (byte & 0x3F);
-            out_pos += 2;
+    // Replace leading `:` with `/`
+    match latin1.split_first() {
+        Some((b':', rest)) if !out.is_empty() => {
+            out[0] = b'/';
+            1 + latin1_to_utf8(rest, &mut out[1..])
         }
+        _ => latin1_to_utf8(latin1, out),
     }
-
-    out_pos
 }
 
 /// Calculate maximum UTF-8 length for a Latin1 string.
@@ -109,6 +105,17 @@ pub const fn max_utf8_len(latin1_len: usize) -> usize {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_symlink_target_utf8_len() {
+        let mut buf = [0u8; BLOCK_SIZE];
+        buf[SYMLINK_OFFSET..SYMLINK_OFFSET + 5].copy_from_slice(b":caf\xE9");
+        assert_eq!(symlink_target_utf8_len(&buf, BLOCK_SIZE), 6);
+
+        let mut out = [0u8; 16];
+        assert_eq!(read_symlink_target(&buf, &mut out), 6);
+        assert_eq!(&out[..6], "/café".as_bytes());
+    }
+
     #[test]
     fn test_latin1_to_utf8_ascii() {
         let input = b"hello";
@@ -0,0 +1,55 @@
+//! Conversions between dates and `time` types.
+
+use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time};
+
+use crate::date::{AmigaDate, DateOutOfRange, DateTime, NANOS_PER_TICK};
+
+impl From<AmigaDate> for OffsetDateTime {
+    /// Convert via [`AmigaDate::to_unix_timestamp`], keeping sub-second ticks.
+    ///
+    /// Dates beyond the `time` range saturate to its maximum (year 9999).
+    fn from(date: AmigaDate) -> Self {
+        let nanos = date.ticks.rem_euclid(50) as u32 * NANOS_PER_TICK;
+        OffsetDateTime::from_unix_timestamp(date.to_unix_timestamp())
+            .and_then(|dt| dt.replace_nanosecond(nanos))
+            .unwrap_or(PrimitiveDateTime::MAX.assume_utc())
+    }
+}
+
+impl TryFrom<OffsetDateTime> for AmigaDate {
+    type Error = DateOutOfRange;
+
+    /// Convert to an Amiga date, truncating to whole ticks.
+    fn try_from(dt: OffsetDateTime) -> Result<Self, Self::Error> {
+        let mut date = AmigaDate::from_unix_timestamp(dt.unix_timestamp()).ok_or(DateOutOfRange)?;
+        date.ticks += (dt.nanosecond() / NANOS_PER_TICK) as i32;
+        Ok(date)
+    }
+}
+
+impl TryFrom<DateTime> for PrimitiveDateTime {
+    type Error = time::error::ComponentRange;
+
+    /// Convert a decoded date, failing if a field is out of range.
+    fn try_from(dt: DateTime) -> Result<Self, Self::Error> {
+        let date = Date::from_calendar_date(dt.year as i32, Month::try_from(dt.month)?, dt.day)?;
+        let time = Time::from_hms(dt.hour, dt.minute, dt.second)?;
+        Ok(PrimitiveDateTime::new(date, time))
+    }
+}
+
+impl From<PrimitiveDateTime> for DateTime {
+    /// Convert to a decoded date, dropping sub-second precision.
+    ///
+    /// Years before 0 clamp to 0.
+    fn from(dt: PrimitiveDateTime) -> Self {
+        Self {
+            year: dt.year().max(0) as u16,
+            month: dt.month() as u8,
+            day: dt.day(),
+            hour: dt.hour(),
+            minute: dt.minute(),
+            second: dt.second(),
+        }
+    }
+}
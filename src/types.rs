@@ -1,5 +1,7 @@
 //! Core types for AFFS.
 
+use core::fmt::Write;
+
 /// Block device trait for reading blocks from storage.
 ///
 /// Implement this trait for your storage medium (file, memory, hardware, etc.).
@@ -14,6 +16,30 @@ pub trait BlockDevice {
     /// `Ok(())` on success, `Err(())` on failure.
     #[allow(clippy::result_unit_err)]
     fn read_block(&self, block: u32, buf: &mut [u8; 512]) -> Result<(), ()>;
+
+    /// Read consecutive 512-byte blocks starting at `start`.
+    ///
+    /// The default implementation calls [`read_block`](Self::read_block)
+    /// once per block. Override it when the storage can transfer several
+    /// blocks in one request.
+    ///
+    /// # Returns
+    /// `Ok(())` if every block was read, `Err(())` otherwise.
+    #[allow(clippy::result_unit_err)]
+    fn read_blocks(&self, start: u32, bufs: &mut [[u8; 512]]) -> Result<(), ()> {
+        for (i, buf) in bufs.iter_mut().enumerate() {
+            let block = start.checked_add(i as u32).ok_or(())?;
+            self.read_block(block, buf)?;
+        }
+        Ok(())
+    }
+
+    /// Hint that `block` is likely to be read soon.
+    ///
+    /// Devices with readahead can start fetching it in the background. The
+    /// default does nothing.
+    #[inline]
+    fn prefetch(&self, _block: u32) {}
 }
 
 /// Sector device trait for reading 512-byte sectors.
@@ -35,7 +61,7 @@ pub trait SectorDevice {
 }
 
 /// Blanket implementation: any BlockDevice is also a SectorDevice.
-impl<T: BlockDevice> SectorDevice for T {
+impl<T: BlockDevice + ?Sized> SectorDevice for T {
     fn read_sector(&self, sector: u64, buf: &mut [u8; 512]) -> Result<(), ()> {
         self.read_block(sector as u32, buf)
     }
@@ -43,6 +69,8 @@ impl<T: BlockDevice> SectorDevice for T {
 
 /// Filesystem type.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum FsType {
     /// Original File System.
     Ofs,
@@ -61,8 +89,86 @@ impl FsType {
     }
 }
 
+impl core::fmt::Display for FsType {
+    /// Format as `OFS` or `FFS`.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::Ofs => "OFS",
+            Self::Ffs => "FFS",
+        })
+    }
+}
+
+impl core::str::FromStr for FsType {
+    type Err = ParseTypeError;
+
+    /// Parse the [`Display`](core::fmt::Display) form, ignoring ASCII case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("OFS") {
+            Ok(Self::Ofs)
+        } else if s.eq_ignore_ascii_case("FFS") {
+            Ok(Self::Ffs)
+        } else {
+            Err(ParseTypeError)
+        }
+    }
+}
+
+/// Error returned when parsing an [`FsType`] or [`EntryType`] from a string fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseTypeError;
+
+impl core::fmt::Display for ParseTypeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("unrecognized type name")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseTypeError {}
+
+/// Primary block type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BlockType {
+    /// Header block (root, directory, file, or link).
+    Header,
+    /// OFS data block.
+    Data,
+    /// File extension block.
+    List,
+    /// Directory cache block.
+    DirCache,
+}
+
+impl BlockType {
+    /// Create from block type value.
+    pub const fn from_i32(block_type: i32) -> Option<Self> {
+        match block_type {
+            crate::T_HEADER => Some(Self::Header),
+            crate::T_DATA => Some(Self::Data),
+            crate::T_LIST => Some(Self::List),
+            crate::T_DIRC => Some(Self::DirCache),
+            _ => None,
+        }
+    }
+
+    /// Get the raw block type value.
+    #[inline]
+    pub const fn as_i32(self) -> i32 {
+        match self {
+            Self::Header => crate::T_HEADER,
+            Self::Data => crate::T_DATA,
+            Self::List => crate::T_LIST,
+            Self::DirCache => crate::T_DIRC,
+        }
+    }
+}
+
 /// Entry type in the filesystem.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum EntryType {
     /// Root directory.
     Root,
@@ -105,28 +211,127 @@ impl EntryType {
     }
 }
 
+impl EntryType {
+    const NAMES: [(Self, &'static str); 6] = [
+        (Self::Root, "root"),
+        (Self::Dir, "dir"),
+        (Self::File, "file"),
+        (Self::HardLinkFile, "hardlink-file"),
+        (Self::HardLinkDir, "hardlink-dir"),
+        (Self::SoftLink, "softlink"),
+    ];
+}
+
+impl core::fmt::Display for EntryType {
+    /// Format as one of `root`, `dir`, `file`, `hardlink-file`,
+    /// `hardlink-dir` or `softlink`.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let name = Self::NAMES
+            .iter()
+            .find(|(ty, _)| ty == self)
+            .map_or("", |(_, name)| name);
+        f.write_str(name)
+    }
+}
+
+impl core::str::FromStr for EntryType {
+    type Err = ParseTypeError;
+
+    /// Parse the [`Display`](core::fmt::Display) form, ignoring ASCII case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::NAMES
+            .iter()
+            .find(|(_, name)| s.eq_ignore_ascii_case(name))
+            .map(|&(ty, _)| ty)
+            .ok_or(ParseTypeError)
+    }
+}
+
 /// Filesystem flags.
 #[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct FsFlags {
     /// International mode enabled.
     pub intl: bool,
     /// Directory cache enabled.
     pub dircache: bool,
+    /// Long filename variant (`DOS\6` or `DOS\7`).
+    pub long_names: bool,
 }
 
 impl FsFlags {
     /// Create flags from DOS type byte.
+    ///
+    /// `DOS\6` and `DOS\7` are the long filename variants. They reuse the
+    /// INTL and DIRCACHE bits, so they are reported as international with
+    /// no directory cache.
     #[inline]
     pub const fn from_dos_type(dos_type: u8) -> Self {
+        let long_names = (dos_type & crate::DOSFS_LONGNAME) == crate::DOSFS_LONGNAME;
         Self {
-            intl: (dos_type & crate::DOSFS_INTL) != 0,
-            dircache: (dos_type & crate::DOSFS_DIRCACHE) != 0,
+            intl: long_names || (dos_type & crate::DOSFS_INTL) != 0,
+            dircache: !long_names && (dos_type & crate::DOSFS_DIRCACHE) != 0,
+            long_names,
         }
     }
+
+    /// Check if names are hashed with international case folding.
+    ///
+    /// AmigaDOS always uses international hashing on directory cache
+    /// volumes (`DOS\4` and `DOS\5`), even though their INTL bit is clear.
+    /// ADFlib and the Linux affs driver apply the same rule.
+    #[inline]
+    pub const fn uses_intl_hashing(&self) -> bool {
+        self.intl || self.dircache
+    }
+}
+
+/// A block number on the device.
+///
+/// [`AffsReader`](crate::AffsReader) methods taking a block accept either a
+/// `BlockNum` or a plain `u32`, so `reader.read_file(882)` keeps working.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BlockNum(pub u32);
+
+impl BlockNum {
+    /// Create from a raw block number.
+    #[inline]
+    pub const fn new(block: u32) -> Self {
+        Self(block)
+    }
+
+    /// Get the raw block number.
+    #[inline]
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+}
+
+impl From<u32> for BlockNum {
+    #[inline]
+    fn from(block: u32) -> Self {
+        Self(block)
+    }
+}
+
+impl From<BlockNum> for u32 {
+    #[inline]
+    fn from(block: BlockNum) -> Self {
+        block.0
+    }
+}
+
+impl core::fmt::Display for BlockNum {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
 }
 
 /// Access permissions.
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Access(pub u32);
 
 impl Access {
@@ -183,4 +388,190 @@ impl Access {
     pub const fn is_hold(self) -> bool {
         (self.0 & crate::ACC_HOLD) != 0
     }
+
+    /// Get the set of protection flags that are set.
+    #[inline]
+    pub const fn flags(self) -> AccessFlags {
+        AccessFlags(self.as_protection_bits() as u8)
+    }
+
+    /// Get the eight `hsparwed` protection bits.
+    ///
+    /// The bits are as stored on disk, so the `rwed` bits are set when the
+    /// operation is denied. Any higher (multi-user) bits are dropped.
+    #[inline]
+    pub const fn as_protection_bits(self) -> u32 {
+        self.0 & 0xFF
+    }
+}
+
+/// A single AmigaDOS protection flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AccessFlag {
+    /// Hold (`h`).
+    Hold,
+    /// Script (`s`).
+    Script,
+    /// Pure (`p`).
+    Pure,
+    /// Archived (`a`).
+    Archive,
+    /// Read protected (`r`).
+    Read,
+    /// Write protected (`w`).
+    Write,
+    /// Execute protected (`e`).
+    Execute,
+    /// Delete protected (`d`).
+    Delete,
+}
+
+impl AccessFlag {
+    /// All flags, in `hsparwed` order.
+    pub const ALL: [Self; 8] = [
+        Self::Hold,
+        Self::Script,
+        Self::Pure,
+        Self::Archive,
+        Self::Read,
+        Self::Write,
+        Self::Execute,
+        Self::Delete,
+    ];
+
+    /// Get the flag's protection bit.
+    #[inline]
+    pub const fn bit(self) -> u32 {
+        match self {
+            Self::Hold => crate::ACC_HOLD,
+            Self::Script => crate::ACC_SCRIPT,
+            Self::Pure => crate::ACC_PURE,
+            Self::Archive => crate::ACC_ARCHIVE,
+            Self::Read => crate::ACC_READ,
+            Self::Write => crate::ACC_WRITE,
+            Self::Execute => crate::ACC_EXECUTE,
+            Self::Delete => crate::ACC_DELETE,
+        }
+    }
+
+    /// Get the flag's letter in a protection string.
+    #[inline]
+    pub const fn letter(self) -> char {
+        match self {
+            Self::Hold => 'h',
+            Self::Script => 's',
+            Self::Pure => 'p',
+            Self::Archive => 'a',
+            Self::Read => 'r',
+            Self::Write => 'w',
+            Self::Execute => 'e',
+            Self::Delete => 'd',
+        }
+    }
+
+    /// Get the flag's name.
+    #[inline]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Hold => "hold",
+            Self::Script => "script",
+            Self::Pure => "pure",
+            Self::Archive => "archive",
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::Execute => "execute",
+            Self::Delete => "delete",
+        }
+    }
+}
+
+/// A compact set of [`AccessFlag`]s.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AccessFlags(pub u8);
+
+impl AccessFlags {
+    /// Get the raw protection bits.
+    #[inline]
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Check if `flag` is set.
+    #[inline]
+    pub const fn contains(self, flag: AccessFlag) -> bool {
+        (self.0 as u32 & flag.bit()) != 0
+    }
+
+    /// Get the number of flags set.
+    #[inline]
+    pub const fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Check if no flags are set.
+    #[inline]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Iterate over the set flags in `hsparwed` order.
+    #[inline]
+    pub const fn iter(self) -> AccessFlagsIter {
+        AccessFlagsIter {
+            flags: self,
+            index: 0,
+        }
+    }
+}
+
+impl IntoIterator for AccessFlags {
+    type Item = AccessFlag;
+    type IntoIter = AccessFlagsIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the flags in an [`AccessFlags`] set.
+#[derive(Debug, Clone)]
+pub struct AccessFlagsIter {
+    flags: AccessFlags,
+    index: usize,
+}
+
+impl Iterator for AccessFlagsIter {
+    type Item = AccessFlag;
+
+    fn next(&mut self) -> Option<AccessFlag> {
+        while let Some(&flag) = AccessFlag::ALL.get(self.index) {
+            self.index += 1;
+            if self.flags.contains(flag) {
+                return Some(flag);
+            }
+        }
+        None
+    }
+}
+
+impl core::fmt::Display for Access {
+    /// Format as an AmigaDOS protection string (`hsparwed`).
+    ///
+    /// Each letter is shown when its bit is set and `-` otherwise. Note that
+    /// in AFFS the `rwed` bits are stored inverted: a set bit means the
+    /// operation is *denied*.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for flag in AccessFlag::ALL {
+            f.write_char(if self.0 & flag.bit() != 0 {
+                flag.letter()
+            } else {
+                '-'
+            })?;
+        }
+        Ok(())
+    }
 }
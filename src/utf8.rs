@@ -1,4 +1,4 @@
-//! UTF-8 validation utilities.
+//! UTF-8 validation and Latin-1 conversion utilities.
 
 /// Validate and convert bytes to UTF-8 string.
 ///
@@ -18,6 +18,36 @@ pub fn from_utf8(bytes: &[u8]) -> Option<&str> {
     }
 }
 
+/// Convert Latin-1 bytes to UTF-8.
+///
+/// Output is truncated at a character boundary if `out` is too small.
+/// Returns the number of bytes written to `out`.
+pub(crate) fn latin1_to_utf8(latin1: &[u8], out: &mut [u8]) -> usize {
+    let mut out_pos = 0;
+
+    for &byte in latin1 {
+        if byte < 0x80 {
+            // ASCII - direct copy
+            if out_pos >= out.len() {
+                break;
+            }
+            out[out_pos] = byte;
+            out_pos += 1;
+        } else {
+            // Latin1 high byte (0x80-0xFF) -> UTF-8 two-byte sequence
+            // UTF-8: 110xxxxx 10xxxxxx
+            if out_pos + 1 >= out.len() {
+                break;
+            }
+            out[out_pos] = 0xC0 | (byte >> 6);
+            out[out_pos + 1] = 0x80 | (byte & 0x3F);
+            out_pos += 2;
+        }
+    }
+
+    out_pos
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -33,6 +63,13 @@ mod tests {
         assert_eq!(from_utf8(&[0xFF, 0xFE]), None);
     }
 
+    #[test]
+    fn test_latin1_to_utf8_truncates_at_char_boundary() {
+        let mut out = [0u8; 2];
+        assert_eq!(latin1_to_utf8(&[b'a', 0xE9], &mut out), 1);
+        assert_eq!(out[0], b'a');
+    }
+
     #[test]
     fn test_utf8_multibyte() {
         assert_eq!(from_utf8("café".as_bytes()), Some("café"));
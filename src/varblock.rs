@@ -9,12 +9,101 @@ use crate::checksum::{boot_sum, normal_sum_slice, read_i32_be_slice, read_u32_be
 use crate::constants::*;
 use crate::date::AmigaDate;
 use crate::error::{AffsError, Result};
-use crate::symlink::read_symlink_target_with_block_size;
+use crate::rdb::PartitionInfo;
+use crate::symlink::{read_symlink_target_with_block_size, symlink_target_utf8_len};
 use crate::types::{EntryType, FsFlags, FsType, SectorDevice};
+use core::ops::RangeInclusive;
 
 /// Maximum block size supported (8192 bytes = 16 sectors).
 pub const MAX_BLOCK_SIZE: usize = 8192;
 
+/// Buffer holding one filesystem block.
+///
+/// Without `alloc` this is an inline array, so every [`VarDirIter`] and
+/// [`VarFileReader`] (and each `AffsReaderVar` call that reads a block)
+/// needs `MAX_BLOCK_SIZE` bytes of stack per buffer. With `alloc` the buffer
+/// is boxed, keeping the stack cost to a pointer at the price of a heap
+/// allocation.
+#[cfg(feature = "alloc")]
+type BlockBuf = alloc::boxed::Box<[u8; MAX_BLOCK_SIZE]>;
+#[cfg(not(feature = "alloc"))]
+type BlockBuf = [u8; MAX_BLOCK_SIZE];
+
+/// Create a zeroed block buffer.
+#[inline]
+fn new_block_buf() -> BlockBuf {
+    #[cfg(feature = "alloc")]
+    {
+        // Allocate directly on the heap rather than building the array on the stack
+        alloc::vec![0u8; MAX_BLOCK_SIZE]
+            .into_boxed_slice()
+            .try_into()
+            .expect("slice size mismatch")
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    {
+        [0u8; MAX_BLOCK_SIZE]
+    }
+}
+
+/// Storage for one block in a [`VarDirIter`].
+///
+/// Volumes with 512-byte blocks use a plain array, so their iterator is
+/// monomorphized with the block size known at compile time and skips the
+/// `MAX_BLOCK_SIZE` buffers. Larger volumes use a [`BlockBuf`].
+trait VarBuf {
+    /// Block size fixed by the buffer type, or `None` to use the volume's.
+    const FIXED_SIZE: Option<usize>;
+
+    /// Create a zeroed buffer.
+    fn zeroed() -> Self;
+
+    /// Get the buffer contents.
+    fn bytes(&self) -> &[u8];
+
+    /// Get the buffer contents mutably.
+    fn bytes_mut(&mut self) -> &mut [u8];
+}
+
+impl VarBuf for [u8; BLOCK_SIZE] {
+    const FIXED_SIZE: Option<usize> = Some(BLOCK_SIZE);
+
+    #[inline]
+    fn zeroed() -> Self {
+        [0u8; BLOCK_SIZE]
+    }
+
+    #[inline]
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+
+    #[inline]
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+impl VarBuf for BlockBuf {
+    const FIXED_SIZE: Option<usize> = None;
+
+    #[inline]
+    fn zeroed() -> Self {
+        new_block_buf()
+    }
+
+    #[inline]
+    fn bytes(&self) -> &[u8] {
+        &self[..]
+    }
+
+    #[inline]
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        &mut self[..]
+    }
+}
+
 /// Variable block size AFFS reader.
 ///
 /// This reader supports AFFS filesystems with block sizes from 512 to 8192 bytes,
@@ -22,6 +111,8 @@ pub const MAX_BLOCK_SIZE: usize = 8192;
 /// the root block at different sizes until the checksum validates.
 pub struct AffsReaderVar<'a, D: SectorDevice> {
     device: &'a D,
+    /// First sector of the filesystem on the device.
+    sector_offset: u64,
     /// Filesystem type (OFS or FFS).
     fs_type: FsType,
     /// Filesystem flags.
@@ -70,17 +161,52 @@ impl<'a, D: SectorDevice> AffsReaderVar<'a, D> {
     /// * `device` - Sector device to read from
     /// * `total_sectors` - Total number of 512-byte sectors on the device
     pub fn new(device: &'a D, total_sectors: u64) -> Result<Self> {
-        let result = Self::probe(device, total_sectors)?;
+        Self::open(device, 0, total_sectors, 0..=MAX_LOG_BLOCK_SIZE)
+    }
+
+    /// Open the filesystem of an RDB partition.
+    ///
+    /// All reads are made relative to the partition's starting sector, and
+    /// only the partition's block size is probed. Returns
+    /// `AffsError::InvalidState` if that block size is not supported.
+    ///
+    /// # Arguments
+    /// * `device` - Sector device holding the whole disk
+    /// * `partition` - Partition from [`parse_rdb`](crate::parse_rdb)
+    pub fn mount_partition(device: &'a D, partition: &PartitionInfo) -> Result<Self> {
+        let log_blocksize = (0..=MAX_LOG_BLOCK_SIZE)
+            .find(|&log| (BLOCK_SIZE as u64) << log == partition.block_size)
+            .ok_or(AffsError::InvalidState)?;
+        Self::open(
+            device,
+            partition.start_sector,
+            partition.num_sectors,
+            log_blocksize..=log_blocksize,
+        )
+    }
+
+    /// Probe and open a filesystem starting at `sector_offset`.
+    fn open(
+        device: &'a D,
+        sector_offset: u64,
+        total_sectors: u64,
+        log_blocksizes: RangeInclusive<u8>,
+    ) -> Result<Self> {
+        let result = Self::probe(device, sector_offset, log_blocksizes)?;
 
         Ok(Self {
             device,
+            sector_offset,
             fs_type: result.fs_type,
             fs_flags: result.fs_flags,
             root_block: result.root_block,
             total_blocks: (total_sectors >> result.log_blocksize) as u32,
             log_blocksize: result.log_blocksize,
             block_size: result.block_size,
-            hash_table_size: result.hash_table_size,
+            // The table must fit in the block
+            hash_table_size: result
+                .hash_table_size
+                .min((result.block_size / 4 - 56) as u32),
             disk_name: result.disk_name,
             disk_name_len: result.disk_name_len,
             creation_date: result.creation_date,
@@ -89,15 +215,21 @@ impl<'a, D: SectorDevice> AffsReaderVar<'a, D> {
     }
 
     /// Probe the filesystem to determine block size.
-    fn probe(device: &'a D, _total_sectors: u64) -> Result<ProbeResult> {
+    fn probe(
+        device: &'a D,
+        sector_offset: u64,
+        log_blocksizes: RangeInclusive<u8>,
+    ) -> Result<ProbeResult> {
         // Buffer for reading - we need max block size
-        let mut buf = [0u8; MAX_BLOCK_SIZE];
+        let mut buf = new_block_buf();
 
         // Try boot block at sector 0 and sector 1
         for boot_sector in 0..=MAX_BOOT_BLOCK {
-            // Read boot block (2 sectors)
-            if Self::read_sectors(device, boot_sector as u64, &mut buf[..BOOT_BLOCK_SIZE]).is_err()
-            {
+            // Read boot block (2 sectors); an offset near u64::MAX has no candidates
+            let Some(sector) = sector_offset.checked_add(boot_sector as u64) else {
+                continue;
+            };
+            if read_sectors_into(device, sector, &mut buf[..BOOT_BLOCK_SIZE]).is_err() {
                 continue;
             }
 
@@ -117,7 +249,7 @@ impl<'a, D: SectorDevice> AffsReaderVar<'a, D> {
 
             // Verify boot checksum if boot code is present
             if buf[12] != 0 {
-                let checksum = read_u32_be_slice(&buf, 4);
+                let checksum = read_u32_be_slice(&buf[..], 4);
                 let boot_buf: &[u8; BOOT_BLOCK_SIZE] = buf[..BOOT_BLOCK_SIZE].try_into().unwrap();
                 let calculated = boot_sum(boot_buf);
                 if checksum != calculated {
@@ -125,38 +257,44 @@ impl<'a, D: SectorDevice> AffsReaderVar<'a, D> {
                 }
             }
 
-            let root_block_num = read_u32_be_slice(&buf, 8);
+            let root_block_num = read_u32_be_slice(&buf[..], 8);
 
             // Try each block size
-            for log_blocksize in 0..=MAX_LOG_BLOCK_SIZE {
-                let block_size = 512usize << log_blocksize;
+            for log_blocksize in log_blocksizes.clone() {
+                let Some(block_size) = block_size_for(log_blocksize) else {
+                    continue;
+                };
 
                 // Read root block
-                let root_sector = (root_block_num as u64) << log_blocksize;
-                if Self::read_sectors(device, root_sector, &mut buf[..block_size]).is_err() {
+                let Some(root_sector) =
+                    sector_offset.checked_add((root_block_num as u64) << log_blocksize)
+                else {
+                    continue;
+                };
+                if read_sectors_into(device, root_sector, &mut buf[..block_size]).is_err() {
                     continue;
                 }
 
                 // Validate root block type
-                let block_type = read_i32_be_slice(&buf, 0);
+                let block_type = read_i32_be_slice(&buf[..], 0);
                 if block_type != T_HEADER {
                     continue;
                 }
 
                 // Validate secondary type (at end of block)
-                let sec_type = read_i32_be_slice(&buf, block_size - 4);
+                let sec_type = read_i32_be_slice(&buf[..], block_size - 4);
                 if sec_type != ST_ROOT {
                     continue;
                 }
 
                 // Validate hash table size
-                let hash_table_size = read_u32_be_slice(&buf, 12);
+                let hash_table_size = read_u32_be_slice(&buf[..], 12);
                 if hash_table_size == 0 {
                     continue;
                 }
 
                 // Validate checksum
-                let checksum = read_u32_be_slice(&buf, 20);
+                let checksum = read_u32_be_slice(&buf[..], 20);
                 let calculated = normal_sum_slice(&buf[..block_size], 20);
                 if checksum != calculated {
                     continue;
@@ -174,18 +312,22 @@ impl<'a, D: SectorDevice> AffsReaderVar<'a, D> {
                 // Actually for root block, dates are at fixed offsets from end
                 let date_offset = block_size - FILE_LOCATION + 0x1A4 - (BLOCK_SIZE - FILE_LOCATION);
                 let creation_date = AmigaDate::new(
-                    read_i32_be_slice(&buf, date_offset),
-                    read_i32_be_slice(&buf, date_offset + 4),
-                    read_i32_be_slice(&buf, date_offset + 8),
+                    read_i32_be_slice(&buf[..], date_offset),
+                    read_i32_be_slice(&buf[..], date_offset + 4),
+                    read_i32_be_slice(&buf[..], date_offset + 8),
                 );
 
                 let mod_offset = block_size - FILE_LOCATION + 0x1D8 - (BLOCK_SIZE - FILE_LOCATION);
                 let last_modified = AmigaDate::new(
-                    read_i32_be_slice(&buf, mod_offset),
-                    read_i32_be_slice(&buf, mod_offset + 4),
-                    read_i32_be_slice(&buf, mod_offset + 8),
+                    read_i32_be_slice(&buf[..], mod_offset),
+                    read_i32_be_slice(&buf[..], mod_offset + 4),
+                    read_i32_be_slice(&buf[..], mod_offset + 8),
                 );
 
+                trace!(
+                    "detected block size {=usize}, root block {=u32}",
+                    block_size, root_block_num
+                );
                 return Ok(ProbeResult {
                     fs_type,
                     fs_flags,
@@ -204,25 +346,15 @@ impl<'a, D: SectorDevice> AffsReaderVar<'a, D> {
         Err(AffsError::InvalidDosType)
     }
 
-    /// Read multiple sectors into a buffer.
-    fn read_sectors(device: &D, start_sector: u64, buf: &mut [u8]) -> Result<()> {
-        let num_sectors = buf.len() / BLOCK_SIZE;
-        let mut sector_buf = [0u8; BLOCK_SIZE];
-
-        for i in 0..num_sectors {
-            device
-                .read_sector(start_sector + i as u64, &mut sector_buf)
-                .map_err(|()| AffsError::BlockReadError)?;
-            buf[i * BLOCK_SIZE..(i + 1) * BLOCK_SIZE].copy_from_slice(&sector_buf);
-        }
-
-        Ok(())
-    }
-
     /// Read a filesystem block into a buffer.
     fn read_block_into(&self, block: u32, buf: &mut [u8]) -> Result<()> {
-        let start_sector = (block as u64) << self.log_blocksize;
-        Self::read_sectors(self.device, start_sector, &mut buf[..self.block_size])
+        read_var_block(
+            self.device,
+            self.sector_offset,
+            block,
+            self.log_blocksize,
+            &mut buf[..self.block_size],
+        )
     }
 
     /// Get the filesystem type (OFS or FFS).
@@ -249,6 +381,14 @@ impl<'a, D: SectorDevice> AffsReaderVar<'a, D> {
         self.total_blocks
     }
 
+    /// Get the first sector of the filesystem on the device.
+    ///
+    /// This is 0 unless opened with [`mount_partition`](Self::mount_partition).
+    #[inline]
+    pub const fn sector_offset(&self) -> u64 {
+        self.sector_offset
+    }
+
     /// Get the block size in bytes.
     #[inline]
     pub const fn block_size(&self) -> usize {
@@ -261,6 +401,24 @@ impl<'a, D: SectorDevice> AffsReaderVar<'a, D> {
         self.log_blocksize
     }
 
+    /// Get the number of 512-byte sectors per block.
+    #[inline]
+    pub const fn sectors_per_block(&self) -> u32 {
+        1 << self.log_blocksize
+    }
+
+    /// Get the first device sector of a block.
+    ///
+    /// Includes the [`sector_offset`](Self::sector_offset), so the result
+    /// can be passed straight to [`SectorDevice::read_sector`]. The block
+    /// spans [`sectors_per_block`](Self::sectors_per_block) sectors from
+    /// there.
+    #[inline]
+    pub const fn block_to_sector(&self, block: u32) -> u64 {
+        self.sector_offset
+            .saturating_add((block as u64) << self.log_blocksize)
+    }
+
     /// Get the disk name (volume label) as bytes.
     #[inline]
     pub fn disk_name(&self) -> &[u8] {
@@ -313,9 +471,29 @@ impl<'a, D: SectorDevice> AffsReaderVar<'a, D> {
     }
 
     /// Check if international mode is enabled.
+    ///
+    /// This reports the INTL flag only, as [`AffsReader::is_intl`] does.
+    /// Use [`uses_intl_hashing`](Self::uses_intl_hashing) to pick the name
+    /// hashing rule.
+    ///
+    /// [`AffsReader::is_intl`]: crate::AffsReader::is_intl
     #[inline]
     pub const fn is_intl(&self) -> bool {
-        self.fs_flags.intl || self.fs_flags.dircache
+        self.fs_flags.intl
+    }
+
+    /// Check if names are hashed with international case folding.
+    ///
+    /// See [`FsFlags::uses_intl_hashing`].
+    #[inline]
+    pub const fn uses_intl_hashing(&self) -> bool {
+        self.fs_flags.uses_intl_hashing()
+    }
+
+    /// Check if this is a long filename volume (`DOS\6` or `DOS\7`).
+    #[inline]
+    pub const fn supports_long_names(&self) -> bool {
+        self.fs_flags.long_names
     }
 
     /// Read a symlink target.
@@ -325,17 +503,22 @@ impl<'a, D: SectorDevice> AffsReaderVar<'a, D> {
     /// * `out` - Buffer to write the UTF-8 symlink target into
     ///
     /// # Returns
-    /// The number of bytes written to `out`.
+    /// The number of bytes written to `out`, or
+    /// `AffsError::SymlinkTooLong` if the converted target does not fit.
     pub fn read_symlink(&self, block: u32, out: &mut [u8]) -> Result<usize> {
-        let mut buf = [0u8; MAX_BLOCK_SIZE];
-        self.read_block_into(block, &mut buf)?;
+        let mut buf = new_block_buf();
+        self.read_block_into(block, &mut buf[..])?;
 
         // Verify this is a symlink
-        let sec_type = read_i32_be_slice(&buf, self.block_size - 4);
+        let sec_type = read_i32_be_slice(&buf[..], self.block_size - 4);
         if sec_type != ST_LSOFT {
             return Err(AffsError::NotASymlink);
         }
 
+        if symlink_target_utf8_len(&buf[..self.block_size], self.block_size) > out.len() {
+            return Err(AffsError::SymlinkTooLong);
+        }
+
         Ok(read_symlink_target_with_block_size(
             &buf[..self.block_size],
             self.block_size,
@@ -343,62 +526,99 @@ impl<'a, D: SectorDevice> AffsReaderVar<'a, D> {
         ))
     }
 
-    /// Iterate over entries in the root directory.
-    pub fn read_root_dir(&self) -> Result<VarDirIter<'_, D>> {
-        let mut buf = [0u8; MAX_BLOCK_SIZE];
-        self.read_block_into(self.root_block, &mut buf)?;
-
-        // Read hash table
-        let mut hash_table = [0u32; 256]; // Max possible hash table size
-        let ht_size = self.hash_table_size as usize;
-        for (i, slot) in hash_table.iter_mut().enumerate().take(ht_size.min(256)) {
-            *slot = read_u32_be_slice(&buf, SYMLINK_OFFSET + i * 4);
-        }
-
-        Ok(VarDirIter::new(
+    /// Open a file for reading.
+    ///
+    /// # Arguments
+    /// * `block` - Block number of the file header
+    pub fn read_file(&self, block: u32) -> Result<VarFileReader<'a, D>> {
+        VarFileReader::new(
             self.device,
-            hash_table,
-            ht_size,
+            self.sector_offset,
+            block,
             self.log_blocksize,
             self.block_size,
-        ))
+            self.block_size / 4 - 56,
+        )
+    }
+
+    /// Iterate over entries in the root directory.
+    pub fn read_root_dir(&self) -> Result<VarDirIter<'_, D>> {
+        self.dir_iter(self.root_block)
     }
 
     /// Iterate over entries in a directory.
+    ///
+    /// The block must be a directory header. Its hash table is read with
+    /// the volume's table size; a directory that records a different
+    /// nonzero size is rejected with `AffsError::InvalidState`.
     pub fn read_dir(&self, block: u32) -> Result<VarDirIter<'_, D>> {
-        if block == self.root_block {
-            return self.read_root_dir();
+        if block != self.root_block && block >= self.total_blocks {
+            return Err(AffsError::BlockOutOfRange);
         }
+        self.dir_iter(block)
+    }
+
+    /// Build a directory iterator, picking the 512-byte fast path when the
+    /// volume's block size allows it.
+    fn dir_iter(&self, block: u32) -> Result<VarDirIter<'_, D>> {
+        let inner = if self.block_size == BLOCK_SIZE {
+            VarDirIterInner::Small(self.chain_iter(self.load_dir(block)?))
+        } else {
+            VarDirIterInner::Large(self.chain_iter(self.load_dir(block)?))
+        };
+        Ok(VarDirIter { inner })
+    }
 
-        let mut buf = [0u8; MAX_BLOCK_SIZE];
-        self.read_block_into(block, &mut buf)?;
+    /// Read a directory block, validating it unless it is the root.
+    fn load_dir<B: VarBuf>(&self, block: u32) -> Result<B> {
+        let mut buf = B::zeroed();
+        self.read_block_into(block, buf.bytes_mut())?;
+        if block == self.root_block {
+            return Ok(buf);
+        }
+        let bytes = buf.bytes();
 
         // Validate block type
-        let block_type = read_i32_be_slice(&buf, 0);
+        let block_type = read_i32_be_slice(bytes, 0);
         if block_type != T_HEADER {
             return Err(AffsError::InvalidBlockType);
         }
 
         // Validate this is a directory
-        let sec_type = read_i32_be_slice(&buf, self.block_size - 4);
+        let sec_type = read_i32_be_slice(bytes, self.block_size - 4);
         if sec_type != ST_DIR && sec_type != ST_LDIR {
             return Err(AffsError::NotADirectory);
         }
 
-        // Read hash table
-        let mut hash_table = [0u32; 256];
-        let ht_size = self.hash_table_size as usize;
-        for (i, slot) in hash_table.iter_mut().enumerate().take(ht_size.min(256)) {
-            *slot = read_u32_be_slice(&buf, SYMLINK_OFFSET + i * 4);
+        // Directories normally leave the table size at 0; any other value must agree
+        let table_size = read_u32_be_slice(bytes, 12);
+        if table_size != 0 && table_size != self.hash_table_size {
+            warn!(
+                "directory {=u32} hash table size {=u32} != {=u32}",
+                block, table_size, self.hash_table_size
+            );
+            return Err(AffsError::InvalidState);
         }
 
-        Ok(VarDirIter::new(
-            self.device,
-            hash_table,
-            ht_size,
-            self.log_blocksize,
-            self.block_size,
-        ))
+        Ok(buf)
+    }
+
+    /// Build a chain iterator over a loaded directory block.
+    fn chain_iter<B: VarBuf>(&self, table: B) -> VarChainIter<'_, D, B> {
+        VarChainIter {
+            device: self.device,
+            sector_offset: self.sector_offset,
+            table,
+            hash_table_size: self.hash_table_size as usize,
+            hash_index: 0,
+            current_chain: 0,
+            total_blocks: self.total_blocks,
+            hops: 0,
+            log_blocksize: self.log_blocksize,
+            block_size: self.block_size,
+            long_names: self.fs_flags.long_names,
+            buf: B::zeroed(),
+        }
     }
 }
 
@@ -454,61 +674,102 @@ impl VarDirEntry {
 }
 
 /// Directory iterator for variable block size filesystem.
+///
+/// Volumes with 512-byte blocks are walked with 512-byte buffers. Without
+/// `alloc` the iterator still reserves room for [`MAX_BLOCK_SIZE`] blocks,
+/// since either size may be needed.
 pub struct VarDirIter<'a, D: SectorDevice> {
+    inner: VarDirIterInner<'a, D>,
+}
+
+/// Chain iterator specialized for the volume's block size.
+// Without `alloc` the large buffers are inline and can't be boxed.
+#[allow(clippy::large_enum_variant)]
+enum VarDirIterInner<'a, D: SectorDevice> {
+    /// 512-byte blocks, with the block size fixed at compile time.
+    Small(VarChainIter<'a, D, [u8; BLOCK_SIZE]>),
+    /// Larger blocks.
+    Large(VarChainIter<'a, D, BlockBuf>),
+}
+
+impl<D: SectorDevice> Iterator for VarDirIter<'_, D> {
+    type Item = Result<VarDirEntry>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            VarDirIterInner::Small(iter) => iter.next(),
+            VarDirIterInner::Large(iter) => iter.next(),
+        }
+    }
+}
+
+/// Walks a directory's hash table and chains using `B` block buffers.
+struct VarChainIter<'a, D: SectorDevice, B> {
     device: &'a D,
-    hash_table: [u32; 256],
+    sector_offset: u64,
+    /// The directory block; its hash table starts at offset 24.
+    table: B,
     hash_table_size: usize,
     hash_index: usize,
     current_chain: u32,
+    /// Total blocks on device (bounds chain pointers and length).
+    total_blocks: u32,
+    /// Number of chain blocks visited so far.
+    hops: u32,
     log_blocksize: u8,
     block_size: usize,
-    buf: [u8; MAX_BLOCK_SIZE],
+    /// Names are in the long filename layout.
+    long_names: bool,
+    buf: B,
 }
 
-impl<'a, D: SectorDevice> VarDirIter<'a, D> {
-    fn new(
-        device: &'a D,
-        hash_table: [u32; 256],
-        hash_table_size: usize,
-        log_blocksize: u8,
-        block_size: usize,
-    ) -> Self {
-        Self {
-            device,
-            hash_table,
-            hash_table_size,
-            hash_index: 0,
-            current_chain: 0,
-            log_blocksize,
-            block_size,
-            buf: [0u8; MAX_BLOCK_SIZE],
+impl<D: SectorDevice, B: VarBuf> VarChainIter<'_, D, B> {
+    /// Get the block size, as a constant when the buffer type fixes it.
+    #[inline(always)]
+    fn block_size(&self) -> usize {
+        match B::FIXED_SIZE {
+            Some(size) => size,
+            None => self.block_size,
         }
     }
 
     fn read_block_into(&mut self, block: u32) -> Result<()> {
-        let start_sector = (block as u64) << self.log_blocksize;
-        let num_sectors = 1usize << self.log_blocksize;
-        let mut sector_buf = [0u8; BLOCK_SIZE];
-
-        for i in 0..num_sectors {
-            self.device
-                .read_sector(start_sector + i as u64, &mut sector_buf)
-                .map_err(|()| AffsError::BlockReadError)?;
-            self.buf[i * BLOCK_SIZE..(i + 1) * BLOCK_SIZE].copy_from_slice(&sector_buf);
-        }
-
-        Ok(())
+        let block_size = self.block_size();
+        read_var_block(
+            self.device,
+            self.sector_offset,
+            block,
+            self.log_blocksize,
+            &mut self.buf.bytes_mut()[..block_size],
+        )
     }
 
-    fn parse_entry(&self, block: u32) -> Option<VarDirEntry> {
-        let buf = &self.buf[..self.block_size];
+    /// Parse the entry in `buf`.
+    ///
+    /// Returns `Ok(None)` for an entry with an unknown secondary type, and
+    /// `AffsError::NameTooLong` for a long filename that does not fit in a
+    /// [`VarDirEntry`].
+    fn parse_entry(&self, block: u32) -> Result<Option<VarDirEntry>> {
+        let block_size = self.block_size();
+        let buf = &self.buf.bytes()[..block_size];
 
         // Entry type is at end of block - 4
-        let sec_type = read_i32_be_slice(buf, self.block_size - 4);
-        let entry_type = EntryType::from_sec_type(sec_type)?;
-
-        // Name is at block_size - FILE_LOCATION + offset
-        let name_offset = self.block_size - FILE_LOCATION + 108;
+        let sec_type = read_i32_be_slice(buf, block_size - 4);
+        let Some(entry_type) = EntryType::from_sec_type(sec_type) else {
+            return Ok(None);
+        };
+
+        // Name is at block_size - FILE_LOCATION + offset, or where the
+        // comment usually starts on long filename volumes
+        let name_offset = if self.long_names {
+            block_size - FILE_LOCATION + 8
+        } else {
+            block_size - FILE_LOCATION + 108
+        };
+        if self.long_names && buf[name_offset] as usize > MAX_NAME_LEN {
+            return Err(AffsError::NameTooLong);
+        }
         let name_len = buf[name_offset].min(MAX_NAME_LEN as u8);
         let mut name = [0u8; MAX_NAME_LEN];
         name[..name_len as usize]
@@ -516,21 +777,26 @@ impl<'a, D: SectorDevice> VarDirIter<'a, D> {
 
         // Size at offset 0x144 relative to start in standard block
         // For variable blocks: block_size - FILE_LOCATION + 12
-        let size_offset = self.block_size - FILE_LOCATION + 12;
+        let size_offset = block_size - FILE_LOCATION + 12;
         let size = read_u32_be_slice(buf, size_offset);
 
         // Parent at block_size - 12
-        let parent = read_u32_be_slice(buf, self.block_size - 12);
-
-        // Date at block_size - FILE_LOCATION + 0x1A4 - (512 - FILE_LOCATION)
-        let date_offset = self.block_size - FILE_LOCATION + 0x1A4 - (BLOCK_SIZE - FILE_LOCATION);
+        let parent = read_u32_be_slice(buf, block_size - 12);
+
+        // Date at block_size - FILE_LOCATION + 0x1A4 - (512 - FILE_LOCATION),
+        // or 0x1C4 on long filename volumes
+        let date_offset = if self.long_names {
+            block_size - FILE_LOCATION + 0x1C4 - (BLOCK_SIZE - FILE_LOCATION)
+        } else {
+            block_size - FILE_LOCATION + 0x1A4 - (BLOCK_SIZE - FILE_LOCATION)
+        };
         let date = AmigaDate::new(
             read_i32_be_slice(buf, date_offset),
             read_i32_be_slice(buf, date_offset + 4),
             read_i32_be_slice(buf, date_offset + 8),
         );
 
-        Some(VarDirEntry {
+        Ok(Some(VarDirEntry {
             name,
             name_len,
             entry_type,
@@ -538,35 +804,43 @@ impl<'a, D: SectorDevice> VarDirIter<'a, D> {
             parent,
             size,
             date,
-        })
+        }))
     }
 }
 
-impl<D: SectorDevice> Iterator for VarDirIter<'_, D> {
+impl<D: SectorDevice, B: VarBuf> Iterator for VarChainIter<'_, D, B> {
     type Item = Result<VarDirEntry>;
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
             // If we're in a hash chain, continue it
             if self.current_chain != 0 {
-                if let Err(e) = self.read_block_into(self.current_chain) {
-                    return Some(Err(e));
+                let block = self.current_chain;
+                self.hops += 1;
+                if block >= self.total_blocks || self.hops > self.total_blocks {
+                    self.current_chain = 0;
+                    return Some(Err(AffsError::BlockOutOfRange));
                 }
 
-                let block = self.current_chain;
+                if let Err(e) = self.read_block_into(block) {
+                    self.current_chain = 0;
+                    return Some(Err(e));
+                }
 
                 // Next in chain at block_size - 16
-                self.current_chain = read_u32_be_slice(&self.buf, self.block_size - 16);
+                self.current_chain = read_u32_be_slice(self.buf.bytes(), self.block_size() - 16);
 
-                if let Some(entry) = self.parse_entry(block) {
-                    return Some(Ok(entry));
+                match self.parse_entry(block) {
+                    Ok(Some(entry)) => return Some(Ok(entry)),
+                    Ok(None) => continue,
+                    Err(e) => return Some(Err(e)),
                 }
-                continue;
             }
 
             // Find next non-empty hash slot
             while self.hash_index < self.hash_table_size {
-                let block = self.hash_table[self.hash_index];
+                let block =
+                    read_u32_be_slice(self.table.bytes(), SYMLINK_OFFSET + self.hash_index * 4);
                 self.hash_index += 1;
 
                 if block != 0 {
@@ -582,6 +856,307 @@ impl<D: SectorDevice> Iterator for VarDirIter<'_, D> {
     }
 }
 
+/// Streaming file reader for variable block size filesystem.
+///
+/// Reads FFS file data using the volume's block size. Data block pointers
+/// are taken from the file header and any `T_LIST` extension blocks.
+pub struct VarFileReader<'a, D: SectorDevice> {
+    device: &'a D,
+    /// First sector of the filesystem on the device.
+    sector_offset: u64,
+    /// Block number of file header (for reset).
+    header_block: u32,
+    /// Log2 of block size relative to 512.
+    log_blocksize: u8,
+    /// Block size in bytes.
+    block_size: usize,
+    /// Number of data block pointers per header/extension block.
+    table_size: usize,
+    /// Total file size in bytes.
+    file_size: u32,
+    /// Bytes remaining to read.
+    remaining: u32,
+    /// Number of data blocks in current header/extension block.
+    blocks_in_current: u32,
+    /// Index within current header/extension block.
+    index_in_current: u32,
+    /// Next extension block.
+    next_extension: u32,
+    /// Offset within current data block.
+    offset_in_block: usize,
+    /// Current header/extension block (holds the pointer table).
+    table_buf: BlockBuf,
+    /// Data block buffer.
+    buf: BlockBuf,
+}
+
+impl<'a, D: SectorDevice> VarFileReader<'a, D> {
+    fn new(
+        device: &'a D,
+        sector_offset: u64,
+        header_block: u32,
+        log_blocksize: u8,
+        block_size: usize,
+        table_size: usize,
+    ) -> Result<Self> {
+        let mut reader = Self {
+            device,
+            sector_offset,
+            header_block,
+            log_blocksize,
+            block_size,
+            table_size,
+            file_size: 0,
+            remaining: 0,
+            blocks_in_current: 0,
+            index_in_current: 0,
+            next_extension: 0,
+            offset_in_block: 0,
+            table_buf: new_block_buf(),
+            buf: new_block_buf(),
+        };
+        reader.load_header()?;
+        Ok(reader)
+    }
+
+    /// Read and validate the file header into the table buffer.
+    fn load_header(&mut self) -> Result<()> {
+        read_var_block(
+            self.device,
+            self.sector_offset,
+            self.header_block,
+            self.log_blocksize,
+            &mut self.table_buf[..self.block_size],
+        )?;
+
+        let buf = &self.table_buf[..self.block_size];
+        if read_i32_be_slice(buf, 0) != T_HEADER {
+            return Err(AffsError::InvalidBlockType);
+        }
+        if read_u32_be_slice(buf, 20) != normal_sum_slice(buf, 20) {
+            return Err(AffsError::ChecksumMismatch);
+        }
+        if read_i32_be_slice(buf, self.block_size - 4) != ST_FILE {
+            return Err(AffsError::NotAFile);
+        }
+
+        self.file_size = read_u32_be_slice(buf, self.block_size - FILE_LOCATION + 12);
+        self.remaining = self.file_size;
+        self.blocks_in_current = read_u32_be_slice(buf, 8);
+        self.index_in_current = 0;
+        self.next_extension = read_u32_be_slice(buf, self.block_size - 8);
+        self.offset_in_block = 0;
+        Ok(())
+    }
+
+    /// Get the total file size in bytes.
+    #[inline]
+    pub const fn size(&self) -> u32 {
+        self.file_size
+    }
+
+    /// Get the block number of the file header.
+    #[inline]
+    pub const fn header_block(&self) -> u32 {
+        self.header_block
+    }
+
+    /// Get the number of bytes remaining to read.
+    #[inline]
+    pub const fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    /// Check if we've reached end of file.
+    #[inline]
+    pub const fn is_eof(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// Get current position in the file.
+    #[inline]
+    pub const fn position(&self) -> u32 {
+        self.file_size - self.remaining
+    }
+
+    /// Reset the reader to the beginning of the file.
+    ///
+    /// This re-reads the file header block.
+    pub fn reset(&mut self) -> Result<()> {
+        self.load_header()
+    }
+
+    /// Read data into a buffer.
+    ///
+    /// Returns the number of bytes read. Returns 0 at end of file.
+    pub fn read(&mut self, out: &mut [u8]) -> Result<usize> {
+        if self.remaining == 0 || out.is_empty() {
+            return Ok(0);
+        }
+
+        let mut total_read = 0;
+
+        while total_read < out.len() && self.remaining > 0 {
+            if self.offset_in_block == 0 || self.offset_in_block >= self.block_size {
+                self.read_next_data_block()?;
+            }
+
+            // Last block may be partial
+            let data_size = (self.remaining as usize + self.offset_in_block).min(self.block_size);
+            let to_read = (data_size - self.offset_in_block).min(out.len() - total_read);
+
+            if to_read == 0 {
+                break;
+            }
+
+            out[total_read..total_read + to_read]
+                .copy_from_slice(&self.buf[self.offset_in_block..self.offset_in_block + to_read]);
+
+            total_read += to_read;
+            self.offset_in_block += to_read;
+            self.remaining -= to_read as u32;
+        }
+
+        Ok(total_read)
+    }
+
+    /// Read the entire file into a buffer.
+    ///
+    /// The buffer must be at least as large as the remaining file size.
+    /// Returns the number of bytes read.
+    pub fn read_all(&mut self, out: &mut [u8]) -> Result<usize> {
+        if out.len() < self.remaining as usize {
+            return Err(AffsError::BufferTooSmall);
+        }
+
+        let mut total = 0;
+        while self.remaining > 0 {
+            let n = self.read(&mut out[total..])?;
+            if n == 0 {
+                break;
+            }
+            total += n;
+        }
+        Ok(total)
+    }
+
+    /// Read the next data block.
+    fn read_next_data_block(&mut self) -> Result<()> {
+        let block = self.next_data_block()?;
+        if block == 0 {
+            return Err(AffsError::TruncatedFile);
+        }
+
+        read_var_block(
+            self.device,
+            self.sector_offset,
+            block,
+            self.log_blocksize,
+            &mut self.buf[..self.block_size],
+        )?;
+        self.offset_in_block = 0;
+        Ok(())
+    }
+
+    /// Get the next data block number (uses block pointer table).
+    fn next_data_block(&mut self) -> Result<u32> {
+        if self.index_in_current >= self.blocks_in_current {
+            if self.next_extension == 0 {
+                return Ok(0);
+            }
+
+            read_var_block(
+                self.device,
+                self.sector_offset,
+                self.next_extension,
+                self.log_blocksize,
+                &mut self.table_buf[..self.block_size],
+            )?;
+
+            let buf = &self.table_buf[..self.block_size];
+            if read_i32_be_slice(buf, 0) != T_LIST {
+                return Err(AffsError::InvalidBlockType);
+            }
+            if read_u32_be_slice(buf, 20) != normal_sum_slice(buf, 20) {
+                return Err(AffsError::ChecksumMismatch);
+            }
+            if read_i32_be_slice(buf, self.block_size - 4) != ST_FILE {
+                return Err(AffsError::InvalidSecType);
+            }
+
+            self.blocks_in_current = read_u32_be_slice(buf, 8);
+            self.next_extension = read_u32_be_slice(buf, self.block_size - 8);
+            self.index_in_current = 0;
+        }
+
+        let idx = self.index_in_current as usize;
+        if idx >= self.table_size {
+            return Ok(0);
+        }
+
+        // Block pointers are stored in reverse order
+        let block = read_u32_be_slice(
+            &self.table_buf[..],
+            SYMLINK_OFFSET + (self.table_size - 1 - idx) * 4,
+        );
+        self.index_in_current += 1;
+        Ok(block)
+    }
+}
+
+/// Read a filesystem block of `buf.len()` bytes starting at sector
+/// `sector_offset + (block << log_blocksize)`.
+fn read_var_block<D: SectorDevice>(
+    device: &D,
+    sector_offset: u64,
+    block: u32,
+    log_blocksize: u8,
+    buf: &mut [u8],
+) -> Result<()> {
+    let sector = sector_offset
+        .checked_add((block as u64) << log_blocksize)
+        .ok_or(AffsError::BlockOutOfRange)?;
+    read_sectors_into(device, sector, buf)
+}
+
+/// Read `buf.len() / 512` consecutive sectors directly into `buf`.
+#[inline]
+fn read_sectors_into<D: SectorDevice>(device: &D, start_sector: u64, buf: &mut [u8]) -> Result<()> {
+    // Fast path for 512-byte blocks: one sector, no loop
+    if let Ok(sector) = <&mut [u8; BLOCK_SIZE]>::try_from(&mut *buf) {
+        return device
+            .read_sector(start_sector, sector)
+            .map_err(|()| AffsError::BlockReadError);
+    }
+
+    for (i, chunk) in buf.chunks_exact_mut(BLOCK_SIZE).enumerate() {
+        let sector: &mut [u8; BLOCK_SIZE] = chunk.try_into().expect("chunk size mismatch");
+        let index = start_sector
+            .checked_add(i as u64)
+            .ok_or(AffsError::BlockOutOfRange)?;
+        device
+            .read_sector(index, sector)
+            .map_err(|()| AffsError::BlockReadError)?;
+    }
+
+    Ok(())
+}
+
+/// Get the block size for a log2 block size, if it is supported.
+///
+/// Sizes above [`MAX_BLOCK_SIZE`] don't fit the read buffer.
+#[inline]
+const fn block_size_for(log_blocksize: u8) -> Option<usize> {
+    if log_blocksize > MAX_LOG_BLOCK_SIZE {
+        return None;
+    }
+    let block_size = BLOCK_SIZE << log_blocksize;
+    if block_size > MAX_BLOCK_SIZE {
+        return None;
+    }
+    Some(block_size)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -708,9 +1283,47 @@ mod tests {
 
         // Read root dir and iterate
         let mut iter = reader.read_root_dir().expect("read_root_dir");
+        assert!(matches!(iter.inner, VarDirIterInner::Small(_)));
         let first = iter.next().expect("entry").expect("ok entry");
         assert_eq!(first.name_str(), Some("file"));
         assert_eq!(first.size, 123);
         assert_eq!(first.block, 5);
     }
+
+    #[test]
+    fn test_var_probe_rejects_invalid_block_sizes() {
+        assert_eq!(block_size_for(0), Some(512));
+        assert_eq!(block_size_for(MAX_LOG_BLOCK_SIZE), Some(MAX_BLOCK_SIZE));
+        assert_eq!(block_size_for(MAX_LOG_BLOCK_SIZE + 1), None);
+        assert_eq!(block_size_for(u8::MAX), None);
+
+        // Out-of-range sizes are skipped rather than overflowing the buffer
+        let device = DummyGoodDevice;
+        let result = AffsReaderVar::open(&device, 0, 100, MAX_LOG_BLOCK_SIZE + 1..=u8::MAX);
+        assert!(result.is_err());
+        let reader = AffsReaderVar::open(&device, 0, 100, 0..=u8::MAX).unwrap();
+        assert_eq!(reader.block_size(), 512);
+    }
+
+    /// [`DummyGoodDevice`] with its sectors moved up by an offset.
+    struct ShiftedDevice(u64);
+
+    impl SectorDevice for ShiftedDevice {
+        fn read_sector(&self, sector: u64, buf: &mut [u8; 512]) -> core::result::Result<(), ()> {
+            DummyGoodDevice.read_sector(sector.wrapping_sub(self.0), buf)
+        }
+    }
+
+    #[test]
+    fn test_var_probe_offset_overflow() {
+        // The boot block fits below u64::MAX, but the root block would not
+        let offset = u64::MAX - 1;
+        let device = ShiftedDevice(offset);
+        let result = AffsReaderVar::open(&device, offset, 100, 0..=0);
+        assert!(result.is_err());
+
+        let device = ShiftedDevice(1000);
+        let reader = AffsReaderVar::open(&device, 1000, 100, 0..=0).unwrap();
+        assert_eq!(reader.disk_name_str(), Some("test"));
+    }
 }
@@ -0,0 +1,309 @@
+//! Volume-level information.
+
+use crate::constants::*;
+use crate::date::AmigaDate;
+use crate::dir::{DirEntry, DirIter};
+use crate::error::Result;
+use crate::reader::AffsReader;
+use crate::types::{BlockDevice, EntryType, FsFlags, FsType, SectorDevice};
+use crate::varblock::{AffsReaderVar, VarDirEntry, VarDirIter};
+
+/// Volume metadata, as returned by [`AffsReader::volume_info`].
+#[derive(Debug, Clone, Copy)]
+pub struct VolumeInfo {
+    /// Volume creation date (root offset 0x1A4).
+    pub creation_date: AmigaDate,
+    /// Volume last modification date (root offset 0x1D8).
+    pub last_modified: AmigaDate,
+    /// Filesystem creation date (root offset 0x1E4).
+    pub fs_creation_date: AmigaDate,
+    /// Filesystem type.
+    pub fs_type: FsType,
+    /// Filesystem flags.
+    pub fs_flags: FsFlags,
+    /// Block size in bytes.
+    pub block_size: u32,
+    /// Total blocks on the volume.
+    pub total_blocks: u32,
+    name: [u8; MAX_NAME_LEN],
+    name_len: u8,
+}
+
+impl VolumeInfo {
+    /// Get the volume name as a byte slice.
+    #[inline]
+    pub fn name(&self) -> &[u8] {
+        &self.name[..self.name_len as usize]
+    }
+
+    /// Get the volume name as a string (if valid UTF-8).
+    #[inline]
+    pub fn name_str(&self) -> Option<&str> {
+        crate::utf8::from_utf8(self.name())
+    }
+}
+
+impl<D: BlockDevice + ?Sized> AffsReader<'_, D> {
+    /// Gather the volume metadata stored in the boot and root blocks.
+    pub fn volume_info(&self) -> VolumeInfo {
+        let disk_name = self.disk_name();
+        let mut name = [0u8; MAX_NAME_LEN];
+        name[..disk_name.len()].copy_from_slice(disk_name);
+
+        VolumeInfo {
+            creation_date: self.creation_date(),
+            last_modified: self.last_modified(),
+            fs_creation_date: self.fs_creation_date(),
+            fs_type: self.fs_type(),
+            fs_flags: self.fs_flags(),
+            block_size: self.block_size() as u32,
+            total_blocks: self.total_blocks(),
+            name,
+            name_len: disk_name.len() as u8,
+        }
+    }
+}
+
+/// Operations shared by [`AffsReader`] and [`AffsReaderVar`].
+///
+/// Lets code be written once for both fixed and variable block size
+/// volumes. Each method forwards to the reader's inherent method of the
+/// same name.
+pub trait AffsVolume {
+    /// Directory entry type yielded by [`read_dir`](Self::read_dir).
+    type Entry: VolumeEntry;
+
+    /// Directory iterator returned by [`read_dir`](Self::read_dir).
+    type DirIter<'s>: Iterator<Item = Result<Self::Entry>>
+    where
+        Self: 's;
+
+    /// Get the filesystem type (OFS or FFS).
+    fn fs_type(&self) -> FsType;
+
+    /// Get filesystem flags.
+    fn fs_flags(&self) -> FsFlags;
+
+    /// Get the root block number.
+    fn root_block(&self) -> u32;
+
+    /// Get the total number of blocks.
+    fn total_blocks(&self) -> u32;
+
+    /// Get the block size in bytes.
+    fn block_size(&self) -> usize;
+
+    /// Get the log2 block size (relative to 512).
+    fn log_blocksize(&self) -> u8;
+
+    /// Get the disk name as bytes.
+    fn disk_name(&self) -> &[u8];
+
+    /// Iterate over entries in a directory.
+    fn read_dir(&self, block: u32) -> Result<Self::DirIter<'_>>;
+
+    /// Iterate over entries in the root directory.
+    #[inline]
+    fn read_root_dir(&self) -> Result<Self::DirIter<'_>> {
+        self.read_dir(self.root_block())
+    }
+}
+
+/// Fields shared by [`DirEntry`] and [`VarDirEntry`].
+pub trait VolumeEntry {
+    /// Get the entry name as bytes.
+    fn name(&self) -> &[u8];
+
+    /// Get the entry type.
+    fn entry_type(&self) -> EntryType;
+
+    /// Get the block number of this entry.
+    fn block(&self) -> u32;
+
+    /// Get the parent block number.
+    fn parent(&self) -> u32;
+
+    /// Get the file size (0 for directories).
+    fn size(&self) -> u32;
+
+    /// Get the last modification date.
+    fn date(&self) -> AmigaDate;
+
+    /// Get the entry name as a string (if valid UTF-8).
+    #[inline]
+    fn name_str(&self) -> Option<&str> {
+        crate::utf8::from_utf8(self.name())
+    }
+
+    /// Check if this is a directory.
+    #[inline]
+    fn is_dir(&self) -> bool {
+        self.entry_type().is_dir()
+    }
+
+    /// Check if this is a file.
+    #[inline]
+    fn is_file(&self) -> bool {
+        self.entry_type().is_file()
+    }
+}
+
+impl<'a, D: BlockDevice + ?Sized> AffsVolume for AffsReader<'a, D> {
+    type Entry = DirEntry;
+    type DirIter<'s>
+        = DirIter<'s, D>
+    where
+        Self: 's;
+
+    #[inline]
+    fn fs_type(&self) -> FsType {
+        AffsReader::fs_type(self)
+    }
+
+    #[inline]
+    fn fs_flags(&self) -> FsFlags {
+        AffsReader::fs_flags(self)
+    }
+
+    #[inline]
+    fn root_block(&self) -> u32 {
+        AffsReader::root_block(self)
+    }
+
+    #[inline]
+    fn total_blocks(&self) -> u32 {
+        AffsReader::total_blocks(self)
+    }
+
+    #[inline]
+    fn block_size(&self) -> usize {
+        AffsReader::block_size(self)
+    }
+
+    #[inline]
+    fn log_blocksize(&self) -> u8 {
+        AffsReader::log_blocksize(self)
+    }
+
+    #[inline]
+    fn disk_name(&self) -> &[u8] {
+        AffsReader::disk_name(self)
+    }
+
+    #[inline]
+    fn read_dir(&self, block: u32) -> Result<DirIter<'_, D>> {
+        AffsReader::read_dir(self, block)
+    }
+}
+
+impl<'a, D: SectorDevice> AffsVolume for AffsReaderVar<'a, D> {
+    type Entry = VarDirEntry;
+    type DirIter<'s>
+        = VarDirIter<'s, D>
+    where
+        Self: 's;
+
+    #[inline]
+    fn fs_type(&self) -> FsType {
+        AffsReaderVar::fs_type(self)
+    }
+
+    #[inline]
+    fn fs_flags(&self) -> FsFlags {
+        AffsReaderVar::fs_flags(self)
+    }
+
+    #[inline]
+    fn root_block(&self) -> u32 {
+        AffsReaderVar::root_block(self)
+    }
+
+    #[inline]
+    fn total_blocks(&self) -> u32 {
+        AffsReaderVar::total_blocks(self)
+    }
+
+    #[inline]
+    fn block_size(&self) -> usize {
+        AffsReaderVar::block_size(self)
+    }
+
+    #[inline]
+    fn log_blocksize(&self) -> u8 {
+        AffsReaderVar::log_blocksize(self)
+    }
+
+    #[inline]
+    fn disk_name(&self) -> &[u8] {
+        AffsReaderVar::disk_name(self)
+    }
+
+    #[inline]
+    fn read_dir(&self, block: u32) -> Result<VarDirIter<'_, D>> {
+        AffsReaderVar::read_dir(self, block)
+    }
+}
+
+impl VolumeEntry for DirEntry {
+    #[inline]
+    fn name(&self) -> &[u8] {
+        DirEntry::name(self)
+    }
+
+    #[inline]
+    fn entry_type(&self) -> EntryType {
+        self.entry_type
+    }
+
+    #[inline]
+    fn block(&self) -> u32 {
+        self.block
+    }
+
+    #[inline]
+    fn parent(&self) -> u32 {
+        self.parent
+    }
+
+    #[inline]
+    fn size(&self) -> u32 {
+        self.size
+    }
+
+    #[inline]
+    fn date(&self) -> AmigaDate {
+        self.date
+    }
+}
+
+impl VolumeEntry for VarDirEntry {
+    #[inline]
+    fn name(&self) -> &[u8] {
+        VarDirEntry::name(self)
+    }
+
+    #[inline]
+    fn entry_type(&self) -> EntryType {
+        self.entry_type
+    }
+
+    #[inline]
+    fn block(&self) -> u32 {
+        self.block
+    }
+
+    #[inline]
+    fn parent(&self) -> u32 {
+        self.parent
+    }
+
+    #[inline]
+    fn size(&self) -> u32 {
+        self.size
+    }
+
+    #[inline]
+    fn date(&self) -> AmigaDate {
+        self.date
+    }
+}
@@ -0,0 +1,263 @@
+//! Recursive directory tree traversal.
+
+use crate::constants::*;
+use crate::dir::DirEntry;
+use crate::error::{AffsError, Result};
+use crate::path::AffsPath;
+use crate::reader::AffsReader;
+use crate::types::{BlockDevice, EntryType};
+
+/// Position within one directory on the walk stack.
+#[derive(Debug, Clone, Copy, Default)]
+struct Frame {
+    /// Directory block being iterated.
+    dir_block: u32,
+    /// Next hash table slot to visit.
+    hash_index: usize,
+    /// Next entry in the current hash chain (0 if none).
+    chain: u32,
+}
+
+/// Depth-first iterator over a directory tree.
+///
+/// Uses a fixed-size stack of `N` directory levels and zero heap allocation.
+/// Only the hash table of the innermost directory is kept in memory; parent
+/// tables are re-read when a subdirectory is finished.
+///
+/// Created by [`AffsReader::walk`] or [`WalkIter::new`].
+pub struct WalkIter<'a, D: BlockDevice + ?Sized, const N: usize = DEFAULT_WALK_DEPTH> {
+    reader: &'a AffsReader<'a, D>,
+    stack: [Frame; N],
+    depth: usize,
+    /// Hash table of the directory on top of the stack.
+    hash_table: [u32; HASH_TABLE_SIZE],
+    /// Directory to descend into on the next call (0 if none).
+    pending: u32,
+    /// Number of entries visited, used to detect cycles.
+    visited: u32,
+    done: bool,
+}
+
+impl<'a, D: BlockDevice + ?Sized, const N: usize> WalkIter<'a, D, N> {
+    /// Create a walker over the tree below `start_block` with a maximum depth of `N`.
+    pub fn new(reader: &'a AffsReader<'a, D>, start_block: u32) -> Self {
+        Self {
+            reader,
+            stack: [Frame::default(); N],
+            depth: 0,
+            hash_table: [0u32; HASH_TABLE_SIZE],
+            pending: start_block,
+            visited: 0,
+            done: false,
+        }
+    }
+
+    /// Block number of the directory containing the most recently yielded entry.
+    ///
+    /// Returns 0 once the walk has finished.
+    #[inline]
+    pub fn current_dir(&self) -> u32 {
+        if self.depth == 0 {
+            0
+        } else {
+            self.stack[self.depth - 1].dir_block
+        }
+    }
+
+    /// Push the pending directory (if any) onto the stack.
+    fn descend(&mut self) -> Result<()> {
+        let block = self.pending;
+        if block == 0 {
+            return Ok(());
+        }
+        self.pending = 0;
+
+        if self.depth >= N {
+            return Err(AffsError::InvalidState);
+        }
+
+        self.hash_table = self.reader.dir_hash_table(block)?;
+        self.stack[self.depth] = Frame {
+            dir_block: block,
+            hash_index: 0,
+            chain: 0,
+        };
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Pop the finished directory and reload its parent's hash table.
+    fn ascend(&mut self) -> Result<()> {
+        self.depth -= 1;
+        if self.depth > 0 {
+            let parent = self.stack[self.depth - 1].dir_block;
+            self.hash_table = self.reader.dir_hash_table(parent)?;
+        }
+        Ok(())
+    }
+}
+
+impl<D: BlockDevice + ?Sized, const N: usize> Iterator for WalkIter<'_, D, N> {
+    type Item = Result<(u32, DirEntry)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        if let Err(e) = self.descend() {
+            return Some(Err(e));
+        }
+
+        loop {
+            if self.depth == 0 {
+                self.done = true;
+                return None;
+            }
+
+            let depth = self.depth;
+            let frame = &mut self.stack[depth - 1];
+
+            if frame.chain != 0 {
+                let block = frame.chain;
+
+                self.visited += 1;
+                if self.visited > self.reader.total_blocks() {
+                    self.done = true;
+                    return Some(Err(AffsError::InvalidState));
+                }
+
+                let entry = match self.reader.read_entry(block) {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        // Abandon this chain so iteration always makes progress
+                        frame.chain = 0;
+                        return Some(Err(e));
+                    }
+                };
+                frame.chain = entry.next_same_hash;
+
+                let Some(dir_entry) = DirEntry::from_entry_block(block, &entry) else {
+                    continue;
+                };
+
+                if dir_entry.entry_type == EntryType::Dir {
+                    self.pending = block;
+                }
+
+                return Some(Ok((depth as u32, dir_entry)));
+            }
+
+            // Find next non-empty hash slot
+            while frame.hash_index < HASH_TABLE_SIZE {
+                let block = self.hash_table[frame.hash_index];
+                frame.hash_index += 1;
+
+                if block != 0 {
+                    frame.chain = block;
+                    break;
+                }
+            }
+
+            if frame.chain != 0 {
+                continue;
+            }
+
+            if let Err(e) = self.ascend() {
+                // The parent's position can no longer be trusted
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+/// Flat iterator over every entry on a volume.
+///
+/// Walks the whole tree from the root like [`WalkIter`], but yields bare
+/// entries without their depth.
+///
+/// Returned by [`AffsReader::entries`].
+pub struct EntriesIter<'a, D: BlockDevice + ?Sized> {
+    inner: WalkIter<'a, D>,
+}
+
+impl<D: BlockDevice + ?Sized> Iterator for EntriesIter<'_, D> {
+    type Item = Result<DirEntry>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|item| item.map(|(_, entry)| entry))
+    }
+}
+
+/// Marks a directory whose path did not fit, so its children don't either.
+const PATH_TOO_LONG: usize = usize::MAX;
+
+/// Iterator over every entry on a volume together with its absolute path.
+///
+/// Walks the tree from the root like [`WalkIter`], keeping the path of the
+/// current directory as it descends, so no parent chains are re-read.
+/// Paths are built in an [`AffsPath<N>`] and start with `/`, as returned by
+/// [`AffsReader::entry_path`]. An entry whose path does not fit in `N`
+/// bytes yields `AffsError::BufferTooSmall`, as does everything below it.
+///
+/// Returned by [`AffsReader::walk_paths`].
+pub struct WalkPathsIter<'a, D: BlockDevice + ?Sized, const N: usize> {
+    inner: WalkIter<'a, D>,
+    path: AffsPath<N>,
+    /// Path length of the last entry yielded at each depth.
+    lens: [usize; DEFAULT_WALK_DEPTH + 1],
+}
+
+impl<D: BlockDevice + ?Sized, const N: usize> Iterator for WalkPathsIter<'_, D, N> {
+    type Item = Result<(AffsPath<N>, DirEntry)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (depth, entry) = match self.inner.next()? {
+            Ok(item) => item,
+            Err(e) => return Some(Err(e)),
+        };
+        let depth = depth as usize;
+
+        // Children follow their directory, whose path is at `depth - 1`
+        let base = self.lens[depth - 1];
+        if base == PATH_TOO_LONG {
+            self.lens[depth] = PATH_TOO_LONG;
+            return Some(Err(AffsError::BufferTooSmall));
+        }
+        self.path.truncate(base);
+        if let Err(e) = self.path.push_component(entry.name()) {
+            self.lens[depth] = PATH_TOO_LONG;
+            return Some(Err(e));
+        }
+        self.lens[depth] = self.path.len();
+
+        Some(Ok((self.path, entry)))
+    }
+}
+
+impl<D: BlockDevice + ?Sized> AffsReader<'_, D> {
+    /// Iterate over every file, directory, and symlink on the volume.
+    ///
+    /// The root directory itself is not yielded. Errors and cycle detection
+    /// are the same as for [`walk`](Self::walk).
+    pub fn entries(&self) -> EntriesIter<'_, D> {
+        EntriesIter {
+            inner: self.walk(self.root_block()),
+        }
+    }
+
+    /// Iterate over every entry on the volume with its absolute path.
+    ///
+    /// Paths are built incrementally in `N`-byte buffers, which is much
+    /// cheaper than calling [`entry_path`](Self::entry_path) per entry. See
+    /// [`WalkPathsIter`] for details.
+    pub fn walk_paths<const N: usize>(&self) -> WalkPathsIter<'_, D, N> {
+        WalkPathsIter {
+            inner: self.walk(self.root_block()),
+            path: AffsPath::new(),
+            lens: [0; DEFAULT_WALK_DEPTH + 1],
+        }
+    }
+}
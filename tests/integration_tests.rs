@@ -503,6 +503,112 @@ fn test_fs_flags() {
     assert!(reader.is_intl());
     assert!(reader.fs_flags().intl);
     assert!(!reader.fs_flags().dircache);
+    assert!(!reader.supports_long_names());
+}
+
+#[test]
+fn test_long_name_variants() {
+    let mut device = create_test_disk();
+
+    device.get_block_mut(0)[3] = 6; // DOS\6
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(reader.supports_long_names());
+    assert_eq!(reader.fs_type(), FsType::Ofs);
+    assert!(reader.is_intl());
+    assert!(!reader.fs_flags().dircache);
+
+    device.get_block_mut(0)[3] = 7; // DOS\7
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(reader.supports_long_names());
+    assert_eq!(reader.fs_type(), FsType::Ffs);
+    assert!(reader.is_intl());
+    assert!(!reader.fs_flags().dircache);
+}
+
+/// Move a classic file header's name into the long filename layout.
+///
+/// The name and `comment` go in the combined area at 0x148 and the date
+/// at 0x1C4.
+fn to_long_name_header(buf: &mut [u8; 512], name: &[u8], comment: &[u8], date: (i32, i32, i32)) {
+    buf[0x148..0x1D0].fill(0);
+    buf[0x148] = name.len() as u8;
+    buf[0x149..0x149 + name.len()].copy_from_slice(name);
+    let comment_at = 0x149 + name.len();
+    buf[comment_at] = comment.len() as u8;
+    buf[comment_at + 1..comment_at + 1 + comment.len()].copy_from_slice(comment);
+    write_i32_be(buf, 0x1C4, date.0);
+    write_i32_be(buf, 0x1C8, date.1);
+    write_i32_be(buf, 0x1CC, date.2);
+    set_checksum(buf, 20);
+}
+
+/// A `DOS\7` disk whose root hash chain holds an entry with a 40-byte
+/// name at 890, followed by `testfile` at 882.
+fn create_long_name_disk() -> MockDevice {
+    let mut device = create_test_disk();
+    device.get_block_mut(0)[3] = 7;
+
+    let mut file = create_file_header(b"testfile", 100, 880, 883, &[883]);
+    to_long_name_header(&mut file, b"testfile", b"note", (7000, 60, 50));
+    device.set_block(882, &file);
+
+    let long = [b'x'; 40];
+    let mut other = create_file_header(b"x", 0, 880, 0, &[]);
+    to_long_name_header(&mut other, &long, b"", (0, 0, 0));
+    write_u32_be(&mut other, 0x1F0, 882);
+    set_checksum(&mut other, 20);
+    device.set_block(890, &other);
+
+    let root = device.get_block_mut(880);
+    write_u32_be(root, 24 + hash_name(b"testfile", true) * 4, 890);
+    set_checksum(root, 20);
+    device
+}
+
+#[test]
+fn test_long_name_layout() {
+    let device = create_long_name_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    let entry = reader.read_entry(882).unwrap();
+    assert_eq!(entry.name(), b"testfile");
+    assert_eq!(entry.comment(), b"note");
+    assert_eq!(entry.date, AmigaDate::new(7000, 60, 50));
+    assert_eq!(entry.byte_size, 100);
+
+    // Lookups step past the long name in the chain
+    let found = reader.find_path(b"testfile").unwrap();
+    assert_eq!(found.block, 882);
+    assert_eq!(
+        reader
+            .find_entry(reader.root_block(), b"TESTFILE")
+            .unwrap()
+            .block,
+        882
+    );
+    let mut data = [0u8; 100];
+    assert_eq!(reader.read_file(882).unwrap().read(&mut data).unwrap(), 100);
+
+    // Names over 30 bytes are an error, not truncated
+    assert!(matches!(
+        reader.read_entry(890),
+        Err(AffsError::NameTooLong)
+    ));
+    let results: Vec<_> = reader.read_root_dir().collect();
+    assert_eq!(results.len(), 2);
+    assert!(matches!(results[0], Err(AffsError::NameTooLong)));
+    assert_eq!(results[1].as_ref().unwrap().name(), b"testfile");
+    assert_eq!(reader.count_entries(reader.root_block()).unwrap(), 2);
+}
+
+#[test]
+fn test_long_names_not_dircache() {
+    let mut device = create_dircache_disk();
+    device.get_block_mut(0)[3] = 7;
+
+    let reader = AffsReader::new(&device).unwrap();
+    let iter = reader.read_dir_cached(reader.root_block()).unwrap();
+    assert!(matches!(iter, CachedDirIter::HashTable(_)));
 }
 
 #[test]
@@ -526,6 +632,106 @@ fn test_hd_floppy() {
     assert_eq!(reader.root_block(), 1760);
 }
 
+#[test]
+fn test_raw_name_len_preserved() {
+    let mut device = create_test_disk();
+    let mut header = create_file_header(b"testfile", 100, 880, 883, &[883]);
+    header[0x1B0] = 200;
+    set_checksum(&mut header, 20);
+    device.set_block(882, &header);
+
+    let reader = AffsReader::new(&device).unwrap();
+    let entry = reader.read_entry(882).unwrap();
+    assert_eq!(entry.raw_name_len(), 200);
+    assert_eq!(entry.name_len, 30);
+    assert_eq!(entry.name().len(), 30);
+    assert_eq!(&entry.name()[..8], b"testfile");
+
+    let mut root = device.blocks[880];
+    root[0x1B0] = 200;
+    set_checksum(&mut root, 20);
+    let root = RootBlock::parse(&root).unwrap();
+    assert_eq!(root.raw_name_len(), 200);
+    assert_eq!(root.name().len(), 30);
+
+    let normal = EntryBlock::parse(&create_file_header(b"ok", 0, 880, 0, &[])).unwrap();
+    assert_eq!(normal.raw_name_len(), 2);
+}
+
+#[test]
+fn test_volume_info() {
+    let mut device = create_test_disk();
+    let mut root = device.blocks[880];
+    for (offset, (days, mins, ticks)) in [
+        (0x1A4, (6988, 100, 10)),
+        (0x1D8, (7000, 200, 20)),
+        (0x1E4, (5000, 300, 30)),
+    ] {
+        write_i32_be(&mut root, offset, days);
+        write_i32_be(&mut root, offset + 4, mins);
+        write_i32_be(&mut root, offset + 8, ticks);
+    }
+    set_checksum(&mut root, 20);
+    device.set_block(880, &root);
+
+    let reader = AffsReader::new(&device).unwrap();
+    let info = reader.volume_info();
+    assert_eq!(info.name(), b"TestDisk");
+    assert_eq!(info.name_str(), Some("TestDisk"));
+    assert_eq!(info.creation_date, AmigaDate::new(6988, 100, 10));
+    assert_eq!(info.last_modified, AmigaDate::new(7000, 200, 20));
+    assert_eq!(info.fs_creation_date, AmigaDate::new(5000, 300, 30));
+    assert_eq!(reader.creation_date(), AmigaDate::new(6988, 100, 10));
+    assert_eq!(reader.volume_modified(), AmigaDate::new(7000, 200, 20));
+    assert_eq!(reader.last_modified(), AmigaDate::new(7000, 200, 20));
+    assert_eq!(reader.fs_creation_date(), AmigaDate::new(5000, 300, 30));
+    assert_eq!(info.fs_type, FsType::Ffs);
+    assert!(!info.fs_flags.intl);
+    assert_eq!(info.block_size, 512);
+    assert_eq!(info.total_blocks, 1760);
+}
+
+#[test]
+fn test_auto_size_dd() {
+    let device = create_test_disk();
+    let reader = AffsReader::auto_size(&device).unwrap();
+    assert_eq!(reader.total_blocks(), 1760);
+    assert_eq!(reader.root_block(), 880);
+    assert!(reader.find_path(b"testfile").is_ok());
+}
+
+#[test]
+fn test_auto_size_hd() {
+    let mut device = MockDevice::new(3520);
+    // Boot block still carries the DD root pointer (880)
+    let (boot0, boot1) = create_boot_block();
+    device.set_block(0, &boot0);
+    device.set_block(1, &boot1);
+    device.set_block(1760, &create_root_block(b"HDDisk"));
+
+    let reader = AffsReader::auto_size(&device).unwrap();
+    assert_eq!(reader.total_blocks(), 3520);
+    assert_eq!(reader.root_block(), 1760);
+    assert_eq!(reader.disk_name(), b"HDDisk");
+}
+
+#[test]
+fn test_auto_size_no_match() {
+    let mut device = MockDevice::new(1760);
+    let (boot0, boot1) = create_boot_block();
+    device.set_block(0, &boot0);
+    device.set_block(1, &boot1);
+
+    let mut root = create_root_block(b"Broken");
+    root[100] = 0xFF;
+    device.set_block(880, &root);
+
+    assert!(matches!(
+        AffsReader::auto_size(&device),
+        Err(AffsError::InvalidState)
+    ));
+}
+
 #[test]
 fn test_invalid_dos_type() {
     let mut device = MockDevice::new(1760);
@@ -540,6 +746,46 @@ fn test_invalid_dos_type() {
     assert!(matches!(result, Err(AffsError::InvalidDosType)));
 }
 
+#[test]
+fn test_empty_volume() {
+    let mut device = MockDevice::new(1760);
+    let (boot0, boot1) = create_boot_block();
+    device.set_block(0, &boot0);
+    device.set_block(1, &boot1);
+
+    // Root block never written
+    assert!(matches!(
+        AffsReader::new(&device),
+        Err(AffsError::EmptyVolume)
+    ));
+    assert!(matches!(
+        AffsReader::with_size(&device, 1760),
+        Err(AffsError::EmptyVolume)
+    ));
+
+    // Stray bytes, but no type, name or bitmap flag
+    let mut root = [0u8; 512];
+    root[100] = 0xE5;
+    device.set_block(880, &root);
+    assert!(matches!(
+        AffsReader::new(&device),
+        Err(AffsError::EmptyVolume)
+    ));
+
+    // A root with a type set reports its own parse error
+    write_i32_be(&mut root, 0, 2);
+    write_i32_be(&mut root, 508, 1);
+    device.set_block(880, &root);
+    assert!(matches!(
+        AffsReader::new(&device),
+        Err(AffsError::ChecksumMismatch)
+    ));
+    assert_eq!(
+        AffsError::EmptyVolume.to_string(),
+        "empty volume (root block not initialized)"
+    );
+}
+
 #[test]
 fn test_invalid_root_block_type() {
     let mut device = MockDevice::new(1760);
@@ -574,6 +820,24 @@ fn test_invalid_root_sec_type() {
     assert!(matches!(result, Err(AffsError::InvalidSecType)));
 }
 
+#[test]
+fn test_invalid_root_hash_table_size() {
+    let mut device = create_test_disk();
+    let mut root = create_root_block(b"Test");
+    write_i32_be(&mut root, 12, 128);
+    set_checksum(&mut root, 20);
+    device.set_block(880, &root);
+
+    assert!(matches!(
+        AffsReader::new(&device),
+        Err(AffsError::InvalidState)
+    ));
+    assert!(matches!(
+        RootBlock::parse(device.get_block_mut(880)),
+        Err(AffsError::InvalidState)
+    ));
+}
+
 #[test]
 fn test_checksum_mismatch() {
     let mut device = MockDevice::new(1760);
@@ -590,6 +854,71 @@ fn test_checksum_mismatch() {
     assert!(matches!(result, Err(AffsError::ChecksumMismatch)));
 }
 
+#[test]
+fn test_lenient_checksums_corrupt_root() {
+    let mut device = create_test_disk();
+
+    // Root block listing "testfile", checksum left stale
+    let mut root = create_root_block(b"TestDisk");
+    let hash_idx = hash_name(b"testfile", false);
+    write_u32_be(&mut root, 24 + hash_idx * 4, 882);
+    device.set_block(880, &root);
+
+    assert!(matches!(
+        AffsReader::new(&device),
+        Err(AffsError::ChecksumMismatch)
+    ));
+
+    let options = Options {
+        verify_checksums: false,
+    };
+    let reader = AffsReader::with_options(&device, 1760, options).unwrap();
+    assert_eq!(reader.options(), options);
+
+    let entries: Vec<_> = reader.read_root_dir().collect();
+    assert_eq!(entries.len(), 1);
+    let entry = entries[0].as_ref().unwrap();
+    assert_eq!(entry.name(), b"testfile");
+
+    let mut file = reader.read_file(entry.block).unwrap();
+    let mut buf = [0u8; 100];
+    assert_eq!(file.read(&mut buf).unwrap(), 100);
+    assert_eq!(buf[0], 1);
+}
+
+#[test]
+fn test_lenient_checksums_corrupt_entry() {
+    let mut device = create_test_disk();
+
+    let mut header = create_file_header(b"testfile", 100, 880, 883, &[883]);
+    header[300] ^= 0xFF;
+    device.set_block(882, &header);
+
+    let strict = AffsReader::new(&device).unwrap();
+    let first = strict.read_root_dir().next().unwrap();
+    assert!(matches!(first, Err(AffsError::ChecksumMismatch)));
+
+    let lenient = AffsReader::with_options(
+        &device,
+        1760,
+        Options {
+            verify_checksums: false,
+        },
+    )
+    .unwrap();
+    let entry = lenient.find_path(b"testfile").unwrap();
+    assert_eq!(entry.block, 882);
+    assert!(lenient.read_file(882).is_ok());
+}
+
+#[test]
+fn test_options_default_is_strict() {
+    assert!(Options::default().verify_checksums);
+    let device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(reader.options().verify_checksums);
+}
+
 #[test]
 fn test_not_a_directory() {
     let device = create_test_disk();
@@ -660,6 +989,80 @@ fn test_fs_type_data_block_size() {
     assert_eq!(FsType::Ffs.data_block_size(), 512);
 }
 
+#[test]
+fn test_read_raw_block() {
+    let device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    let mut buf = [0u8; 512];
+    reader.read_raw_block(0, &mut buf).unwrap();
+    assert_eq!(&buf[0..3], b"DOS");
+    reader.read_raw_block(880, &mut buf).unwrap();
+    assert_eq!(buf, device.blocks[880]);
+    reader.read_raw_block(1759, &mut buf).unwrap();
+
+    assert!(matches!(
+        reader.read_raw_block(1760, &mut buf),
+        Err(AffsError::BlockOutOfRange)
+    ));
+    assert_eq!(reader.last_error_block(), Some(1760));
+
+    // In range for the reader but not backed by the device
+    let reader = AffsReader::with_size(&device, 2000).unwrap();
+    assert!(matches!(
+        reader.read_raw_block(1800, &mut buf),
+        Err(AffsError::BlockReadError)
+    ));
+}
+
+#[test]
+fn test_block_num() {
+    let block = BlockNum::from(882);
+    assert_eq!(block, BlockNum::new(882));
+    assert_eq!(block.get(), 882);
+    assert_eq!(u32::from(block), 882);
+    let raw: u32 = block.into();
+    assert_eq!(raw, 882);
+    assert_eq!(format!("{block}"), "882");
+    assert!(BlockNum(880) < block);
+
+    // Reader methods accept literals, u32 values and BlockNum alike
+    let device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let root: BlockNum = reader.root_block().into();
+    let entry = reader.find_entry(root, b"testfile").unwrap();
+    assert_eq!(entry.block, 882);
+    assert_eq!(reader.find_entry(880, b"testfile").unwrap().block, 882);
+    assert_eq!(
+        reader.read_entry(BlockNum(882)).unwrap().name(),
+        reader.read_entry(882).unwrap().name()
+    );
+    assert_eq!(reader.read_file(block).unwrap().size(), 100);
+    assert_eq!(reader.read_file(entry.block).unwrap().size(), 100);
+    assert!(reader.read_dir(root).unwrap().count() > 0);
+}
+
+#[test]
+fn test_access_flag_set() {
+    let all = Access::new(0xFF).flags();
+    assert_eq!(all.len(), 8);
+    assert_eq!(all.bits(), 0xFF);
+    assert!(AccessFlag::ALL.iter().all(|&flag| all.contains(flag)));
+    assert_eq!(all.iter().collect::<Vec<_>>(), AccessFlag::ALL);
+    let letters: String = all.into_iter().map(AccessFlag::letter).collect();
+    assert_eq!(letters, "hsparwed");
+    assert_eq!(Access::new(0xFF).as_protection_bits(), 0xFF);
+
+    let some = Access::new(0xF00 | ACC_SCRIPT | ACC_READ).flags();
+    let names: Vec<_> = some.iter().map(AccessFlag::name).collect();
+    assert_eq!(names, ["script", "read"]);
+    assert!(!some.contains(AccessFlag::Delete));
+    assert_eq!(Access::new(0xF00 | ACC_READ).as_protection_bits(), ACC_READ);
+
+    assert!(Access::new(0).flags().is_empty());
+    assert_eq!(Access::new(0).flags().iter().next(), None);
+}
+
 #[test]
 fn test_access_flags() {
     let access = Access::new(0b11111111);
@@ -692,13 +1095,21 @@ fn test_fs_flags_dircache() {
     assert!(!flags.intl);
     assert!(flags.dircache);
 
-    let flags2 = FsFlags::from_dos_type(6); // INTL + DIRCACHE
-    assert!(flags2.intl);
+    let flags2 = FsFlags::from_dos_type(5); // FFS + DIRCACHE
+    assert!(!flags2.intl);
     assert!(flags2.dircache);
+    assert!(!flags2.long_names);
+
+    // DOS\6 is the long filename variant, not INTL + DIRCACHE
+    let flags3 = FsFlags::from_dos_type(6);
+    assert!(flags3.intl);
+    assert!(!flags3.dircache);
+    assert!(flags3.long_names);
 
     let default_flags = FsFlags::default();
     assert!(!default_flags.intl);
     assert!(!default_flags.dircache);
+    assert!(!default_flags.long_names);
 }
 
 #[test]
@@ -1002,6 +1413,29 @@ fn test_boot_block_with_code() {
 
     let reader = AffsReader::new(&device).unwrap();
     assert_eq!(reader.disk_name(), b"BootDisk");
+    assert!(reader.is_bootable());
+
+    full_boot[..512].copy_from_slice(&block0);
+    let code = BootBlock::boot_code(&full_boot);
+    assert_eq!(code.len(), 1012);
+    assert_eq!(code[0], 0x60);
+    assert!(BootBlock::parse(&full_boot).unwrap().is_bootable());
+}
+
+#[test]
+fn test_boot_block_not_bootable() {
+    let device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(!reader.is_bootable());
+
+    // Code with a bad checksum is not bootable
+    let (mut block0, block1) = create_boot_block();
+    block0[12] = 0x60;
+    let mut full_boot = [0u8; 1024];
+    full_boot[..512].copy_from_slice(&block0);
+    full_boot[512..].copy_from_slice(&block1);
+    let boot = BootBlock::parse_with(&full_boot, false).unwrap();
+    assert!(!boot.is_bootable());
 }
 
 #[test]
@@ -1055,6 +1489,47 @@ fn test_ofs_data_block_parsing() {
     assert_eq!(data_slice[0], 0xAB);
 }
 
+#[test]
+fn test_ofs_data_block_valid_data() {
+    let block = create_ofs_data_block(882, 1, &[0x11; 12], 0);
+    let data = OfsDataBlock::valid_data(&block).unwrap();
+    assert_eq!(data.len(), 12);
+    assert!(data.iter().all(|&b| b == 0x11));
+
+    let mut block = create_ofs_data_block(882, 1, &[0x11; 12], 0);
+    write_u32_be(&mut block, 12, 489);
+    set_checksum(&mut block, 20);
+    assert!(matches!(
+        OfsDataBlock::valid_data(&block),
+        Err(AffsError::InvalidState)
+    ));
+}
+
+#[test]
+fn test_entry_block_rejects_crafted_header() {
+    // Valid type and checksum, everything else zero
+    let mut block = [0u8; 512];
+    write_i32_be(&mut block, 0, 2);
+    set_checksum(&mut block, 20);
+    assert!(matches!(
+        EntryBlock::parse(&block),
+        Err(AffsError::InvalidSecType)
+    ));
+
+    // Known secondary type but no name
+    write_i32_be(&mut block, 508, -3);
+    set_checksum(&mut block, 20);
+    assert!(matches!(
+        EntryBlock::parse(&block),
+        Err(AffsError::EmptyName)
+    ));
+
+    block[0x1B0] = 1;
+    block[0x1B1] = b'x';
+    set_checksum(&mut block, 20);
+    assert_eq!(EntryBlock::parse(&block).unwrap().name(), b"x");
+}
+
 #[test]
 fn test_invalid_ofs_data_block() {
     let mut block = [0u8; 512];
@@ -1309,6 +1784,29 @@ fn test_file_with_comment() {
     assert_eq!(entry.comment_str(), Some("This is a test comment"));
 }
 
+#[test]
+fn test_read_comment() {
+    let mut device = create_test_disk();
+    let mut file = create_file_header(b"testfile", 100, 880, 0, &[]);
+    let comment = b"Caf\xE9 notes";
+    file[0x148] = comment.len() as u8;
+    file[0x149..0x149 + comment.len()].copy_from_slice(comment);
+    set_checksum(&mut file, 20);
+    device.set_block(882, &file);
+    let reader = AffsReader::new(&device).unwrap();
+
+    let mut out = [0u8; max_utf8_len(MAX_COMMENT_LEN)];
+    let n = reader.read_comment(882, &mut out).unwrap();
+    assert_eq!(std::str::from_utf8(&out[..n]).unwrap(), "Caf\u{e9} notes");
+
+    let mut small = [0u8; 10];
+    assert_eq!(
+        reader.read_comment(882, &mut small),
+        Err(AffsError::BufferTooSmall)
+    );
+    assert_eq!(reader.read_comment(880, &mut out), Ok(0));
+}
+
 #[test]
 fn test_default_root_block_calculation() {
     // When boot block has root_block = 0, use middle of disk
@@ -1571,6 +2069,145 @@ fn test_file_reader_seek_backward() {
     assert_eq!(&buf2[..], &buf[20..30]);
 }
 
+#[test]
+fn test_file_reader_seek_from_end() {
+    let device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+
+    file.seek_from_end(5).unwrap();
+    assert_eq!(file.position(), 95);
+    let mut buf = [0u8; 10];
+    assert_eq!(file.read(&mut buf).unwrap(), 5);
+    assert_eq!(&buf[..5], &[96, 97, 98, 99, 100]);
+
+    file.seek_from_end(0).unwrap();
+    assert!(file.is_eof());
+    assert!(matches!(file.seek_from_end(101), Err(AffsError::EndOfFile)));
+}
+
+#[test]
+fn test_file_reader_seek_relative() {
+    let device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+
+    let mut buf = [0u8; 40];
+    file.read(&mut buf).unwrap();
+    file.seek_relative(-15).unwrap();
+    assert_eq!(file.position(), 25);
+
+    let mut one = [0u8; 1];
+    file.read(&mut one).unwrap();
+    assert_eq!(one[0], 26);
+
+    file.seek_relative(10).unwrap();
+    assert_eq!(file.position(), 36);
+
+    assert!(matches!(file.seek_relative(-37), Err(AffsError::EndOfFile)));
+    assert!(matches!(file.seek_relative(65), Err(AffsError::EndOfFile)));
+    assert_eq!(file.position(), 36);
+}
+
+#[test]
+fn test_buf_reader() {
+    let (device, size) = create_large_ffs_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file: BufReader<'_, _, 1024> = BufReader::new(reader.read_file(882).unwrap());
+    assert_eq!(file.capacity(), 1024);
+    assert_eq!(file.size(), size);
+
+    // Byte at a time, refilling every 1024 bytes
+    let mut byte = [0u8; 1];
+    for i in 0..3000 {
+        assert_eq!(file.read(&mut byte).unwrap(), 1);
+        assert_eq!(byte[0], large_file_byte(i));
+    }
+    assert_eq!(file.position(), 3000);
+    assert_eq!(file.get_ref().position(), 3072);
+    assert_eq!(file.buffer().len(), 72);
+
+    // Backward seek inside the buffer keeps it
+    file.seek(2100).unwrap();
+    assert_eq!(file.buffer().len(), 972);
+    file.read(&mut byte).unwrap();
+    assert_eq!(byte[0], large_file_byte(2100));
+
+    // Seek outside the buffer discards it
+    file.seek(30_000).unwrap();
+    assert!(file.buffer().is_empty());
+    assert_eq!(file.remaining(), size - 30_000);
+
+    // Large reads bypass the buffer
+    let mut big = vec![0u8; 2048];
+    assert_eq!(file.read(&mut big).unwrap(), 2048);
+    assert!(
+        big.iter()
+            .enumerate()
+            .all(|(i, &b)| b == large_file_byte(30_000 + i))
+    );
+    assert!(file.buffer().is_empty());
+
+    assert!(matches!(file.seek(size + 1), Err(AffsError::EndOfFile)));
+    file.seek(size - 1).unwrap();
+    file.read(&mut byte).unwrap();
+    assert!(file.is_eof());
+    assert_eq!(file.read(&mut byte).unwrap(), 0);
+
+    // Unwrapping drops the buffer without losing the position
+    file.seek(10).unwrap();
+    file.read(&mut byte).unwrap();
+    let mut inner = file.into_inner().unwrap();
+    assert_eq!(inner.position(), 11);
+    inner.read(&mut byte).unwrap();
+    assert_eq!(byte[0], large_file_byte(11));
+}
+
+#[test]
+fn test_buf_reader_ofs() {
+    let device = create_ofs_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file: BufReader<'_, _> = BufReader::new(reader.read_file(882).unwrap());
+    assert_eq!(file.capacity(), DEFAULT_BUF_SIZE);
+
+    let mut out = Vec::new();
+    let mut byte = [0u8; 1];
+    while file.read(&mut byte).unwrap() == 1 {
+        out.push(byte[0]);
+    }
+    assert_eq!(out, (10..60).collect::<Vec<u8>>());
+}
+
+#[test]
+fn test_file_reader_clone() {
+    let (device, size) = create_large_ffs_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+
+    let mut buf = [0u8; 30];
+    file.read(&mut buf).unwrap();
+    let mut copy = file.clone();
+    assert_eq!(copy.position(), 30);
+
+    let rest = size as usize - 30;
+    let mut a = vec![0u8; rest];
+    let mut b = vec![0u8; rest];
+    assert_eq!(file.read_all(&mut a).unwrap(), rest);
+    assert_eq!(copy.read_all(&mut b).unwrap(), rest);
+    assert_eq!(a, b);
+    assert!(
+        a.iter()
+            .enumerate()
+            .all(|(i, &x)| x == large_file_byte(i + 30))
+    );
+
+    // Clones seek independently
+    let mut other = file.clone();
+    other.seek(0).unwrap();
+    assert!(file.is_eof());
+    assert_eq!(other.position(), 0);
+}
+
 #[test]
 fn test_file_reader_reset() {
     let device = create_test_disk();
@@ -1610,6 +2247,25 @@ fn test_entry_block_comment_method() {
     assert_eq!(entry.comment(), b"Test comment here");
 }
 
+#[test]
+fn test_entry_block_first_data_block() {
+    // FFS headers often leave first_data at 0
+    let header = create_file_header(b"file", 1024, 880, 0, &[900, 901]);
+    let entry = EntryBlock::parse(&header).unwrap();
+    assert_eq!(entry.first_data_block(FsType::Ffs), 900);
+    assert_eq!(entry.first_data_block(FsType::Ofs), 0);
+
+    // OFS follows first_data, even if the table disagrees
+    let header = create_file_header(b"file", 1024, 880, 950, &[900, 901]);
+    let entry = EntryBlock::parse(&header).unwrap();
+    assert_eq!(entry.first_data_block(FsType::Ofs), 950);
+    assert_eq!(entry.first_data_block(FsType::Ffs), 900);
+
+    let empty = create_file_header(b"empty", 0, 880, 0, &[]);
+    let entry = EntryBlock::parse(&empty).unwrap();
+    assert_eq!(entry.first_data_block(FsType::Ffs), 0);
+}
+
 // ============================================================================
 // New Feature Tests: Symlink reading, Volume label, Modification time
 // ============================================================================
@@ -1725,6 +2381,30 @@ fn test_symlink_latin1_to_utf8() {
     assert_eq!(&target_buf[..len], &[b'c', b'a', b'f', 0xC3, 0xA9]);
 }
 
+#[test]
+fn test_symlink_buffer_too_small() {
+    let mut device = create_test_disk();
+    let symlink = create_softlink(b"testfile", b"path/to/caf\xE9\0", 880);
+    device.set_block(882, &symlink);
+    let reader = AffsReader::new(&device).unwrap();
+
+    // 12 Latin1 bytes become 13 UTF-8 bytes
+    let mut tiny = [0u8; 4];
+    assert!(matches!(
+        reader.read_symlink(882, &mut tiny),
+        Err(AffsError::SymlinkTooLong)
+    ));
+    let mut short = [0u8; 12];
+    assert!(matches!(
+        reader.read_symlink(882, &mut short),
+        Err(AffsError::SymlinkTooLong)
+    ));
+
+    let mut exact = [0u8; 13];
+    assert_eq!(reader.read_symlink(882, &mut exact).unwrap(), 13);
+    assert_eq!(&exact, "path/to/café".as_bytes());
+}
+
 #[test]
 fn test_symlink_not_a_symlink_error() {
     let device = create_test_disk();
@@ -1902,3 +2582,3607 @@ fn test_symlink_functions() {
     assert_eq!(len, 5);
     assert_eq!(&out[..len], b"hello");
 }
+
+// ============================================================================
+// Allocation bitmap
+// ============================================================================
+
+/// Create a bitmap block where the given bit indices are marked used.
+///
+/// All other bits (including those past the end of the volume) are free.
+fn create_bitmap_block(used: &[u32]) -> [u8; 512] {
+    let mut map = [0xFFFF_FFFFu32; 127];
+    for &bit in used {
+        map[(bit / 32) as usize] &= !(1 << (bit % 32));
+    }
+
+    let mut buf = [0u8; 512];
+    for (i, &word) in map.iter().enumerate() {
+        write_u32_be(&mut buf, 4 + i * 4, word);
+    }
+    let checksum = bitmap_sum(&buf);
+    write_u32_be(&mut buf, 0, checksum);
+    buf
+}
+
+#[test]
+fn test_bitmap_block_parse() {
+    let block = create_bitmap_block(&[0, 33]);
+    let bitmap = BitmapBlock::parse(&block).unwrap();
+
+    assert!(!bitmap.is_free(0));
+    assert!(bitmap.is_free(1));
+    assert!(!bitmap.is_free(33));
+    assert!(!bitmap.is_free(127 * 32)); // Out of range
+    assert_eq!(bitmap.count_free(40), 38);
+}
+
+#[test]
+fn test_bitmap_block_checksum_mismatch() {
+    let mut block = create_bitmap_block(&[]);
+    block[100] = 0;
+
+    let result = BitmapBlock::parse(&block);
+    assert!(matches!(result, Err(AffsError::ChecksumMismatch)));
+}
+
+#[test]
+fn test_free_blocks() {
+    let mut device = create_test_disk();
+    // Boot blocks are not tracked; root (880), bitmap (881), file header
+    // (882) and data block (883) are in use.
+    let used: Vec<u32> = [880, 881, 882, 883].iter().map(|b| b - 2).collect();
+    device.set_block(881, &create_bitmap_block(&used));
+
+    let reader = AffsReader::new(&device).unwrap();
+    assert_eq!(reader.free_blocks().unwrap(), 1758 - 4);
+    assert_eq!(reader.used_blocks().unwrap(), 6);
+    assert_eq!(reader.free_bytes().unwrap(), (1758 - 4) * 512);
+}
+
+#[test]
+fn test_free_blocks_bitmap_extension() {
+    // 120000 blocks need 30 bitmap pages: 25 in the root, 5 via extension
+    let mut device = MockDevice::new(1000);
+    let (mut boot0, boot1) = create_boot_block();
+    write_u32_be(&mut boot0, 8, 2);
+    device.set_block(0, &boot0);
+    device.set_block(1, &boot1);
+
+    let mut root = create_root_block(b"BigDisk");
+    for i in 0..25 {
+        write_u32_be(&mut root, 0x13C + i * 4, 10 + i as u32);
+    }
+    write_u32_be(&mut root, 0x1A0, 3);
+    set_checksum(&mut root, 20);
+    device.set_block(2, &root);
+
+    let mut ext = [0u8; 512];
+    for i in 0..5 {
+        write_u32_be(&mut ext, i * 4, 40 + i as u32);
+    }
+    device.set_block(3, &ext);
+
+    for page in (10..35).chain(40..45) {
+        device.set_block(page, &create_bitmap_block(&[]));
+    }
+    // One used block in the last page
+    device.set_block(44, &create_bitmap_block(&[0]));
+
+    let reader = AffsReader::with_size(&device, 120000).unwrap();
+    assert_eq!(reader.free_blocks().unwrap(), 120000 - 2 - 1);
+}
+
+#[test]
+fn test_bitmap_blocks_extension() {
+    let mut device = MockDevice::new(1000);
+    let (mut boot0, boot1) = create_boot_block();
+    write_u32_be(&mut boot0, 8, 2);
+    device.set_block(0, &boot0);
+    device.set_block(1, &boot1);
+
+    let mut root = create_root_block(b"BigDisk");
+    for i in 0..25 {
+        write_u32_be(&mut root, 0x13C + i * 4, 10 + i as u32);
+    }
+    write_u32_be(&mut root, 0x1A0, 3);
+    set_checksum(&mut root, 20);
+    device.set_block(2, &root);
+
+    let mut ext = [0u8; 512];
+    for i in 0..3 {
+        write_u32_be(&mut ext, i * 4, 40 + i as u32);
+    }
+    device.set_block(3, &ext);
+
+    let parsed = BitmapExtBlock::parse(&ext);
+    assert_eq!(&parsed.bm_pages[..4], &[40, 41, 42, 0]);
+    assert_eq!(parsed.next, 0);
+
+    let reader = AffsReader::with_size(&device, 120000).unwrap();
+    let pages: Vec<u32> = reader
+        .bitmap_blocks()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let expected: Vec<u32> = (10..35).chain(40..43).collect();
+    assert_eq!(pages, expected);
+
+    // An extension chain that points back at itself is cut off
+    write_u32_be(&mut ext, 508, 3);
+    for i in 0..127 {
+        write_u32_be(&mut ext, i * 4, 40);
+    }
+    device.set_block(3, &ext);
+    let reader = AffsReader::with_size(&device, 1000).unwrap();
+    let last = reader.bitmap_blocks().unwrap().last().unwrap();
+    assert_eq!(last, Err(AffsError::InvalidState));
+}
+
+#[test]
+fn test_free_blocks_invalid_bitmap() {
+    let mut device = create_test_disk();
+    let mut root = create_root_block(b"TestDisk");
+    write_i32_be(&mut root, 0x138, 0); // Bitmap not valid
+    set_checksum(&mut root, 20);
+    device.set_block(880, &root);
+
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(matches!(reader.free_blocks(), Err(AffsError::InvalidState)));
+}
+
+#[test]
+fn test_is_block_free() {
+    let mut device = create_test_disk();
+    let used: Vec<u32> = [880, 881, 882, 883].iter().map(|b| b - 2).collect();
+    device.set_block(881, &create_bitmap_block(&used));
+
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(!reader.is_block_free(880).unwrap());
+    assert!(!reader.is_block_free(883).unwrap());
+    assert!(reader.is_block_free(884).unwrap());
+    assert!(reader.is_block_free(2).unwrap());
+    assert!(reader.is_block_free(1759).unwrap());
+
+    assert!(matches!(
+        reader.is_block_free(1),
+        Err(AffsError::BlockOutOfRange)
+    ));
+    assert!(matches!(
+        reader.is_block_free(1760),
+        Err(AffsError::BlockOutOfRange)
+    ));
+}
+
+#[test]
+fn test_is_block_free_bitmap_extension() {
+    let mut device = MockDevice::new(1000);
+    let (mut boot0, boot1) = create_boot_block();
+    write_u32_be(&mut boot0, 8, 2);
+    device.set_block(0, &boot0);
+    device.set_block(1, &boot1);
+
+    let mut root = create_root_block(b"BigDisk");
+    for i in 0..25 {
+        write_u32_be(&mut root, 0x13C + i * 4, 10 + i as u32);
+    }
+    write_u32_be(&mut root, 0x1A0, 3);
+    set_checksum(&mut root, 20);
+    device.set_block(2, &root);
+
+    let mut ext = [0u8; 512];
+    write_u32_be(&mut ext, 0, 40);
+    write_u32_be(&mut ext, 4, 41);
+    device.set_block(3, &ext);
+
+    for page in 10..35 {
+        device.set_block(page, &create_bitmap_block(&[]));
+    }
+    device.set_block(40, &create_bitmap_block(&[]));
+    device.set_block(41, &create_bitmap_block(&[5]));
+
+    let reader = AffsReader::with_size(&device, 110000).unwrap();
+    // Page 26 (the second extension slot) covers blocks from 2 + 26 * 4064
+    let first_in_page = 2 + 26 * 4064;
+    assert!(reader.is_block_free(first_in_page).unwrap());
+    assert!(!reader.is_block_free(first_in_page + 5).unwrap());
+}
+
+#[test]
+fn test_is_block_free_invalid_bitmap() {
+    let mut device = create_test_disk();
+    let mut root = create_root_block(b"TestDisk");
+    write_i32_be(&mut root, 0x138, 0);
+    set_checksum(&mut root, 20);
+    device.set_block(880, &root);
+
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(matches!(
+        reader.is_block_free(900),
+        Err(AffsError::InvalidState)
+    ));
+}
+
+// ============================================================================
+// Hard links
+// ============================================================================
+
+/// Create a hard link block pointing to `real_entry`.
+fn create_hard_link(name: &[u8], real_entry: u32, parent: u32, sec_type: i32) -> [u8; 512] {
+    let mut buf = [0u8; 512];
+
+    // Block type = T_HEADER (2)
+    write_i32_be(&mut buf, 0, 2);
+
+    // Name
+    let name_len = name.len().min(30);
+    buf[0x1B0] = name_len as u8;
+    buf[0x1B1..0x1B1 + name_len].copy_from_slice(&name[..name_len]);
+
+    // Real entry
+    write_u32_be(&mut buf, 0x1D4, real_entry);
+
+    // Parent
+    write_u32_be(&mut buf, 0x1F4, parent);
+
+    // Secondary type (ST_LFILE = -4, ST_LDIR = 4)
+    write_i32_be(&mut buf, 0x1FC, sec_type);
+
+    // Set checksum
+    set_checksum(&mut buf, 20);
+
+    buf
+}
+
+/// Create a disk with `testfile` at 882 and a hard link `hardlink` at 884.
+fn create_hard_link_disk() -> MockDevice {
+    let mut device = create_test_disk();
+
+    let mut root = create_root_block(b"TestDisk");
+    write_u32_be(&mut root, 24 + hash_name(b"testfile", false) * 4, 882);
+    write_u32_be(&mut root, 24 + hash_name(b"hardlink", false) * 4, 884);
+    set_checksum(&mut root, 20);
+    device.set_block(880, &root);
+
+    device.set_block(884, &create_hard_link(b"hardlink", 882, 880, -4));
+    device
+}
+
+#[test]
+fn test_read_dir_through_dir_hard_link() {
+    let mut device = create_test_disk();
+    device.set_block(
+        900,
+        &create_dir_header(b"realdir", 880, &[(hash_name(b"inner", false), 901)]),
+    );
+    device.set_block(901, &create_file_header(b"inner", 0, 900, 0, &[]));
+    device.set_block(902, &create_hard_link(b"dirlink", 900, 880, 4));
+    // A link to the link, and a link to the root directory
+    device.set_block(903, &create_hard_link(b"linklink", 902, 880, 4));
+    device.set_block(904, &create_hard_link(b"rootlink", 880, 880, 4));
+    device.set_block(905, &create_hard_link(b"broken", 0, 880, 4));
+
+    let reader = AffsReader::new(&device).unwrap();
+    let names = |block| -> Vec<Vec<u8>> {
+        reader
+            .read_dir(block)
+            .unwrap()
+            .map(|e| e.unwrap().name().to_vec())
+            .collect()
+    };
+    assert_eq!(names(902), [b"inner".to_vec()]);
+    assert_eq!(names(903), [b"inner".to_vec()]);
+    assert_eq!(names(904), names(880));
+    assert_eq!(reader.find_entry(902, b"inner").unwrap().block, 901);
+    assert_eq!(reader.count_entries(902).unwrap(), 1);
+    assert!(matches!(
+        reader.read_dir(905),
+        Err(AffsError::BlockOutOfRange)
+    ));
+}
+
+#[test]
+fn test_resolve_link() {
+    let device = create_hard_link_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    let link = reader.find_entry(880, b"hardlink").unwrap();
+    assert_eq!(link.entry_type, EntryType::HardLinkFile);
+
+    let real = reader.resolve_link(&link).unwrap();
+    assert_eq!(real.entry_type, EntryType::File);
+    assert_eq!(real.block, 882);
+    assert_eq!(real.name(), b"testfile");
+    assert_eq!(real.size, 100);
+
+    // Non-links resolve to themselves
+    let file = reader.find_entry(880, b"testfile").unwrap();
+    let same = reader.resolve_link(&file).unwrap();
+    assert_eq!(same.block, 882);
+}
+
+#[test]
+fn test_hard_link_target() {
+    let mut device = create_hard_link_disk();
+    device.set_block(900, &create_dir_header(b"realdir", 880, &[]));
+    device.set_block(
+        901,
+        &create_dir_header(b"links", 880, &[(hash_name(b"dirlink", false), 902)]),
+    );
+    device.set_block(902, &create_hard_link(b"dirlink", 900, 901, 4));
+    let reader = AffsReader::new(&device).unwrap();
+
+    let link = reader.find_entry(880, b"hardlink").unwrap();
+    assert!(link.is_hardlink());
+    assert!(link.is_file());
+    assert_eq!(link.link_target(), Some(882));
+
+    let dir_link = reader.find_entry(901, b"dirlink").unwrap();
+    assert!(dir_link.is_hardlink());
+    assert!(dir_link.is_dir());
+    assert_eq!(dir_link.link_target(), Some(900));
+
+    let file = reader.find_entry(880, b"testfile").unwrap();
+    assert!(!file.is_hardlink());
+    assert_eq!(file.link_target(), None);
+}
+
+#[test]
+fn test_resolve_link_chain() {
+    let mut device = create_hard_link_disk();
+
+    // Second link pointing at the first link
+    let mut root = create_root_block(b"TestDisk");
+    write_u32_be(&mut root, 24 + hash_name(b"testfile", false) * 4, 882);
+    write_u32_be(&mut root, 24 + hash_name(b"hardlink", false) * 4, 884);
+    write_u32_be(&mut root, 24 + hash_name(b"link2", false) * 4, 885);
+    set_checksum(&mut root, 20);
+    device.set_block(880, &root);
+    device.set_block(885, &create_hard_link(b"link2", 884, 880, -4));
+
+    let reader = AffsReader::new(&device).unwrap();
+    let link = reader.find_entry(880, b"link2").unwrap();
+    let real = reader.resolve_link(&link).unwrap();
+    assert_eq!(real.block, 882);
+    assert_eq!(real.entry_type, EntryType::File);
+}
+
+#[test]
+fn test_read_file_follows_hard_link() {
+    let device = create_hard_link_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    let mut expected = [0u8; 100];
+    reader
+        .read_file(882)
+        .unwrap()
+        .read_all(&mut expected)
+        .unwrap();
+
+    let mut file = reader.read_file(884).unwrap();
+    assert_eq!(file.size(), 100);
+    assert_eq!(file.header_block(), 882);
+    let mut out = [0u8; 100];
+    assert_eq!(file.read_all(&mut out).unwrap(), 100);
+    assert_eq!(out, expected);
+
+    // A self-referencing link is cut off
+    let mut device = create_hard_link_disk();
+    device.set_block(884, &create_hard_link(b"hardlink", 884, 880, -4));
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(matches!(
+        reader.read_file(884),
+        Err(AffsError::InvalidState)
+    ));
+}
+
+#[test]
+fn test_resolve_link_cycle() {
+    let mut device = create_hard_link_disk();
+    // Link pointing at itself
+    device.set_block(884, &create_hard_link(b"hardlink", 884, 880, -4));
+
+    let reader = AffsReader::new(&device).unwrap();
+    let link = reader.find_entry(880, b"hardlink").unwrap();
+    assert!(matches!(
+        reader.resolve_link(&link),
+        Err(AffsError::InvalidState)
+    ));
+}
+
+// ============================================================================
+// Symlink resolution
+// ============================================================================
+
+/// Create a disk with `subdir/inner` and symlinks in the root and subdir.
+///
+/// - `link` -> `subdir` (relative)
+/// - `abslink` -> `:subdir/inner` (volume-relative)
+/// - `loop` -> `loop`
+/// - `subdir/sibling` -> `inner` (relative to `subdir`)
+fn create_symlink_disk() -> MockDevice {
+    let mut device = MockDevice::new(1760);
+    let (boot0, boot1) = create_boot_block();
+    device.set_block(0, &boot0);
+    device.set_block(1, &boot1);
+
+    let mut root = create_root_block(b"LinkDisk");
+    write_u32_be(&mut root, 24 + hash_name(b"subdir", false) * 4, 882);
+    write_u32_be(&mut root, 24 + hash_name(b"link", false) * 4, 886);
+    write_u32_be(&mut root, 24 + hash_name(b"abslink", false) * 4, 887);
+    write_u32_be(&mut root, 24 + hash_name(b"loop", false) * 4, 888);
+    set_checksum(&mut root, 20);
+    device.set_block(880, &root);
+
+    let subdir = create_dir_header(
+        b"subdir",
+        880,
+        &[
+            (hash_name(b"inner", false), 884),
+            (hash_name(b"sibling", false), 889),
+        ],
+    );
+    device.set_block(882, &subdir);
+
+    device.set_block(884, &create_file_header(b"inner", 5, 882, 885, &[885]));
+    device.set_block(885, &[0xAB; 512]);
+
+    device.set_block(886, &create_softlink(b"link", b"subdir\0", 880));
+    device.set_block(887, &create_softlink(b"abslink", b":subdir/inner\0", 880));
+    device.set_block(888, &create_softlink(b"loop", b"loop\0", 880));
+    device.set_block(889, &create_softlink(b"sibling", b"inner\0", 882));
+
+    device
+}
+
+#[test]
+fn test_find_path_resolving_through_dir_link() {
+    let device = create_symlink_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    // Plain find_path stops at the symlink
+    assert!(reader.find_path(b"link/inner").is_err());
+
+    let entry = reader.find_path_resolving(b"link/inner", 8).unwrap();
+    assert_eq!(entry.name(), b"inner");
+    assert_eq!(entry.block, 884);
+}
+
+#[test]
+fn test_find_path_resolving_absolute_and_relative() {
+    let device = create_symlink_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    let entry = reader.find_path_resolving(b"abslink", 8).unwrap();
+    assert_eq!(entry.block, 884);
+
+    let entry = reader.find_path_resolving(b"subdir/sibling", 8).unwrap();
+    assert_eq!(entry.block, 884);
+
+    let entry = reader.find_path_resolving(b"link", 8).unwrap();
+    assert!(entry.is_dir());
+    assert_eq!(entry.block, 882);
+}
+
+#[test]
+fn test_find_path_resolving_loop() {
+    let device = create_symlink_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    let result = reader.find_path_resolving(b"loop", 8);
+    assert!(matches!(result, Err(AffsError::SymlinkLoop)));
+
+    // Not enough hops allowed
+    let result = reader.find_path_resolving(b"link/inner", 0);
+    assert!(matches!(result, Err(AffsError::SymlinkLoop)));
+
+    assert_eq!(
+        format!("{}", AffsError::SymlinkLoop),
+        "too many levels of symlinks"
+    );
+}
+
+// ============================================================================
+// Variable block size file reading
+// ============================================================================
+
+/// Calculate and set the normal checksum for a block of any size.
+fn set_checksum_var(buf: &mut [u8], checksum_offset: usize) {
+    write_u32_be(buf, checksum_offset, 0);
+    let sum = buf.chunks_exact(4).fold(0u32, |acc, c| {
+        acc.wrapping_add(u32::from_be_bytes([c[0], c[1], c[2], c[3]]))
+    });
+    write_u32_be(buf, checksum_offset, sum.wrapping_neg());
+}
+
+/// Write a filesystem block of `data.len()` bytes as consecutive sectors.
+fn set_var_block(device: &mut MockDevice, block: u32, data: &[u8]) {
+    let sectors = data.len() / 512;
+    for (i, chunk) in data.chunks_exact(512).enumerate() {
+        device.set_block(block * sectors as u32 + i as u32, chunk.try_into().unwrap());
+    }
+}
+
+/// Create an FFS volume with 2048-byte blocks holding one 5000-byte file.
+///
+/// The file header lists two data blocks and an extension block lists the third.
+fn create_var_file_disk() -> (MockDevice, Vec<u8>) {
+    const BS: usize = 2048;
+    let ht_size = BS / 4 - 56;
+    let mut device = MockDevice::new(64 * 4);
+
+    let mut boot = [0u8; 512];
+    boot[0..4].copy_from_slice(b"DOS\x01");
+    write_u32_be(&mut boot, 8, 10);
+    device.set_block(0, &boot);
+
+    let mut root = vec![0u8; BS];
+    write_i32_be(&mut root, 0, T_HEADER);
+    write_u32_be(&mut root, 12, ht_size as u32);
+    let name_off = BS - 200 + 108;
+    root[name_off] = 3;
+    root[name_off + 1..name_off + 4].copy_from_slice(b"Var");
+    write_i32_be(&mut root, BS - 4, ST_ROOT);
+    set_checksum_var(&mut root, 20);
+    set_var_block(&mut device, 10, &root);
+
+    let content: Vec<u8> = (0..5000u32).map(|i| (i * 7 % 251) as u8).collect();
+
+    let mut header = vec![0u8; BS];
+    write_i32_be(&mut header, 0, T_HEADER);
+    write_u32_be(&mut header, 4, 11);
+    write_u32_be(&mut header, 8, 2);
+    write_u32_be(&mut header, 16, 12);
+    write_u32_be(&mut header, 24 + (ht_size - 1) * 4, 12);
+    write_u32_be(&mut header, 24 + (ht_size - 2) * 4, 13);
+    write_u32_be(&mut header, BS - 200 + 12, content.len() as u32);
+    header[name_off] = 3;
+    header[name_off + 1..name_off + 4].copy_from_slice(b"big");
+    write_u32_be(&mut header, BS - 12, 10);
+    write_u32_be(&mut header, BS - 8, 15);
+    write_i32_be(&mut header, BS - 4, ST_FILE);
+    set_checksum_var(&mut header, 20);
+    set_var_block(&mut device, 11, &header);
+
+    let mut ext = vec![0u8; BS];
+    write_i32_be(&mut ext, 0, T_LIST);
+    write_u32_be(&mut ext, 4, 15);
+    write_u32_be(&mut ext, 8, 1);
+    write_u32_be(&mut ext, 24 + (ht_size - 1) * 4, 14);
+    write_u32_be(&mut ext, BS - 12, 11);
+    write_i32_be(&mut ext, BS - 4, ST_FILE);
+    set_checksum_var(&mut ext, 20);
+    set_var_block(&mut device, 15, &ext);
+
+    for (i, chunk) in content.chunks(BS).enumerate() {
+        let mut data = vec![0u8; BS];
+        data[..chunk.len()].copy_from_slice(chunk);
+        set_var_block(&mut device, 12 + i as u32, &data);
+    }
+
+    (device, content)
+}
+
+/// Create an FFS volume with `BS`-byte blocks holding a subdirectory.
+///
+/// The root lists "sub" (block 11) in its last hash slot. The subdirectory
+/// holds "a" (12) and "b" (13) chained from its last slot, and "c" (14) in
+/// its first slot.
+fn create_var_dir_disk(bs: usize) -> MockDevice {
+    let ht_size = bs / 4 - 56;
+    let mut device = MockDevice::new(64 * bs / 512);
+
+    let mut boot = [0u8; 512];
+    boot[0..4].copy_from_slice(b"DOS\x01");
+    write_u32_be(&mut boot, 8, 10);
+    device.set_block(0, &boot);
+
+    let name_off = bs - 200 + 108;
+    let entry = |key: u32, name: &[u8], sec_type: i32, parent: u32| {
+        let mut buf = vec![0u8; bs];
+        write_i32_be(&mut buf, 0, T_HEADER);
+        write_u32_be(&mut buf, 4, key);
+        buf[name_off] = name.len() as u8;
+        buf[name_off + 1..name_off + 1 + name.len()].copy_from_slice(name);
+        write_u32_be(&mut buf, bs - 12, parent);
+        write_i32_be(&mut buf, bs - 4, sec_type);
+        buf
+    };
+
+    let mut root = entry(10, b"VarDir", ST_ROOT, 0);
+    write_u32_be(&mut root, 12, ht_size as u32);
+    write_u32_be(&mut root, 24 + (ht_size - 1) * 4, 11);
+    set_checksum_var(&mut root, 20);
+    set_var_block(&mut device, 10, &root);
+
+    let mut sub = entry(11, b"sub", ST_DIR, 10);
+    write_u32_be(&mut sub, 24 + (ht_size - 1) * 4, 12);
+    write_u32_be(&mut sub, 24, 14);
+    set_checksum_var(&mut sub, 20);
+    set_var_block(&mut device, 11, &sub);
+
+    let mut a = entry(12, b"a", ST_FILE, 11);
+    write_u32_be(&mut a, bs - 16, 13);
+    set_checksum_var(&mut a, 20);
+    set_var_block(&mut device, 12, &a);
+    for (block, name) in [(13, b"b"), (14, b"c")] {
+        let mut file = entry(block, name, ST_FILE, 11);
+        set_checksum_var(&mut file, 20);
+        set_var_block(&mut device, block, &file);
+    }
+
+    device
+}
+
+/// Names of all entries in a variable block size directory, sorted.
+fn var_dir_names(reader: &AffsReaderVar<'_, MockDevice>, block: u32) -> Vec<String> {
+    let mut names: Vec<String> = reader
+        .read_dir(block)
+        .unwrap()
+        .map(|entry| entry.unwrap().name_str().unwrap().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_var_dircache_only_flags() {
+    // DOS\5: FFS with directory cache, INTL bit clear
+    let mut device = create_var_dir_disk(1024);
+    device.get_block_mut(0)[3] = 5;
+    let reader = AffsReaderVar::new(&device, 128).unwrap();
+    assert!(reader.fs_flags().dircache);
+    assert!(!reader.is_intl());
+    assert!(reader.uses_intl_hashing());
+
+    device.get_block_mut(0)[3] = 1;
+    let reader = AffsReaderVar::new(&device, 128).unwrap();
+    assert!(!reader.is_intl());
+    assert!(!reader.uses_intl_hashing());
+
+    device.get_block_mut(0)[3] = 3;
+    let reader = AffsReaderVar::new(&device, 128).unwrap();
+    assert!(reader.is_intl());
+    assert!(reader.uses_intl_hashing());
+}
+
+#[test]
+fn test_dircache_intl_lookup() {
+    // DOS\5 hashes with international folding even though INTL is clear
+    let name = b"caf\xe9";
+    let bucket = hash_name(name, true);
+    assert_ne!(bucket, hash_name(name, false));
+
+    let mut device = create_test_disk();
+    device.get_block_mut(0)[3] = 5;
+    device.set_block(890, &create_file_header(name, 0, 880, 0, &[]));
+    let root = device.get_block_mut(880);
+    write_u32_be(root, 24 + bucket * 4, 890);
+    set_checksum(root, 20);
+
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(!reader.is_intl());
+    assert!(reader.uses_intl_hashing());
+    assert_eq!(reader.find_entry(880, b"CAF\xc9").unwrap().block, 890);
+    assert!(reader.verify_hash_placement(880).unwrap());
+
+    device.get_block_mut(0)[3] = 1;
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(!reader.uses_intl_hashing());
+    assert!(!reader.verify_hash_placement(880).unwrap());
+}
+
+#[test]
+fn test_var_subdir_1024() {
+    let device = create_var_dir_disk(1024);
+    let reader = AffsReaderVar::new(&device, 128).unwrap();
+    assert_eq!(reader.block_size(), 1024);
+    assert_eq!(reader.hash_table_size(), 200);
+
+    assert_eq!(var_dir_names(&reader, 10), ["sub"]);
+    assert_eq!(var_dir_names(&reader, 11), ["a", "b", "c"]);
+    let sub = reader
+        .read_dir(11)
+        .unwrap()
+        .find(|e| e.as_ref().unwrap().name() == b"b");
+    assert_eq!(sub.unwrap().unwrap().parent, 11);
+}
+
+#[test]
+fn test_var_subdir_large_tables() {
+    // Tables longer than 256 entries, with entries in the last slot
+    for bs in [2048, 4096, 8192] {
+        let device = create_var_dir_disk(bs);
+        let reader = AffsReaderVar::new(&device, 64 * bs as u64 / 512).unwrap();
+        assert_eq!(reader.block_size(), bs);
+        assert_eq!(var_dir_names(&reader, 10), ["sub"]);
+        assert_eq!(var_dir_names(&reader, 11), ["a", "b", "c"]);
+    }
+}
+
+#[test]
+fn test_var_long_name_layout() {
+    let bs = 1024;
+    let mut device = create_var_dir_disk(bs);
+    device.get_block_mut(0)[3] = 7;
+
+    // Rewrite the files in sub/ with the long filename layout
+    let nac = bs - 200 + 8;
+    for (block, name) in [(12u32, &b"a"[..]), (13, &[b'n'; 40][..]), (14, b"c")] {
+        let mut buf = vec![0u8; bs];
+        for i in 0..bs / 512 {
+            let sector = block as usize * bs / 512 + i;
+            buf[i * 512..(i + 1) * 512].copy_from_slice(&device.blocks[sector]);
+        }
+        buf[bs - 200..bs - 20].fill(0);
+        buf[nac] = name.len() as u8;
+        buf[nac + 1..nac + 1 + name.len()].copy_from_slice(name);
+        write_i32_be(&mut buf, bs - 60, 1234);
+        set_checksum_var(&mut buf, 20);
+        set_var_block(&mut device, block, &buf);
+    }
+
+    let reader = AffsReaderVar::new(&device, 64).unwrap();
+    assert!(reader.supports_long_names());
+    let results: Vec<_> = reader.read_dir(11).unwrap().collect();
+    assert_eq!(results.len(), 3);
+    let mut names = Vec::new();
+    for result in results {
+        match result {
+            Ok(entry) => {
+                assert_eq!(entry.date.days, 1234);
+                names.push(entry.name_str().unwrap().to_string());
+            }
+            Err(e) => assert_eq!(e, AffsError::NameTooLong),
+        }
+    }
+    names.sort();
+    assert_eq!(names, ["a", "c"]);
+}
+
+#[test]
+fn test_var_subdir_512() {
+    // 512-byte volumes walk directories with fixed-size buffers
+    let device = create_var_dir_disk(512);
+    let reader = AffsReaderVar::new(&device, 64).unwrap();
+    assert_eq!(reader.block_size(), 512);
+    assert_eq!(var_dir_names(&reader, 10), ["sub"]);
+    assert_eq!(var_dir_names(&reader, 11), ["a", "b", "c"]);
+    assert!(matches!(
+        reader.read_dir(64),
+        Err(AffsError::BlockOutOfRange)
+    ));
+}
+
+/// Summarize a volume through the shared [`AffsVolume`] trait.
+fn volume_summary<V: AffsVolume>(volume: &V) -> (usize, Vec<String>) {
+    let mut names: Vec<String> = volume
+        .read_root_dir()
+        .unwrap()
+        .map(|entry| entry.unwrap().name_str().unwrap().to_string())
+        .collect();
+    names.sort();
+    (volume.block_size() << volume.log_blocksize(), names)
+}
+
+#[test]
+fn test_affs_volume_generic() {
+    let device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    assert_eq!(reader.block_size(), 512);
+    assert_eq!(reader.log_blocksize(), 0);
+    assert_eq!(AffsVolume::disk_name(&reader), reader.disk_name());
+    assert_eq!(volume_summary(&reader), (512, vec!["testfile".to_string()]));
+
+    let device = create_var_dir_disk(1024);
+    let reader = AffsReaderVar::new(&device, 128).unwrap();
+    assert_eq!(AffsVolume::fs_type(&reader), FsType::Ffs);
+    assert_eq!(AffsVolume::disk_name(&reader), b"VarDir");
+    assert_eq!(volume_summary(&reader), (2048, vec!["sub".to_string()]));
+
+    let sub = AffsVolume::read_dir(&reader, 11)
+        .unwrap()
+        .map(|entry| entry.unwrap())
+        .find(|entry| VolumeEntry::name(entry) == b"c")
+        .unwrap();
+    assert!(VolumeEntry::is_file(&sub));
+    assert_eq!(VolumeEntry::parent(&sub), 11);
+}
+
+#[test]
+fn test_var_block_to_sector() {
+    let device = create_var_dir_disk(8192);
+    let reader = AffsReaderVar::new(&device, 64 * 16).unwrap();
+    assert_eq!(reader.sectors_per_block(), 16);
+    assert_eq!(reader.block_to_sector(0), 0);
+    assert_eq!(reader.block_to_sector(2), 32);
+
+    // The root block's first sector holds its block type
+    let mut sector = [0u8; 512];
+    device
+        .read_sector(reader.block_to_sector(reader.root_block()), &mut sector)
+        .unwrap();
+    assert_eq!(
+        i32::from_be_bytes(sector[..4].try_into().unwrap()),
+        T_HEADER
+    );
+
+    let device = create_var_dir_disk(512);
+    let reader = AffsReaderVar::new(&device, 64).unwrap();
+    assert_eq!(reader.sectors_per_block(), 1);
+    assert_eq!(reader.block_to_sector(2), 2);
+}
+
+#[test]
+fn test_var_dir_table_size_mismatch() {
+    let mut device = create_var_dir_disk(1024);
+    let mut sub = vec![0u8; 1024];
+    for i in 0..2 {
+        sub[i * 512..(i + 1) * 512].copy_from_slice(&device.blocks[22 + i]);
+    }
+    write_u32_be(&mut sub, 12, 72);
+    set_checksum_var(&mut sub, 20);
+    set_var_block(&mut device, 11, &sub);
+
+    let reader = AffsReaderVar::new(&device, 128).unwrap();
+    assert!(matches!(reader.read_dir(11), Err(AffsError::InvalidState)));
+    assert!(matches!(reader.read_dir(12), Err(AffsError::NotADirectory)));
+    assert!(matches!(
+        reader.read_dir(64),
+        Err(AffsError::BlockOutOfRange)
+    ));
+}
+
+#[test]
+fn test_var_dir_chain_out_of_range() {
+    let mut device = create_var_dir_disk(1024);
+    let mut a = vec![0u8; 1024];
+    for i in 0..2 {
+        a[i * 512..(i + 1) * 512].copy_from_slice(&device.blocks[24 + i]);
+    }
+    write_u32_be(&mut a, 1024 - 16, 5000);
+    set_checksum_var(&mut a, 20);
+    set_var_block(&mut device, 12, &a);
+
+    let reader = AffsReaderVar::new(&device, 128).unwrap();
+    let results: Vec<_> = reader.read_dir(11).unwrap().collect();
+    assert!(
+        results
+            .iter()
+            .any(|r| matches!(r, Err(AffsError::BlockOutOfRange)))
+    );
+    // The bad chain ends; other slots are still listed
+    let names: Vec<_> = results
+        .iter()
+        .filter_map(|r| r.as_ref().ok())
+        .map(|e| e.name().to_vec())
+        .collect();
+    assert!(names.contains(&b"a".to_vec()));
+    assert!(names.contains(&b"c".to_vec()));
+    assert_eq!(results.len(), 3);
+}
+
+#[test]
+fn test_var_file_reader_2048() {
+    let (device, content) = create_var_file_disk();
+    let reader = AffsReaderVar::new(&device, 64 * 4).unwrap();
+    assert_eq!(reader.block_size(), 2048);
+
+    let mut file = reader.read_file(11).unwrap();
+    assert_eq!(file.size(), 5000);
+    assert_eq!(file.remaining(), 5000);
+
+    let mut buf = vec![0u8; 5000];
+    assert_eq!(file.read_all(&mut buf).unwrap(), 5000);
+    assert_eq!(buf, content);
+    assert_eq!(file.remaining(), 0);
+    assert_eq!(file.read(&mut buf).unwrap(), 0);
+
+    // Small reads straddling block boundaries after reset
+    file.reset().unwrap();
+    let mut out = Vec::new();
+    let mut chunk = [0u8; 700];
+    loop {
+        let n = file.read(&mut chunk).unwrap();
+        if n == 0 {
+            break;
+        }
+        out.extend_from_slice(&chunk[..n]);
+    }
+    assert_eq!(out, content);
+}
+
+#[test]
+fn test_var_file_reader_not_a_file() {
+    let (device, _) = create_var_file_disk();
+    let reader = AffsReaderVar::new(&device, 64 * 4).unwrap();
+
+    assert!(matches!(reader.read_file(10), Err(AffsError::NotAFile)));
+
+    let mut file = reader.read_file(11).unwrap();
+    let mut small = [0u8; 10];
+    assert!(matches!(
+        file.read_all(&mut small),
+        Err(AffsError::BufferTooSmall)
+    ));
+}
+
+// ============================================================================
+// Tree walking
+// ============================================================================
+
+#[test]
+fn test_walk() {
+    let device = create_symlink_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    let entries: Vec<_> = reader
+        .walk(reader.root_block())
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(entries.len(), 6);
+
+    // Children of subdir directly follow it, one level deeper
+    let pos = entries
+        .iter()
+        .position(|(_, e)| e.name() == b"subdir")
+        .unwrap();
+    assert_eq!(entries[pos].0, 1);
+    let mut children: Vec<_> = entries[pos + 1..pos + 3]
+        .iter()
+        .map(|(depth, e)| (*depth, e.name().to_vec()))
+        .collect();
+    children.sort();
+    assert_eq!(
+        children,
+        vec![(2, b"inner".to_vec()), (2, b"sibling".to_vec())]
+    );
+
+    // Symlinks are yielded but not followed
+    let depth1 = entries.iter().filter(|(depth, _)| *depth == 1).count();
+    assert_eq!(depth1, 4);
+
+    // Walking from a subdirectory
+    let sub: Vec<_> = reader.walk(882).map(|r| r.unwrap()).collect();
+    assert_eq!(sub.len(), 2);
+    assert!(sub.iter().all(|(depth, _)| *depth == 1));
+}
+
+#[test]
+fn test_walk_depth_limit() {
+    let device = create_symlink_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    let results: Vec<_> = WalkIter::<_, 1>::new(&reader, reader.root_block()).collect();
+    assert_eq!(results.len(), 5);
+    assert!(
+        results
+            .iter()
+            .any(|r| matches!(r, Err(AffsError::InvalidState)))
+    );
+    assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 4);
+}
+
+#[test]
+fn test_walk_directory_cycle() {
+    let mut device = create_symlink_disk();
+    // Make subdir contain itself
+    let subdir = create_dir_header(b"subdir", 880, &[(hash_name(b"subdir", false), 882)]);
+    device.set_block(882, &subdir);
+
+    let reader = AffsReader::new(&device).unwrap();
+    let results: Vec<_> = reader.walk(reader.root_block()).collect();
+    assert!(
+        results
+            .iter()
+            .any(|r| matches!(r, Err(AffsError::InvalidState)))
+    );
+}
+
+#[test]
+fn test_walk_not_a_directory() {
+    let device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    let mut walk = reader.walk(882);
+    assert!(matches!(walk.next(), Some(Err(AffsError::NotADirectory))));
+    assert!(walk.next().is_none());
+}
+
+// ============================================================================
+// Latin-1 names
+// ============================================================================
+
+#[test]
+fn test_name_and_comment_utf8() {
+    let mut device = MockDevice::new(1760);
+    let (boot0, boot1) = create_boot_block();
+    device.set_block(0, &boot0);
+    device.set_block(1, &boot1);
+
+    let name = b"caf\xE9";
+    let mut root = create_root_block(b"Latin1Disk");
+    write_u32_be(&mut root, 24 + hash_name(name, false) * 4, 882);
+    set_checksum(&mut root, 20);
+    device.set_block(880, &root);
+
+    let mut file = create_file_header(name, 0, 880, 0, &[]);
+    let comment = b"r\xE9sum\xE9 \xA9";
+    file[0x148] = comment.len() as u8;
+    file[0x149..0x149 + comment.len()].copy_from_slice(comment);
+    set_checksum(&mut file, 20);
+    device.set_block(882, &file);
+
+    let reader = AffsReader::new(&device).unwrap();
+    let entry = reader.find_entry(880, name).unwrap();
+    assert_eq!(entry.name_str(), None);
+
+    let mut out = [0u8; max_utf8_len(MAX_NAME_LEN)];
+    let len = entry.name_utf8(&mut out);
+    assert_eq!(&out[..len], &[b'c', b'a', b'f', 0xC3, 0xA9]);
+    assert_eq!(core::str::from_utf8(&out[..len]), Ok("café"));
+
+    let mut out = [0u8; max_utf8_len(MAX_COMMENT_LEN)];
+    let len = entry.comment_utf8(&mut out);
+    assert_eq!(core::str::from_utf8(&out[..len]), Ok("résumé ©"));
+
+    // Truncated at a character boundary
+    let mut small = [0u8; 4];
+    let len = entry.name_utf8(&mut small);
+    assert_eq!(&small[..len], b"caf");
+}
+
+#[test]
+fn test_access_display() {
+    assert_eq!(format!("{}", Access::new(0)), "--------");
+    assert_eq!(format!("{}", Access::new(0xFF)), "hsparwed");
+    assert_eq!(
+        format!("{}", Access::new(ACC_SCRIPT | ACC_READ | ACC_DELETE)),
+        "-s--r--d"
+    );
+    // Bits above the protection flags are ignored
+    assert_eq!(format!("{}", Access::new(0xFF00)), "--------");
+}
+
+// ============================================================================
+// Entry paths
+// ============================================================================
+
+#[test]
+fn test_entry_path() {
+    let device = create_symlink_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let mut out = [0u8; 256];
+
+    let inner = reader.find_path(b"subdir/inner").unwrap();
+    let len = reader.entry_path(&inner, &mut out).unwrap();
+    assert_eq!(&out[..len], b"/subdir/inner");
+
+    let subdir = reader.find_path(b"subdir").unwrap();
+    let len = reader.entry_path(&subdir, &mut out).unwrap();
+    assert_eq!(&out[..len], b"/subdir");
+
+    let len = reader.entry_path(&reader.root_entry(), &mut out).unwrap();
+    assert_eq!(&out[..len], b"/");
+
+    // Exactly fits
+    let mut exact = [0u8; 13];
+    assert_eq!(reader.entry_path(&inner, &mut exact).unwrap(), 13);
+    assert_eq!(&exact, b"/subdir/inner");
+
+    let mut small = [0u8; 12];
+    assert!(matches!(
+        reader.entry_path(&inner, &mut small),
+        Err(AffsError::BufferTooSmall)
+    ));
+}
+
+#[test]
+fn test_entry_path_parent_cycle() {
+    let mut device = create_symlink_disk();
+    // subdir claims to be its own parent
+    let subdir = create_dir_header(b"subdir", 882, &[(hash_name(b"inner", false), 884)]);
+    device.set_block(882, &subdir);
+
+    let reader = AffsReader::new(&device).unwrap();
+    let inner = reader.find_path(b"subdir/inner").unwrap();
+
+    let mut out = [0u8; 4096];
+    assert!(matches!(
+        reader.entry_path(&inner, &mut out),
+        Err(AffsError::InvalidState)
+    ));
+}
+
+// ============================================================================
+// Serde
+// ============================================================================
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_dir_entry_roundtrip() {
+    let device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let entry = reader.find_path(b"testfile").unwrap();
+
+    let json = serde_json::to_string(&entry).unwrap();
+    assert!(json.contains(r#""name":"testfile""#));
+    assert!(json.contains(r#""entry_type":"File""#));
+    assert!(json.contains(r#""comment":"""#));
+
+    let back: DirEntry = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.name(), entry.name());
+    assert_eq!(back.block, entry.block);
+    assert_eq!(back.size, entry.size);
+    assert_eq!(back.date, entry.date);
+    assert_eq!(back.comment(), b"");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_latin1_name_as_bytes() {
+    let json = r#"{"name":[99,97,102,233],"entry_type":"Dir","block":1,"parent":880,
+        "size":0,"access":0,"date":{"days":1,"mins":2,"ticks":3},"real_entry":0,"comment":""}"#;
+    let entry: DirEntry = serde_json::from_str(json).unwrap();
+    assert_eq!(entry.name(), b"caf\xE9");
+    assert_eq!(entry.date, AmigaDate::new(1, 2, 3));
+
+    let json = serde_json::to_string(&entry).unwrap();
+    assert!(json.contains(r#""name":[99,97,102,233]"#));
+
+    // Names longer than MAX_NAME_LEN are rejected
+    let long = json.replace(r#"[99,97,102,233]"#, &format!("\"{}\"", "x".repeat(31)));
+    assert!(serde_json::from_str::<DirEntry>(&long).is_err());
+}
+
+#[test]
+fn test_file_read_at() {
+    let device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+
+    let mut head = [0u8; 30];
+    file.read(&mut head).unwrap();
+    assert_eq!(file.position(), 30);
+
+    // Non-overlapping ranges, out of order
+    for (offset, len) in [(60u32, 10usize), (10, 10), (80, 20)] {
+        let mut out = vec![0u8; len];
+        let n = file.read_at(offset, &mut out).unwrap();
+        assert_eq!(n, len);
+        for (i, &b) in out.iter().enumerate() {
+            assert_eq!(b, (offset as usize + i + 1) as u8);
+        }
+        assert_eq!(file.position(), 30);
+    }
+
+    // At EOF nothing is read; past EOF is an error
+    let mut out = [0u8; 4];
+    assert_eq!(file.read_at(100, &mut out).unwrap(), 0);
+    assert!(matches!(
+        file.read_at(101, &mut out),
+        Err(AffsError::EndOfFile)
+    ));
+    assert_eq!(file.position(), 30);
+
+    // Streaming continues where it left off
+    let mut next = [0u8; 1];
+    file.read(&mut next).unwrap();
+    assert_eq!(next[0], 31);
+}
+
+// ============================================================================
+// Chrono
+// ============================================================================
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_chrono_roundtrip() {
+    use chrono::{DateTime, Datelike, Timelike, Utc};
+
+    // 1997-02-18 12:34:03.40
+    let date = AmigaDate::new(6988, 754, 170);
+    let dt: DateTime<Utc> = date.into();
+    assert_eq!((dt.year(), dt.month(), dt.day()), (1997, 2, 18));
+    assert_eq!((dt.hour(), dt.minute(), dt.second()), (12, 34, 3));
+    assert_eq!(dt.timestamp_subsec_millis(), 400);
+    assert_eq!(dt.timestamp(), date.to_unix_timestamp());
+
+    assert_eq!(AmigaDate::try_from(dt), Ok(date));
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_chrono_epoch_and_range() {
+    use chrono::{DateTime, Utc};
+
+    let epoch: DateTime<Utc> = AmigaDate::new(0, 0, 0).into();
+    assert_eq!(epoch.timestamp(), 252_460_800);
+
+    let before = DateTime::<Utc>::from_timestamp(0, 0).unwrap();
+    assert_eq!(AmigaDate::try_from(before), Err(DateOutOfRange));
+
+    let huge: DateTime<Utc> = AmigaDate::new(i32::MAX, 0, 0).into();
+    assert_eq!(huge, DateTime::<Utc>::MAX_UTC);
+}
+
+// ============================================================================
+// Time
+// ============================================================================
+
+#[cfg(feature = "time")]
+#[test]
+fn test_time_offset_date_time() {
+    use time::OffsetDateTime;
+
+    // 1997-02-18 12:34:03.40 UTC
+    let date = AmigaDate::new(6988, 754, 170);
+    let dt: OffsetDateTime = date.into();
+    assert_eq!(dt.unix_timestamp(), 856_269_243);
+    assert_eq!(dt.millisecond(), 400);
+    assert_eq!(dt.offset(), time::UtcOffset::UTC);
+    assert_eq!(
+        (dt.year(), dt.month(), dt.day()),
+        (1997, time::Month::February, 18)
+    );
+
+    assert_eq!(AmigaDate::try_from(dt), Ok(date));
+    assert_eq!(
+        AmigaDate::try_from(OffsetDateTime::UNIX_EPOCH),
+        Err(DateOutOfRange)
+    );
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn test_time_primitive_date_time() {
+    use time::PrimitiveDateTime;
+
+    let dt = AmigaDate::new(6988, 754, 150).to_date_time();
+    let primitive = PrimitiveDateTime::try_from(dt).unwrap();
+    assert_eq!(
+        (primitive.hour(), primitive.minute(), primitive.second()),
+        (12, 34, 3)
+    );
+    assert_eq!(DateTime::from(primitive), dt);
+
+    let bad = DateTime { month: 13, ..dt };
+    assert!(PrimitiveDateTime::try_from(bad).is_err());
+}
+
+// ============================================================================
+// Consistency check
+// ============================================================================
+
+#[test]
+fn test_check_clean_volume() {
+    let device = create_symlink_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    let report = reader.check().unwrap();
+    assert_eq!(report.files, 1);
+    assert_eq!(report.dirs, 1);
+    assert_eq!(report.symlinks, 4);
+    assert_eq!(report.errors, 0);
+    assert!(report.is_ok());
+}
+
+#[test]
+fn test_check_reports_all_errors() {
+    let mut device = create_symlink_disk();
+
+    // Wrong parent pointer
+    device.set_block(884, &create_file_header(b"inner", 5, 880, 885, &[885]));
+    // Size claims more data blocks than listed
+    let mut root = device.blocks[880];
+    write_u32_be(&mut root, 24 + hash_name(b"big", false) * 4, 890);
+    set_checksum(&mut root, 20);
+    device.set_block(880, &root);
+    device.set_block(890, &create_file_header(b"big", 2000, 880, 891, &[891]));
+    // Corrupt checksum on a symlink
+    device.get_block_mut(888)[100] ^= 0xFF;
+
+    let reader = AffsReader::new(&device).unwrap();
+    let report = reader.check().unwrap();
+
+    assert_eq!(report.errors, 3);
+    assert!(!report.is_ok());
+    assert_eq!(report.files, 2);
+    assert_eq!(report.symlinks, 3);
+
+    #[cfg(feature = "alloc")]
+    {
+        let mut issues = report.issues.clone();
+        issues.sort_by_key(|i| i.block);
+        assert_eq!(
+            issues,
+            vec![
+                CheckIssue {
+                    block: 880,
+                    error: AffsError::ChecksumMismatch
+                },
+                CheckIssue {
+                    block: 884,
+                    error: AffsError::InvalidState
+                },
+                CheckIssue {
+                    block: 890,
+                    error: AffsError::InvalidDataSequence
+                },
+            ]
+        );
+    }
+}
+
+// ============================================================================
+// Statistics
+// ============================================================================
+
+/// Create a disk with `subdir/inner` (5 bytes) and nothing else.
+fn create_subdir_disk() -> MockDevice {
+    let mut device = MockDevice::new(1760);
+    let (boot0, boot1) = create_boot_block();
+    device.set_block(0, &boot0);
+    device.set_block(1, &boot1);
+
+    let mut root = create_root_block(b"SubdirDisk");
+    write_u32_be(&mut root, 24 + hash_name(b"subdir", false) * 4, 882);
+    set_checksum(&mut root, 20);
+    device.set_block(880, &root);
+
+    let subdir = create_dir_header(b"subdir", 880, &[(hash_name(b"inner", false), 884)]);
+    device.set_block(882, &subdir);
+
+    device.set_block(884, &create_file_header(b"inner", 5, 882, 885, &[885]));
+    device.set_block(885, &[0xAB; 512]);
+
+    device
+}
+
+#[test]
+fn test_stats() {
+    let device = create_subdir_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    let stats = reader.stats().unwrap();
+    assert_eq!(stats.total_dirs, 1);
+    assert_eq!(stats.total_files, 1);
+    assert_eq!(stats.total_symlinks, 0);
+    assert_eq!(stats.total_bytes, 5);
+    assert_eq!(stats.largest_file, Some((884, 5)));
+    assert_eq!(stats.max_depth, 2);
+
+    let device = create_symlink_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let stats = reader.stats().unwrap();
+    assert_eq!(stats.total_symlinks, 4);
+}
+
+#[test]
+fn test_entries_flat() {
+    let device = create_subdir_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    let entries: Vec<DirEntry> = reader.entries().collect::<Result<_, _>>().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].name(), b"subdir");
+    assert!(entries[0].is_dir());
+    assert_eq!(entries[1].name(), b"inner");
+    assert_eq!(entries[1].parent, 882);
+}
+
+#[test]
+fn test_walk_paths() {
+    let device = create_symlink_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    let paths: Vec<(AffsPath<64>, DirEntry)> =
+        reader.walk_paths().collect::<Result<_, _>>().unwrap();
+    assert_eq!(paths.len(), 6);
+    let inner = paths
+        .iter()
+        .find(|(_, e)| e.name() == b"inner")
+        .map(|(path, _)| path.as_bytes())
+        .unwrap();
+    assert_eq!(inner, b"/subdir/inner");
+    for (path, entry) in &paths {
+        assert_eq!(*path, reader.entry_path_buf::<64>(entry).unwrap());
+    }
+
+    // "subdir" fits in 8 bytes but its children don't
+    let results: Vec<_> = reader.walk_paths::<8>().collect();
+    assert_eq!(results.len(), 6);
+    let too_long = results
+        .iter()
+        .filter(|r| matches!(r, Err(AffsError::BufferTooSmall)))
+        .count();
+    assert_eq!(too_long, 2);
+    assert!(
+        results
+            .iter()
+            .any(|r| matches!(r, Ok((path, _)) if path.as_bytes() == b"/subdir"))
+    );
+}
+
+#[test]
+fn test_dir_iter_strict() {
+    let mut device = create_test_disk();
+    // Chain "testfile" -> unknown secondary type -> "after" -> empty name -> "last"
+    let mut file = create_file_header(b"testfile", 100, 880, 883, &[883]);
+    write_u32_be(&mut file, 0x1F0, 890);
+    set_checksum(&mut file, 20);
+    device.set_block(882, &file);
+    let mut bogus = create_file_header(b"bogus", 0, 880, 0, &[]);
+    write_i32_be(&mut bogus, 508, 99);
+    write_u32_be(&mut bogus, 0x1F0, 891);
+    set_checksum(&mut bogus, 20);
+    device.set_block(890, &bogus);
+    let mut after = create_file_header(b"after", 0, 880, 0, &[]);
+    write_u32_be(&mut after, 0x1F0, 892);
+    set_checksum(&mut after, 20);
+    device.set_block(891, &after);
+    let mut unnamed = create_file_header(b"", 0, 880, 0, &[]);
+    write_u32_be(&mut unnamed, 0x1F0, 893);
+    set_checksum(&mut unnamed, 20);
+    device.set_block(892, &unnamed);
+    device.set_block(893, &create_file_header(b"last", 0, 880, 0, &[]));
+
+    let reader = AffsReader::new(&device).unwrap();
+
+    // The default iterator skips the malformed entries but not the rest of the chain
+    let names: Vec<Vec<u8>> = reader
+        .read_root_dir()
+        .map(|e| e.unwrap().name().to_vec())
+        .collect();
+    assert_eq!(
+        names,
+        vec![b"testfile".to_vec(), b"after".to_vec(), b"last".to_vec()]
+    );
+
+    let results: Vec<_> = reader.read_root_dir().strict().collect();
+    assert_eq!(results.len(), 5);
+    assert_eq!(results[0].as_ref().unwrap().name(), b"testfile");
+    assert!(matches!(results[1], Err(AffsError::InvalidSecType)));
+    assert_eq!(results[2].as_ref().unwrap().name(), b"after");
+    assert!(matches!(results[3], Err(AffsError::EmptyName)));
+    assert_eq!(results[4].as_ref().unwrap().name(), b"last");
+    assert_eq!(reader.count_entries(880).unwrap(), 3);
+    assert_eq!(reader.read_dir(880).unwrap().count_entries().unwrap(), 3);
+
+    // A bad checksum still ends the chain, since its pointer can't be trusted
+    device.get_block_mut(890)[100] ^= 1;
+    let reader = AffsReader::new(&device).unwrap();
+    let results: Vec<_> = reader.read_root_dir().collect();
+    assert_eq!(results.len(), 2);
+    assert!(matches!(results[1], Err(AffsError::ChecksumMismatch)));
+}
+
+/// Test disk whose bucket for "target" (block 891) starts with a nameless
+/// block (890) chained to it.
+fn create_rejected_chain_disk() -> MockDevice {
+    let mut device = create_test_disk();
+    let bucket = hash_name(b"target", false);
+    let mut unnamed = create_file_header(b"", 0, 880, 0, &[]);
+    write_u32_be(&mut unnamed, 0x1F0, 891);
+    set_checksum(&mut unnamed, 20);
+    device.set_block(890, &unnamed);
+    device.set_block(891, &create_file_header(b"target", 0, 880, 0, &[]));
+    let root = device.get_block_mut(880);
+    write_u32_be(root, 24 + bucket * 4, 890);
+    set_checksum(root, 20);
+    device
+}
+
+#[test]
+fn test_lookup_skips_rejected_entries() {
+    let device = create_rejected_chain_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    assert_eq!(reader.find_entry(880, b"target").unwrap().block, 891);
+    assert_eq!(reader.find_entry_exact(880, b"target").unwrap().block, 891);
+    assert_eq!(reader.last_error_block(), None);
+    assert!(reader.verify_hash_placement(880).unwrap());
+    assert_eq!(reader.count_entries(880).unwrap(), 2);
+    assert_eq!(reader.read_root_dir().count(), 2);
+}
+
+#[test]
+fn test_verify_hash_placement() {
+    let mut device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(reader.verify_hash_placement(880).unwrap());
+
+    let entry = reader.find_entry(880, b"testfile").unwrap();
+    let bucket = entry.hash_bucket(false);
+    assert_eq!(bucket, hash_name(b"testfile", false));
+
+    // Move the entry to the next bucket
+    let mut root = create_root_block(b"TestDisk");
+    write_u32_be(&mut root, 24 + ((bucket + 1) % 72) * 4, 882);
+    set_checksum(&mut root, 20);
+    device.set_block(880, &root);
+
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(!reader.verify_hash_placement(880).unwrap());
+    assert!(reader.find_entry(880, b"testfile").is_err());
+}
+
+#[test]
+fn test_dir_iter_size_hint_and_count() {
+    let device = create_names_disk(&[b"a", b"b", b"c", b"d"]);
+    let reader = AffsReader::new(&device).unwrap();
+
+    let mut iter = reader.read_root_dir();
+    let (lower, upper) = iter.size_hint();
+    assert_eq!(lower, 0);
+    assert!(upper.is_none_or(|u| u >= 4));
+    assert_eq!(reader.read_root_dir().count_entries().unwrap(), 4);
+
+    while iter.next().is_some() {}
+    assert_eq!(iter.size_hint(), (0, Some(0)));
+
+    let mut iter = reader.read_root_dir();
+    iter.next();
+    assert_eq!(iter.count_entries().unwrap(), 3);
+}
+
+#[test]
+fn test_find_path_strict_parent() {
+    let mut device = create_subdir_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    assert_eq!(reader.find_path_strict(b"subdir/inner").unwrap().block, 884);
+
+    // The subdirectory claims a parent other than the root
+    let subdir = create_dir_header(b"subdir", 900, &[(hash_name(b"inner", false), 884)]);
+    device.set_block(882, &subdir);
+
+    let reader = AffsReader::new(&device).unwrap();
+    assert_eq!(reader.find_path(b"subdir/inner").unwrap().block, 884);
+    assert!(matches!(
+        reader.find_path_strict(b"subdir/inner"),
+        Err(AffsError::InvalidState)
+    ));
+}
+
+#[test]
+fn test_last_error_block() {
+    let mut device = create_subdir_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    assert_eq!(reader.last_error_block(), None);
+    assert!(reader.find_path(b"subdir/inner").is_ok());
+    assert_eq!(reader.last_error_block(), None);
+
+    // Corrupt the inner file header
+    device.get_block_mut(884)[100] ^= 0xFF;
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(matches!(
+        reader.find_path(b"subdir/inner"),
+        Err(AffsError::ChecksumMismatch)
+    ));
+    assert_eq!(reader.last_error_block(), Some(884));
+
+    // Then the subdirectory header
+    device.get_block_mut(882)[100] ^= 0xFF;
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(matches!(
+        reader.read_dir(882),
+        Err(AffsError::ChecksumMismatch)
+    ));
+    assert_eq!(reader.last_error_block(), Some(882));
+    assert!(matches!(
+        reader.read_file(884),
+        Err(AffsError::ChecksumMismatch)
+    ));
+    assert_eq!(reader.last_error_block(), Some(884));
+}
+
+#[test]
+fn test_entry_path_buf() {
+    let device = create_subdir_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let inner = reader.find_path(b"subdir/inner").unwrap();
+
+    let path = reader.entry_path_buf::<64>(&inner).unwrap();
+    assert_eq!(path.as_bytes(), b"/subdir/inner");
+    assert_eq!(path.as_str(), Some("/subdir/inner"));
+    assert_eq!(path.to_string(), "/subdir/inner");
+
+    let root = reader.entry_path_buf::<4>(&reader.root_entry()).unwrap();
+    assert_eq!(root.as_bytes(), b"/");
+
+    assert!(matches!(
+        reader.entry_path_buf::<8>(&inner),
+        Err(AffsError::BufferTooSmall)
+    ));
+}
+
+#[test]
+fn test_affs_path_push_component() {
+    let mut path = AffsPath::<12>::new();
+    assert!(path.is_empty());
+    path.push_component(b"dir").unwrap();
+    path.push_component(b"f\xe9").unwrap();
+    assert_eq!(path.as_bytes(), b"/dir/f\xe9");
+    assert_eq!(path.as_str(), None);
+    assert_eq!(path.to_string(), "/dir/f\u{e9}");
+    assert_eq!(path.len(), 7);
+
+    assert!(matches!(
+        path.push_component(b"longer"),
+        Err(AffsError::BufferTooSmall)
+    ));
+    assert_eq!(path.len(), 7);
+    path.push_component(b"abc").unwrap();
+    assert_eq!(path.len(), 11);
+}
+
+#[test]
+fn test_affs_path_push_onto_root() {
+    let mut path = AffsPath::<8>::new();
+    path.push_component(b"").unwrap();
+    assert_eq!(path.as_bytes(), b"/");
+    path.push_component(b"dir").unwrap();
+    assert_eq!(path.as_bytes(), b"/dir");
+
+    // Only the name has to fit after the root's `/`
+    let mut path = AffsPath::<8>::new();
+    path.push_component(b"").unwrap();
+    path.push_component(b"1234567").unwrap();
+    assert_eq!(path.as_bytes(), b"/1234567");
+    assert!(matches!(
+        path.push_component(b"x"),
+        Err(AffsError::BufferTooSmall)
+    ));
+}
+
+#[test]
+fn test_stats_directory_cycle() {
+    let mut device = create_subdir_disk();
+    let subdir = create_dir_header(b"subdir", 880, &[(hash_name(b"subdir", false), 882)]);
+    device.set_block(882, &subdir);
+
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(matches!(reader.stats(), Err(AffsError::InvalidState)));
+}
+
+// ============================================================================
+// Entry counting
+// ============================================================================
+
+#[test]
+fn test_count_entries() {
+    let device = create_symlink_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    assert_eq!(reader.count_entries(reader.root_block()).unwrap(), 4);
+    assert_eq!(reader.count_entries(882).unwrap(), 2);
+    assert_eq!(
+        reader.count_entries(882).unwrap(),
+        reader.read_dir(882).unwrap().count()
+    );
+    assert!(matches!(
+        reader.count_entries(884),
+        Err(AffsError::NotADirectory)
+    ));
+}
+
+#[test]
+fn test_count_entries_chain_cycle() {
+    let mut device = create_test_disk();
+    // testfile's hash chain points back to itself
+    let mut file = create_file_header(b"testfile", 100, 880, 883, &[883]);
+    write_u32_be(&mut file, 0x1F0, 882);
+    set_checksum(&mut file, 20);
+    device.set_block(882, &file);
+
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(matches!(
+        reader.count_entries(reader.root_block()),
+        Err(AffsError::InvalidState)
+    ));
+}
+
+// ============================================================================
+// Directory cache
+// ============================================================================
+
+/// Append a packed directory cache record to `buf` at `*pos`.
+#[allow(clippy::too_many_arguments)]
+fn push_dircache_record(
+    buf: &mut [u8; 512],
+    pos: &mut usize,
+    header: u32,
+    size: u32,
+    protect: u32,
+    date: (u16, u16, u16),
+    sec_type: i8,
+    name: &[u8],
+    comment: &[u8],
+) {
+    let p = *pos;
+    write_u32_be(buf, p, header);
+    write_u32_be(buf, p + 4, size);
+    write_u32_be(buf, p + 8, protect);
+    buf[p + 16..p + 18].copy_from_slice(&date.0.to_be_bytes());
+    buf[p + 18..p + 20].copy_from_slice(&date.1.to_be_bytes());
+    buf[p + 20..p + 22].copy_from_slice(&date.2.to_be_bytes());
+    buf[p + 22] = sec_type as u8;
+    buf[p + 23] = name.len() as u8;
+    buf[p + 24..p + 24 + name.len()].copy_from_slice(name);
+    buf[p + 24 + name.len()] = comment.len() as u8;
+    let c = p + 25 + name.len();
+    buf[c..c + comment.len()].copy_from_slice(comment);
+    *pos = (c + comment.len() + 1) & !1;
+}
+
+/// Create a DIRCACHE volume whose root has a cache listing `testfile` and `cached`.
+///
+/// `cached` exists only in the cache, proving entries come from the records.
+fn create_dircache_disk() -> MockDevice {
+    let mut device = create_test_disk();
+    device.get_block_mut(0)[3] = DOSFS_FFS | DOSFS_DIRCACHE;
+
+    let mut root = device.blocks[880];
+    write_u32_be(&mut root, 0x1F8, 900);
+    set_checksum(&mut root, 20);
+    device.set_block(880, &root);
+
+    let mut cache = [0u8; 512];
+    write_i32_be(&mut cache, 0, T_DIRC);
+    write_u32_be(&mut cache, 4, 900);
+    write_u32_be(&mut cache, 8, 880);
+    write_u32_be(&mut cache, 12, 2);
+    let mut pos = 24;
+    push_dircache_record(
+        &mut cache,
+        &mut pos,
+        882,
+        100,
+        ACC_ARCHIVE,
+        (6988, 754, 150),
+        ST_FILE as i8,
+        b"testfile",
+        b"odd",
+    );
+    push_dircache_record(
+        &mut cache,
+        &mut pos,
+        886,
+        0,
+        0,
+        (1, 2, 3),
+        ST_DIR as i8,
+        b"cached",
+        b"",
+    );
+    set_checksum(&mut cache, 20);
+    device.set_block(900, &cache);
+
+    device
+}
+
+#[test]
+fn test_dircache_block_parse() {
+    let device = create_dircache_disk();
+    let cache = DirCacheBlock::parse(&device.blocks[900]).unwrap();
+    assert_eq!(cache.parent, 880);
+    assert_eq!(cache.records_nb, 2);
+    assert_eq!(cache.next_dirc, 0);
+
+    let records: Vec<_> = cache.iter().collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].name, b"testfile");
+    assert_eq!(records[0].comment, b"odd");
+    assert_eq!(records[0].entry_type(), Some(EntryType::File));
+    assert_eq!(records[0].date, AmigaDate::new(6988, 754, 150));
+    assert_eq!(records[1].name, b"cached");
+    assert_eq!(records[1].entry_type(), Some(EntryType::Dir));
+
+    // Corrupted checksum
+    let mut bad = device.blocks[900];
+    bad[30] ^= 1;
+    assert!(matches!(
+        DirCacheBlock::parse(&bad),
+        Err(AffsError::ChecksumMismatch)
+    ));
+}
+
+#[test]
+fn test_read_dir_cached() {
+    let device = create_dircache_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(reader.fs_flags().dircache);
+
+    let iter = reader.read_dir_cached(reader.root_block()).unwrap();
+    assert!(matches!(iter, CachedDirIter::Cache(_)));
+    let entries: Vec<_> = iter.map(|e| e.unwrap()).collect();
+    assert_eq!(entries.len(), 2);
+
+    assert_eq!(entries[0].name(), b"testfile");
+    assert_eq!(entries[0].block, 882);
+    assert_eq!(entries[0].parent, 880);
+    assert_eq!(entries[0].size, 100);
+    assert!(entries[0].access.is_archived());
+    assert_eq!(entries[0].comment(), b"odd");
+    assert!(entries[1].is_dir());
+    assert_eq!(entries[1].name(), b"cached");
+}
+
+#[test]
+fn test_read_dir_cached_fallback() {
+    // No DIRCACHE flag: hash table is used even if a cache pointer exists
+    let mut device = create_dircache_disk();
+    device.get_block_mut(0)[3] = DOSFS_FFS;
+    let reader = AffsReader::new(&device).unwrap();
+    let iter = reader.read_dir_cached(reader.root_block()).unwrap();
+    assert!(matches!(iter, CachedDirIter::HashTable(_)));
+    assert_eq!(iter.count(), 1);
+
+    // DIRCACHE volume but directory without a cache
+    let mut device = create_test_disk();
+    device.get_block_mut(0)[3] = DOSFS_FFS | DOSFS_DIRCACHE;
+    let reader = AffsReader::new(&device).unwrap();
+    let entries: Vec<_> = reader
+        .read_dir_cached(reader.root_block())
+        .unwrap()
+        .map(|e| e.unwrap())
+        .collect();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].name(), b"testfile");
+
+    assert!(matches!(
+        reader.read_dir_cached(882),
+        Err(AffsError::NotADirectory)
+    ));
+}
+
+#[test]
+fn test_block_type_enum() {
+    for (raw, kind) in [
+        (T_HEADER, BlockType::Header),
+        (T_DATA, BlockType::Data),
+        (T_LIST, BlockType::List),
+        (T_DIRC, BlockType::DirCache),
+    ] {
+        assert_eq!(BlockType::from_i32(raw), Some(kind));
+        assert_eq!(kind.as_i32(), raw);
+    }
+    for raw in [0, 1, 3, 17, 32, -2] {
+        assert_eq!(BlockType::from_i32(raw), None);
+    }
+
+    let device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let entry = reader.read_entry(882).unwrap();
+    assert_eq!(entry.block_type_enum(), Some(BlockType::Header));
+
+    let ext = FileExtBlock::parse(&create_file_ext_block(882, &[900], 0)).unwrap();
+    assert_eq!(ext.block_type_enum(), Some(BlockType::List));
+
+    let data = OfsDataBlock::parse(&create_ofs_data_block(882, 1, b"x", 0)).unwrap();
+    assert_eq!(data.block_type_enum(), Some(BlockType::Data));
+}
+
+#[test]
+fn test_file_peek() {
+    for device in [create_test_disk(), create_ofs_test_disk()] {
+        let reader = AffsReader::new(&device).unwrap();
+        let mut file = reader.read_file(882).unwrap();
+
+        let mut magic = [0u8; 4];
+        assert_eq!(file.peek(&mut magic).unwrap(), 4);
+        assert_eq!(file.position(), 0);
+
+        let mut all = vec![0u8; file.size() as usize];
+        file.read_all(&mut all).unwrap();
+        assert_eq!(&all[..4], &magic);
+
+        // Peeking at EOF reads nothing
+        assert_eq!(file.peek(&mut magic).unwrap(), 0);
+        assert!(file.is_eof());
+    }
+}
+
+// ============================================================================
+// Skipping
+// ============================================================================
+
+/// Byte at `pos` of the file created by `create_large_ffs_disk`.
+fn large_file_byte(pos: usize) -> u8 {
+    ((pos / 512) as u8).wrapping_mul(31).wrapping_add(pos as u8)
+}
+
+/// Create an FFS disk with a 73-block (plus 100 bytes) file using one extension block.
+fn create_large_ffs_disk() -> (MockDevice, u32) {
+    let size = 73 * 512 + 100;
+    let mut device = MockDevice::new(1760);
+    let (boot0, boot1) = create_boot_block();
+    device.set_block(0, &boot0);
+    device.set_block(1, &boot1);
+
+    let mut root = create_root_block(b"LargeDisk");
+    write_u32_be(&mut root, 24 + hash_name(b"large", false) * 4, 882);
+    set_checksum(&mut root, 20);
+    device.set_block(880, &root);
+
+    let data_blocks: Vec<u32> = (890..962).collect();
+    let mut file = create_file_header(b"large", size, 880, 890, &data_blocks);
+    write_u32_be(&mut file, 0x1F8, 883);
+    set_checksum(&mut file, 20);
+    device.set_block(882, &file);
+    device.set_block(883, &create_file_ext_block(882, &[962, 963], 0));
+
+    for (i, block) in (890..=963).enumerate() {
+        let mut data = [0u8; 512];
+        for (j, byte) in data.iter_mut().enumerate() {
+            *byte = large_file_byte(i * 512 + j);
+        }
+        device.set_block(block, &data);
+    }
+
+    (device, size)
+}
+
+/// FFS disk with a 1024-byte file whose header lists a third, bogus data block.
+fn create_overallocated_ffs_disk() -> MockDevice {
+    let mut device = create_test_disk();
+    // Third pointer is past the end of the device, so reading it fails
+    let header = create_file_header(b"testfile", 1024, 880, 883, &[883, 884, 5000]);
+    device.set_block(882, &header);
+    device.set_block(883, &[0x11u8; 512]);
+    device.set_block(884, &[0x22u8; 512]);
+    device
+}
+
+#[test]
+fn test_ffs_read_stops_at_byte_size() {
+    let device = create_overallocated_ffs_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    // Large buffer
+    let mut file = reader.read_file(882).unwrap();
+    let mut buf = [0u8; 2048];
+    assert_eq!(file.read_all(&mut buf).unwrap(), 1024);
+    assert!(buf[..512].iter().all(|&b| b == 0x11));
+    assert!(buf[512..1024].iter().all(|&b| b == 0x22));
+    assert_eq!(file.read(&mut buf).unwrap(), 0);
+    assert!(file.is_eof());
+
+    // Small chunks
+    let mut file = reader.read_file(882).unwrap();
+    let mut total = 0;
+    let mut chunk = [0u8; 100];
+    loop {
+        let n = file.read(&mut chunk).unwrap();
+        if n == 0 {
+            break;
+        }
+        total += n;
+    }
+    assert_eq!(total, 1024);
+
+    // Block at a time
+    let mut file = reader.read_file(882).unwrap();
+    let mut blocks = 0;
+    while let Some(data) = file.next_block().unwrap() {
+        assert_eq!(data.len(), 512);
+        blocks += 1;
+    }
+    assert_eq!(blocks, 2);
+
+    // Seeking to the end does not load the extra block
+    let mut file = reader.read_file(882).unwrap();
+    file.seek(1024).unwrap();
+    assert_eq!(file.read(&mut chunk).unwrap(), 0);
+}
+
+#[test]
+fn test_file_info() {
+    let device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let info = reader.file_info(882).unwrap();
+    assert_eq!(info.size, 100);
+    assert_eq!(info.blocks_in_header, 1);
+    assert_eq!(info.first_data, 883);
+    assert_eq!(info.extension, 0);
+    assert!(!info.is_ofs);
+
+    let device = create_ofs_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(reader.file_info(882).unwrap().is_ofs);
+    assert!(matches!(reader.file_info(880), Err(AffsError::NotAFile)));
+}
+
+#[test]
+fn test_verify_fs_type() {
+    let device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(reader.verify_fs_type(882).unwrap());
+
+    let device = create_ofs_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(reader.verify_fs_type(882).unwrap());
+
+    // FFS boot block, but the data block is an OFS data block
+    let mut device = create_test_disk();
+    device.set_block(883, &create_ofs_data_block(882, 1, &[7; 100], 0));
+    let reader = AffsReader::new(&device).unwrap();
+    assert_eq!(reader.fs_type(), FsType::Ffs);
+    assert!(!reader.verify_fs_type(882).unwrap());
+}
+
+#[test]
+fn test_file_layout_extension() {
+    let (device, _) = create_large_ffs_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    let layout = reader.file_layout(882).unwrap();
+    assert_eq!(layout.data_block_count, 74);
+    assert_eq!(layout.extension_block_count, 1);
+    assert_eq!(layout.contiguous_runs, 1);
+}
+
+#[test]
+fn test_file_layout_fragmented() {
+    let mut device = create_test_disk();
+    let file = create_file_header(b"testfile", 1200, 880, 883, &[883, 884, 900]);
+    device.set_block(882, &file);
+
+    let reader = AffsReader::new(&device).unwrap();
+    let layout = reader.file_layout(882).unwrap();
+    assert_eq!(layout.data_block_count, 3);
+    assert_eq!(layout.extension_block_count, 0);
+    assert_eq!(layout.contiguous_runs, 2);
+}
+
+#[test]
+fn test_file_layout_not_a_file() {
+    let device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(matches!(reader.file_layout(880), Err(AffsError::NotAFile)));
+}
+
+#[test]
+fn test_file_skip_ffs() {
+    let (device, size) = create_large_ffs_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+    let mut byte = [0u8; 1];
+
+    // Within the first block before anything is loaded
+    assert_eq!(file.skip(10).unwrap(), 10);
+    file.read(&mut byte).unwrap();
+    assert_eq!(byte[0], large_file_byte(10));
+
+    // Within the loaded block
+    assert_eq!(file.skip(100).unwrap(), 100);
+    file.read(&mut byte).unwrap();
+    assert_eq!(byte[0], large_file_byte(111));
+
+    // Across several blocks to a block boundary
+    assert_eq!(file.skip(5 * 512 - 112).unwrap(), 5 * 512 - 112);
+    assert_eq!(file.position(), 5 * 512);
+    file.read(&mut byte).unwrap();
+    assert_eq!(byte[0], large_file_byte(5 * 512));
+
+    // Into the extension block
+    let target = 72 * 512 + 7;
+    assert_eq!(
+        file.skip(target - file.position()).unwrap(),
+        target - 5 * 512 - 1
+    );
+    file.read(&mut byte).unwrap();
+    assert_eq!(byte[0], large_file_byte(target as usize));
+
+    // Past EOF
+    let remaining = file.remaining();
+    assert_eq!(file.skip(u32::MAX).unwrap(), remaining);
+    assert!(file.is_eof());
+    assert_eq!(file.position(), size);
+    assert_eq!(file.skip(1).unwrap(), 0);
+
+    // Seek uses skip and lands correctly after a reset
+    file.seek(73 * 512 + 50).unwrap();
+    file.read(&mut byte).unwrap();
+    assert_eq!(byte[0], large_file_byte(73 * 512 + 50));
+}
+
+#[test]
+fn test_file_skip_ofs() {
+    let device = create_ofs_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+
+    assert_eq!(file.skip(20).unwrap(), 20);
+    let mut byte = [0u8; 1];
+    file.read(&mut byte).unwrap();
+    assert_eq!(byte[0], 30);
+
+    assert_eq!(file.skip(100).unwrap(), 29);
+    assert!(file.is_eof());
+    assert_eq!(file.skip(5).unwrap(), 0);
+}
+
+// ============================================================================
+// Truncated files
+// ============================================================================
+
+#[test]
+fn test_truncated_file_ffs() {
+    let mut device = create_test_disk();
+    // Header claims 1000 bytes but lists a single data block
+    device.set_block(
+        882,
+        &create_file_header(b"testfile", 1000, 880, 883, &[883]),
+    );
+
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+
+    let mut buf = [0u8; 512];
+    assert_eq!(file.read(&mut buf).unwrap(), 512);
+    assert!(matches!(file.read(&mut buf), Err(AffsError::TruncatedFile)));
+
+    file.reset();
+    assert!(matches!(file.skip(600), Err(AffsError::TruncatedFile)));
+
+    let mut all = vec![0u8; 1000];
+    file.reset();
+    assert!(matches!(
+        file.read_all(&mut all),
+        Err(AffsError::TruncatedFile)
+    ));
+
+    assert_eq!(
+        format!("{}", AffsError::TruncatedFile),
+        "file data truncated"
+    );
+}
+
+#[test]
+fn test_truncated_file_ofs() {
+    let mut device = create_ofs_test_disk();
+    // Header claims more than the single 50-byte data block holds
+    device.set_block(882, &create_file_header(b"ofsfile", 600, 880, 883, &[883]));
+
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+
+    let mut buf = [0u8; 600];
+    assert_eq!(file.read(&mut buf).unwrap(), 50);
+    assert!(matches!(file.read(&mut buf), Err(AffsError::TruncatedFile)));
+}
+
+#[test]
+fn test_clean_eof_is_not_truncated() {
+    let device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+
+    let mut buf = [0u8; 200];
+    assert_eq!(file.read(&mut buf).unwrap(), 100);
+    assert_eq!(file.read(&mut buf).unwrap(), 0);
+}
+
+// ============================================================================
+// Bundled devices
+// ============================================================================
+
+/// Flatten a mock device into a raw ADF image.
+fn to_image(device: &MockDevice) -> Vec<u8> {
+    device.blocks.iter().flatten().copied().collect()
+}
+
+#[test]
+fn test_slice_device_reads_disk() {
+    let image = to_image(&create_test_disk());
+    let device = SliceDevice::new(&image);
+    assert_eq!(device.num_blocks(), 1760);
+
+    let reader = AffsReader::new(&device).unwrap();
+    let entry = reader.find_entry(reader.root_block(), b"testfile").unwrap();
+    let mut file = reader.read_file(entry.block).unwrap();
+    let mut buf = [0u8; 100];
+    assert_eq!(file.read(&mut buf).unwrap(), 100);
+
+    let mut block = [0u8; 512];
+    assert!(device.read_block(1760, &mut block).is_err());
+}
+
+#[test]
+fn test_offset_device_reads_disk() {
+    let mut image = vec![0xEE; 100 * 512];
+    image.extend(to_image(&create_test_disk()));
+    let backing = SliceDevice::new(&image);
+    let device = OffsetDevice::new(&backing, 100);
+    assert_eq!(device.offset(), 100);
+
+    let reader = AffsReader::new(&device).unwrap();
+    assert_eq!(reader.disk_name(), b"TestDisk");
+    let entry = reader.find_entry(reader.root_block(), b"testfile").unwrap();
+    let mut buf = [0u8; 100];
+    assert_eq!(
+        reader
+            .read_file(entry.block)
+            .unwrap()
+            .read_all(&mut buf)
+            .unwrap(),
+        100
+    );
+    assert_eq!(buf[0], 1);
+
+    // Past the end of the backing store, and offset overflow
+    let mut block = [0u8; 512];
+    assert!(device.read_block(1760, &mut block).is_err());
+    let far = OffsetDevice::new(&backing, u32::MAX);
+    assert!(far.read_block(1, &mut block).is_err());
+}
+
+/// Create an `RDSK` block whose partition list starts at `partition_list`.
+fn create_rdsk_block(partition_list: u32) -> [u8; 512] {
+    let mut buf = [0u8; 512];
+    buf[0..4].copy_from_slice(b"RDSK");
+    write_u32_be(&mut buf, 4, 64); // SummedLongs
+    write_u32_be(&mut buf, 0x10, 512); // BlockBytes
+    write_u32_be(&mut buf, 0x18, 0xFFFF_FFFF); // BadBlockList
+    write_u32_be(&mut buf, 0x1C, partition_list);
+    write_u32_be(&mut buf, 0x20, 0xFFFF_FFFF); // FileSysHeaderList
+    write_u32_be(&mut buf, 0x40, 64); // Cylinders
+    write_u32_be(&mut buf, 0x44, 16); // Sectors
+    write_u32_be(&mut buf, 0x48, 1); // Heads
+    set_checksum_var(&mut buf[..256], 8);
+    buf
+}
+
+/// Create a `PART` block for a one-head, 16 sectors per track partition.
+fn create_part_block(
+    name: &[u8],
+    next: u32,
+    cyls: (u32, u32),
+    sectors_per_block: u32,
+    dos_type: &[u8; 4],
+) -> [u8; 512] {
+    let mut buf = [0u8; 512];
+    buf[0..4].copy_from_slice(b"PART");
+    write_u32_be(&mut buf, 4, 64);
+    write_u32_be(&mut buf, 0x10, next);
+    buf[0x24] = name.len() as u8;
+    buf[0x25..0x25 + name.len()].copy_from_slice(name);
+    let env = 0x80;
+    write_u32_be(&mut buf, env, 16); // TableSize
+    write_u32_be(&mut buf, env + 4, 128); // SizeBlock (longs)
+    write_u32_be(&mut buf, env + 12, 1); // Surfaces
+    write_u32_be(&mut buf, env + 16, sectors_per_block);
+    write_u32_be(&mut buf, env + 20, 16); // BlocksPerTrack
+    write_u32_be(&mut buf, env + 24, 2); // Reserved
+    write_u32_be(&mut buf, env + 36, cyls.0);
+    write_u32_be(&mut buf, env + 40, cyls.1);
+    buf[env + 64..env + 68].copy_from_slice(dos_type);
+    set_checksum_var(&mut buf[..256], 8);
+    buf
+}
+
+#[test]
+fn test_parse_rdb_single_partition() {
+    // Cylinder 0 holds the RDB (not at sector 0), cylinders 1-8 a 1024-byte block FFS volume
+    let mut image = vec![0u8; 16 * 512];
+    image[512..1024].copy_from_slice(&create_rdsk_block(2));
+    image[1024..1536].copy_from_slice(&create_part_block(
+        b"DH0",
+        0xFFFF_FFFF,
+        (1, 8),
+        2,
+        b"DOS\x01",
+    ));
+    image.extend(to_image(&create_var_dir_disk(1024)));
+    let device = SliceDevice::new(&image);
+
+    let partitions = parse_rdb(&device).unwrap();
+    assert_eq!(partitions.rdb().partition_list, 2);
+    assert_eq!(partitions.rdb().cylinders, 64);
+    assert_eq!(partitions.len(), 1);
+
+    let part = partitions.get(0).unwrap();
+    assert_eq!(part.block, 2);
+    assert_eq!(part.name(), b"DH0");
+    assert_eq!(part.name_str(), Some("DH0"));
+    assert_eq!(part.dos_type, u32::from_be_bytes(*b"DOS\x01"));
+    assert_eq!(part.start_sector, 16);
+    assert_eq!(part.num_sectors, 128);
+    assert_eq!(part.block_size, 1024);
+
+    let scoped = OffsetDevice::new(&device, part.start_sector as u32);
+    let reader = AffsReaderVar::new(&scoped, part.num_sectors).unwrap();
+    assert_eq!(reader.block_size(), 1024);
+    let names: Vec<_> = reader
+        .read_root_dir()
+        .unwrap()
+        .map(|e| e.unwrap().name().to_vec())
+        .collect();
+    assert_eq!(names, [b"sub".to_vec()]);
+}
+
+#[test]
+fn test_parse_rdb_errors() {
+    let mut image = vec![0u8; 16 * 512];
+    let device = SliceDevice::new(&image);
+    assert!(matches!(
+        parse_rdb(&device),
+        Err(AffsError::InvalidBlockType)
+    ));
+
+    image[..512].copy_from_slice(&create_rdsk_block(1));
+    image[512..1024].copy_from_slice(&create_part_block(b"DH0", 1, (1, 8), 1, b"DOS\x03"));
+    let device = SliceDevice::new(&image);
+    // Partition list that points back at itself
+    assert!(matches!(parse_rdb(&device), Err(AffsError::InvalidState)));
+
+    image[0x50] ^= 1;
+    let device = SliceDevice::new(&image);
+    assert!(matches!(
+        parse_rdb(&device),
+        Err(AffsError::ChecksumMismatch)
+    ));
+
+    // RDB shorter than the search window
+    let short = vec![0u8; 4 * 512];
+    let device = SliceDevice::new(&short);
+    assert!(matches!(parse_rdb(&device), Err(AffsError::BlockReadError)));
+}
+
+#[test]
+fn test_parse_rdb_hostile_geometry() {
+    let env = 0x80;
+    let hostile: [&[(usize, u32)]; 5] = [
+        // Cylinder size overflows
+        &[(4, u32::MAX), (12, u32::MAX), (20, u32::MAX)],
+        // Start sector overflows
+        &[
+            (12, u32::MAX),
+            (20, u32::MAX),
+            (36, u32::MAX),
+            (40, u32::MAX),
+        ],
+        // Last cylinder before the first
+        &[(36, 8), (40, 1)],
+        // No heads
+        &[(12, 0)],
+        // Sectors smaller than 512 bytes
+        &[(4, 64)],
+    ];
+    for fields in hostile {
+        let mut part = create_part_block(b"DH0", 0xFFFF_FFFF, (1, 8), 1, b"DOS\x03");
+        for &(offset, value) in fields {
+            write_u32_be(&mut part, env + offset, value);
+        }
+        set_checksum_var(&mut part[..256], 8);
+
+        let mut image = vec![0u8; 16 * 512];
+        image[..512].copy_from_slice(&create_rdsk_block(1));
+        image[512..1024].copy_from_slice(&part);
+        let device = SliceDevice::new(&image);
+        assert!(matches!(parse_rdb(&device), Err(AffsError::InvalidState)));
+    }
+}
+
+#[test]
+fn test_mount_partition_two_partitions() {
+    // DH0: cylinders 1-16 with 2048-byte blocks, DH1: cylinders 17-24 with 1024-byte blocks
+    let mut image = vec![0u8; 16 * 512];
+    image[..512].copy_from_slice(&create_rdsk_block(1));
+    image[512..1024].copy_from_slice(&create_part_block(b"DH0", 2, (1, 16), 4, b"DOS\x01"));
+    image[1024..1536].copy_from_slice(&create_part_block(
+        b"DH1",
+        0xFFFF_FFFF,
+        (17, 24),
+        2,
+        b"DOS\x01",
+    ));
+    let (file_disk, content) = create_var_file_disk();
+    image.extend(to_image(&file_disk));
+    image.extend(to_image(&create_var_dir_disk(1024)));
+    let device = SliceDevice::new(&image);
+
+    let partitions = parse_rdb(&device).unwrap();
+    assert_eq!(partitions.len(), 2);
+    let dh0 = partitions.get(0).unwrap();
+    let dh1 = partitions.get(1).unwrap();
+    assert_eq!(dh1.name(), b"DH1");
+    assert_eq!(dh1.start_sector, 272);
+
+    let reader = AffsReaderVar::mount_partition(&device, dh1).unwrap();
+    assert_eq!(reader.sector_offset(), 272);
+    assert_eq!(reader.block_size(), 1024);
+    assert_eq!(reader.total_blocks(), 64);
+    let names: Vec<_> = reader
+        .read_root_dir()
+        .unwrap()
+        .map(|e| e.unwrap().name().to_vec())
+        .collect();
+    assert_eq!(names, [b"sub".to_vec()]);
+    let mut sub: Vec<_> = reader
+        .read_dir(11)
+        .unwrap()
+        .map(|e| e.unwrap().name().to_vec())
+        .collect();
+    sub.sort();
+    assert_eq!(sub, [b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]);
+
+    let reader = AffsReaderVar::mount_partition(&device, dh0).unwrap();
+    assert_eq!(reader.disk_name(), b"Var");
+    let mut buf = vec![0u8; 5000];
+    assert_eq!(
+        reader.read_file(11).unwrap().read_all(&mut buf).unwrap(),
+        5000
+    );
+    assert_eq!(buf, content);
+
+    // Only the partition's own block size is probed
+    let mut wrong = *dh1;
+    wrong.block_size = 2048;
+    assert!(matches!(
+        AffsReaderVar::mount_partition(&device, &wrong),
+        Err(AffsError::InvalidDosType)
+    ));
+    wrong.block_size = 3000;
+    assert!(matches!(
+        AffsReaderVar::mount_partition(&device, &wrong),
+        Err(AffsError::InvalidState)
+    ));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_file_device_reads_disk() {
+    let path = std::env::temp_dir().join(format!("affs-read-test-{}.adf", std::process::id()));
+    std::fs::write(&path, to_image(&create_test_disk())).unwrap();
+
+    let device = FileDevice::open(&path).unwrap();
+    assert_eq!(device.num_blocks().unwrap(), 1760);
+
+    let reader = AffsReader::new(&device).unwrap();
+    let names: Vec<_> = reader
+        .read_root_dir()
+        .map(|e| e.unwrap().name().to_vec())
+        .collect();
+    assert!(names.contains(&b"testfile".to_vec()));
+
+    let mut block = [0u8; 512];
+    assert!(device.read_block(1760, &mut block).is_err());
+
+    drop(device);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_io_device_reads_disk() {
+    let cursor = std::io::Cursor::new(to_image(&create_test_disk()));
+    let device = IoDevice::new(cursor);
+
+    let reader = AffsReader::new(&device).unwrap();
+    let entry = reader.find_entry(reader.root_block(), b"testfile").unwrap();
+    let mut file = reader.read_file(entry.block).unwrap();
+    let mut buf = [0u8; 200];
+    assert_eq!(file.read(&mut buf).unwrap(), 100);
+
+    let mut block = [0u8; 512];
+    assert!(device.read_block(1760, &mut block).is_err());
+    assert_eq!(device.into_inner().into_inner().len(), 1760 * 512);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_file_copy_to() {
+    let (device, size) = create_large_ffs_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+
+    let mut out = Vec::new();
+    let written = file.copy_to(&mut out).unwrap();
+    assert_eq!(written, size as u64);
+    assert_eq!(out.len(), written as usize);
+    assert!(
+        out.iter()
+            .enumerate()
+            .all(|(i, &b)| b == large_file_byte(i))
+    );
+    assert!(file.is_eof());
+
+    // Copying again from EOF writes nothing
+    assert_eq!(file.copy_to(&mut out).unwrap(), 0);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_file_copy_to_truncated() {
+    let mut device = create_test_disk();
+    device.set_block(
+        882,
+        &create_file_header(b"testfile", 1000, 880, 883, &[883]),
+    );
+
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+    let mut out = Vec::new();
+    let err = file.copy_to(&mut out).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    assert_eq!(out.len(), 512);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_read_dir_sorted() {
+    let mut device = create_test_disk();
+    let names: [&[u8]; 4] = [b"charlie", b"alpha", b"Beta", b"ALPHA"];
+    let mut table = Vec::new();
+    for (i, name) in names.iter().enumerate() {
+        let block = 901 + i as u32;
+        let mut header = create_file_header(name, 0, 900, 0, &[]);
+        if *name == b"alpha" {
+            // "ALPHA" hashes to the same slot and is chained behind "alpha"
+            write_u32_be(&mut header, 0x1F0, 904);
+            set_checksum(&mut header, 20);
+        }
+        device.set_block(block, &header);
+        if *name != b"ALPHA" {
+            table.push((hash_name(name, false), block));
+        }
+    }
+    device.set_block(900, &create_dir_header(b"sorted", 880, &table));
+
+    let reader = AffsReader::new(&device).unwrap();
+    let sorted: Vec<_> = reader
+        .read_dir_sorted(900)
+        .unwrap()
+        .iter()
+        .map(|e| e.name().to_vec())
+        .collect();
+    assert_eq!(
+        sorted,
+        [
+            b"ALPHA".to_vec(),
+            b"alpha".to_vec(),
+            b"Beta".to_vec(),
+            b"charlie".to_vec()
+        ]
+    );
+
+    assert!(matches!(
+        reader.read_dir_sorted(901),
+        Err(AffsError::NotADirectory)
+    ));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_list_dir() {
+    let device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    let listed = reader.list_dir(880).unwrap();
+    let streamed: Vec<_> = reader.read_dir(880).unwrap().map(|e| e.unwrap()).collect();
+    assert_eq!(listed.len(), streamed.len());
+    for (a, b) in listed.iter().zip(&streamed) {
+        assert_eq!(a.name(), b.name());
+        assert_eq!(a.block, b.block);
+        assert_eq!(a.size, b.size);
+    }
+    assert_eq!(listed[0].name(), b"testfile");
+
+    assert!(matches!(
+        reader.list_dir(882),
+        Err(AffsError::NotADirectory)
+    ));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_dir_entry_name_cow() {
+    use std::borrow::Cow;
+
+    let mut device = create_test_disk();
+    device.set_block(
+        900,
+        &create_dir_header(b"names", 880, &[(hash_name(b"caf\xE9", false), 901)]),
+    );
+    device.set_block(901, &create_file_header(b"caf\xE9", 0, 900, 0, &[]));
+    let reader = AffsReader::new(&device).unwrap();
+
+    let ascii = reader.find_entry(880, b"testfile").unwrap();
+    assert!(matches!(ascii.name_cow(), Cow::Borrowed("testfile")));
+
+    let accented = reader.find_entry(900, b"caf\xE9").unwrap();
+    let name = accented.name_cow();
+    assert!(matches!(name, Cow::Owned(_)));
+    assert_eq!(name, "caf\u{e9}");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_file_read_to_vec() {
+    let (device, size) = create_large_ffs_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+
+    let data = file.read_to_vec().unwrap();
+    assert_eq!(data.len(), size as usize);
+    assert!(
+        data.iter()
+            .enumerate()
+            .all(|(i, &b)| b == large_file_byte(i))
+    );
+    assert!(file.read_to_vec().unwrap().is_empty());
+
+    // Only the remaining bytes are returned
+    file.seek(size - 10).unwrap();
+    assert_eq!(file.read_to_vec().unwrap().len(), 10);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_file_read_to_string() {
+    let mut device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let text = reader.read_file(882).unwrap().read_to_string().unwrap();
+    let expected: Vec<u8> = (1..=100).collect();
+    assert_eq!(text.as_bytes(), &expected[..]);
+
+    device.get_block_mut(883)[50] = 0xFF;
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+    assert_eq!(file.read_to_string(), Err(AffsError::InvalidUtf8));
+}
+
+#[test]
+fn test_find_entry_exact() {
+    let mut device = create_test_disk();
+
+    // "File" and "file" share a hash bucket; "File" is first in the chain
+    let mut root = create_root_block(b"TestDisk");
+    let hash_idx = hash_name(b"file", false);
+    assert_eq!(hash_idx, hash_name(b"File", false));
+    write_u32_be(&mut root, 24 + hash_idx * 4, 884);
+    set_checksum(&mut root, 20);
+    device.set_block(880, &root);
+
+    let mut upper = create_file_header(b"File", 10, 880, 883, &[883]);
+    write_u32_be(&mut upper, 0x1F0, 885);
+    set_checksum(&mut upper, 20);
+    device.set_block(884, &upper);
+    device.set_block(885, &create_file_header(b"file", 20, 880, 883, &[883]));
+
+    let reader = AffsReader::new(&device).unwrap();
+    let root = reader.root_block();
+
+    assert_eq!(reader.find_entry(root, b"file").unwrap().block, 884);
+    assert_eq!(reader.find_entry_exact(root, b"file").unwrap().block, 885);
+    assert_eq!(reader.find_entry_exact(root, b"File").unwrap().block, 884);
+    assert!(matches!(
+        reader.find_entry_exact(root, b"FILE"),
+        Err(AffsError::EntryNotFound)
+    ));
+    assert!(reader.find_entry(root, b"FILE").is_ok());
+}
+
+/// Create a disk whose root holds one empty-data file per name, at blocks 882, 883, ...
+fn create_names_disk(names: &[&[u8]]) -> MockDevice {
+    let mut device = create_test_disk();
+    let mut root = create_root_block(b"NamesDisk");
+    for (i, name) in names.iter().enumerate() {
+        let block = 882 + i as u32;
+        let slot = 24 + hash_name(name, false) * 4;
+        // Prepend to the hash chain
+        let next = u32::from_be_bytes(root[slot..slot + 4].try_into().unwrap());
+        let mut header = create_file_header(name, 0, 880, 0, &[]);
+        write_u32_be(&mut header, 0x1F0, next);
+        set_checksum(&mut header, 20);
+        device.set_block(block, &header);
+        write_u32_be(&mut root, slot, block);
+    }
+    set_checksum(&mut root, 20);
+    device.set_block(880, &root);
+    device
+}
+
+#[test]
+fn test_directory_handle_iterate() {
+    let device = create_names_disk(&[b"alpha", b"beta", b"gamma"]);
+    let reader = AffsReader::new(&device).unwrap();
+    let dir = reader.dir(reader.root_block()).unwrap();
+
+    let mut names = Vec::new();
+    for entry in &dir {
+        names.push(entry.unwrap().name().to_vec());
+    }
+    names.sort();
+    assert_eq!(
+        names,
+        [b"alpha".to_vec(), b"beta".to_vec(), b"gamma".to_vec()]
+    );
+
+    assert_eq!(dir.len().unwrap(), 3);
+    assert!(!dir.is_empty());
+    assert_eq!(dir.clone().into_iter().count(), 3);
+}
+
+#[test]
+fn test_directory_handle_find() {
+    let device = create_names_disk(&[b"alpha", b"beta"]);
+    let reader = AffsReader::new(&device).unwrap();
+    let dir = reader.dir(reader.root_block()).unwrap();
+
+    assert_eq!(dir.find(b"BETA").unwrap().name(), b"beta");
+    assert!(matches!(dir.find(b"delta"), Err(AffsError::EntryNotFound)));
+    // The handle is reusable after a lookup
+    assert_eq!(dir.len().unwrap(), 2);
+}
+
+#[test]
+fn test_directory_handle_errors() {
+    let device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(matches!(reader.dir(882), Err(AffsError::NotADirectory)));
+
+    let mut device = MockDevice::new(1760);
+    let (boot0, boot1) = create_boot_block();
+    device.set_block(0, &boot0);
+    device.set_block(1, &boot1);
+    device.set_block(880, &create_root_block(b"Empty"));
+    let reader = AffsReader::new(&device).unwrap();
+    let dir = reader.dir(880).unwrap();
+    assert!(dir.is_empty());
+    assert_eq!(dir.len().unwrap(), 0);
+}
+
+fn sorted_names<I: Iterator<Item = Result<DirEntry, AffsError>>>(iter: I) -> Vec<Vec<u8>> {
+    let mut names: Vec<_> = iter.map(|e| e.unwrap().name().to_vec()).collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_glob() {
+    let device = create_names_disk(&[b"Disk.info", b"readme.INFO", b"readme", b"cat", b"bat"]);
+    let reader = AffsReader::new(&device).unwrap();
+    let root = reader.root_block();
+
+    assert_eq!(
+        sorted_names(reader.glob(root, b"*.info").unwrap()),
+        vec![b"Disk.info".to_vec(), b"readme.INFO".to_vec()]
+    );
+    assert_eq!(
+        sorted_names(reader.glob(root, b"?at").unwrap()),
+        vec![b"bat".to_vec(), b"cat".to_vec()]
+    );
+    assert_eq!(
+        sorted_names(reader.glob(root, b"README").unwrap()),
+        vec![b"readme".to_vec()]
+    );
+    assert_eq!(reader.glob(root, b"*.txt").unwrap().count(), 0);
+    assert_eq!(reader.glob(root, b"*").unwrap().count(), 5);
+}
+
+#[test]
+fn test_dir_iter_files_and_dirs() {
+    let mut device = create_symlink_disk();
+    // Add a plain file to the root next to the subdir and the links
+    let mut root = device.blocks[880];
+    write_u32_be(&mut root, 24 + hash_name(b"plain", false) * 4, 890);
+    set_checksum(&mut root, 20);
+    device.set_block(880, &root);
+    device.set_block(890, &create_file_header(b"plain", 5, 880, 885, &[885]));
+
+    let reader = AffsReader::new(&device).unwrap();
+    let root = reader.root_block();
+
+    let files = sorted_names(reader.read_dir(root).unwrap().files());
+    assert_eq!(files, vec![b"plain".to_vec()]);
+    let dirs = sorted_names(reader.read_dir(root).unwrap().dirs());
+    assert_eq!(dirs, vec![b"subdir".to_vec()]);
+    assert_eq!(reader.read_dir(root).unwrap().count(), 5);
+
+    // Errors still come through
+    device.set_block(882, &[0u8; 512]);
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(reader.read_dir(root).unwrap().dirs().any(|e| e.is_err()));
+}
+
+#[test]
+fn test_entry_type_display_from_str() {
+    let cases = [
+        (EntryType::Root, "root"),
+        (EntryType::Dir, "dir"),
+        (EntryType::File, "file"),
+        (EntryType::HardLinkFile, "hardlink-file"),
+        (EntryType::HardLinkDir, "hardlink-dir"),
+        (EntryType::SoftLink, "softlink"),
+    ];
+    for (ty, name) in cases {
+        assert_eq!(ty.to_string(), name);
+        assert_eq!(name.parse::<EntryType>(), Ok(ty));
+    }
+    assert_eq!("DIR".parse::<EntryType>(), Ok(EntryType::Dir));
+    assert_eq!("symlink".parse::<EntryType>(), Err(ParseTypeError));
+    assert_eq!("".parse::<EntryType>(), Err(ParseTypeError));
+}
+
+#[test]
+fn test_fs_type_display_from_str() {
+    for (ty, name) in [(FsType::Ofs, "OFS"), (FsType::Ffs, "FFS")] {
+        assert_eq!(ty.to_string(), name);
+        assert_eq!(name.parse::<FsType>(), Ok(ty));
+    }
+    assert_eq!("ffs".parse::<FsType>(), Ok(FsType::Ffs));
+    assert_eq!("SFS".parse::<FsType>(), Err(ParseTypeError));
+    assert_eq!(ParseTypeError.to_string(), "unrecognized type name");
+}
+
+#[test]
+fn test_exists_and_path_is_dir() {
+    let device = create_subdir_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    assert!(reader.exists(b"subdir/inner"));
+    assert!(!reader.path_is_dir(b"subdir/inner").unwrap());
+    assert!(reader.exists(b"subdir"));
+    assert!(reader.path_is_dir(b"subdir").unwrap());
+
+    assert!(!reader.exists(b"missing"));
+    assert!(!reader.path_is_dir(b"missing").unwrap());
+    assert!(!reader.exists(b"nodir/inner"));
+    assert!(!reader.path_is_dir(b"nodir/inner").unwrap());
+
+    for root in [&b""[..], b"/"] {
+        assert!(reader.exists(root));
+        assert!(reader.path_is_dir(root).unwrap());
+    }
+}
+
+#[test]
+fn test_path_is_dir_propagates_read_errors() {
+    let mut device = create_subdir_disk();
+    device.set_block(882, &[0u8; 512]);
+    let reader = AffsReader::new(&device).unwrap();
+
+    assert!(!reader.exists(b"subdir/inner"));
+    assert!(reader.path_is_dir(b"subdir/inner").is_err());
+}
+
+#[test]
+fn test_parent_entry() {
+    let device = create_subdir_disk();
+    let reader = AffsReader::new(&device).unwrap();
+
+    let inner = reader.find_path(b"subdir/inner").unwrap();
+    let subdir = reader.parent_entry(&inner).unwrap();
+    assert_eq!(subdir.block, 882);
+    assert_eq!(subdir.name(), b"subdir");
+    assert!(subdir.is_dir());
+
+    let root = reader.parent_entry(&subdir).unwrap();
+    assert_eq!(root.block, reader.root_block());
+    assert_eq!(root.entry_type, EntryType::Root);
+
+    // The root is its own parent
+    assert_eq!(
+        reader.parent_entry(&root).unwrap().block,
+        reader.root_block()
+    );
+
+    let mut orphan = inner.clone();
+    orphan.parent = 0;
+    assert!(matches!(
+        reader.parent_entry(&orphan),
+        Err(AffsError::BlockOutOfRange)
+    ));
+    orphan.parent = 5000;
+    assert!(matches!(
+        reader.parent_entry(&orphan),
+        Err(AffsError::BlockOutOfRange)
+    ));
+}
+
+#[test]
+fn test_parent_entry_of_root_file() {
+    let device = create_test_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let file = reader.find_path(b"testfile").unwrap();
+    let parent = reader.parent_entry(&file).unwrap();
+    assert_eq!(parent.block, 880);
+    assert_eq!(parent.entry_type, EntryType::Root);
+}
+
+#[test]
+fn test_metadata() {
+    let mut device = create_subdir_disk();
+    let mut header = create_file_header(b"inner", 5, 882, 885, &[885]);
+    header[0x148] = 4;
+    header[0x149..0x14D].copy_from_slice(b"note");
+    write_u32_be(&mut header, 0x140, ACC_ARCHIVE);
+    write_u32_be(&mut header, 0x1A4, 7000);
+    write_u32_be(&mut header, 0x1A8, 60);
+    set_checksum(&mut header, 20);
+    device.set_block(884, &header);
+
+    let reader = AffsReader::new(&device).unwrap();
+    let entry = reader.find_path(b"subdir/inner").unwrap();
+    let meta = reader.metadata(b"subdir/inner").unwrap();
+
+    assert_eq!(meta.entry_type, entry.entry_type);
+    assert_eq!(meta.size, entry.size);
+    assert_eq!(meta.size, 5);
+    assert_eq!(meta.access, entry.access);
+    assert!(meta.access.is_archived());
+    assert_eq!(meta.date, entry.date);
+    assert_eq!(meta.date.days, 7000);
+    assert_eq!(meta.block, 884);
+    assert_eq!(meta.name(), b"inner");
+    assert_eq!(meta.comment(), entry.comment());
+    assert_eq!(meta.comment(), b"note");
+    assert!(meta.is_file());
+
+    let dir = reader.metadata(b"subdir").unwrap();
+    assert!(dir.is_dir());
+    assert_eq!(dir.block, 882);
+
+    let root = reader.metadata(b"").unwrap();
+    assert_eq!(root.entry_type, EntryType::Root);
+    assert_eq!(root.block, 880);
+
+    assert!(matches!(
+        reader.metadata(b"missing"),
+        Err(AffsError::EntryNotFound)
+    ));
+}
+
+/// Create an OFS disk with a 10-block (plus 37 bytes) file at block 882, data at 890..=900.
+fn create_large_ofs_disk() -> (MockDevice, u32) {
+    let size = 10 * 488 + 37;
+    let mut device = create_ofs_test_disk();
+
+    let data_blocks: Vec<u32> = (890..=900).collect();
+    device.set_block(
+        882,
+        &create_file_header(b"ofsfile", size, 880, 890, &data_blocks),
+    );
+
+    let bytes: Vec<u8> = (0..size as usize).map(large_file_byte).collect();
+    for (i, chunk) in bytes.chunks(488).enumerate() {
+        let block = 890 + i as u32;
+        let next = if block < 900 { block + 1 } else { 0 };
+        device.set_block(
+            block,
+            &create_ofs_data_block(882, i as u32 + 1, chunk, next),
+        );
+    }
+
+    (device, size)
+}
+
+#[test]
+fn test_read_verified_corrupt_ofs_block() {
+    let (mut device, size) = create_large_ofs_disk();
+    device.get_block_mut(895)[100] ^= 0xFF;
+    let lenient = Options {
+        verify_checksums: false,
+    };
+    let reader = AffsReader::with_options(&device, 1760, lenient).unwrap();
+
+    // A plain lenient read returns the corrupted bytes
+    let mut out = vec![0u8; size as usize];
+    assert_eq!(
+        reader.read_file(882).unwrap().read_all(&mut out).unwrap(),
+        size as usize
+    );
+    assert_ne!(out[5 * 488 + 76], large_file_byte(5 * 488 + 76));
+
+    // The first five blocks are fine, the sixth fails its checksum
+    let mut file = reader.read_file(882).unwrap();
+    assert_eq!(file.read_verified(&mut out[..5 * 488]).unwrap(), 5 * 488);
+    assert!(matches!(
+        file.read_verified(&mut out),
+        Err(AffsError::ChecksumMismatch)
+    ));
+
+    // Sequence numbers are checked too
+    let (mut device, size) = create_large_ofs_disk();
+    let chunk: Vec<u8> = (3 * 488..4 * 488).map(large_file_byte).collect();
+    device.set_block(893, &create_ofs_data_block(882, 9, &chunk, 894));
+    let reader = AffsReader::new(&device).unwrap();
+    let mut out = vec![0u8; size as usize];
+    assert_eq!(
+        reader.read_file(882).unwrap().read_all(&mut out).unwrap(),
+        size as usize
+    );
+    assert!(matches!(
+        reader.read_file(882).unwrap().read_verified(&mut out),
+        Err(AffsError::InvalidDataSequence)
+    ));
+}
+
+#[test]
+fn test_fs_type_mismatch() {
+    let mut buf = [0u8; 64];
+
+    // OFS data read as FFS would return the block header as file data
+    let ofs = create_ofs_test_disk();
+    let mut file = FileReader::new(&ofs, FsType::Ffs, 882).unwrap();
+    assert_eq!(file.read(&mut buf), Err(AffsError::FsTypeMismatch));
+    let mut file = FileReader::new(&ofs, FsType::Ofs, 882).unwrap();
+    assert_eq!(file.read(&mut buf).unwrap(), 50);
+
+    // Also caught when several contiguous blocks are read at once
+    let (large_ofs, _) = create_large_ofs_disk();
+    let mut file = FileReader::new(&large_ofs, FsType::Ffs, 882).unwrap();
+    let mut big = [0u8; 4096];
+    assert_eq!(file.read(&mut big), Err(AffsError::FsTypeMismatch));
+
+    // FFS data read as OFS has no data block header
+    let mut ffs = create_test_disk();
+    let mut file = FileReader::new(&ffs, FsType::Ofs, 882).unwrap();
+    assert_eq!(file.read(&mut buf), Err(AffsError::FsTypeMismatch));
+
+    // Same through a boot block that mislabels the volume
+    ffs.get_block_mut(0)[3] = 0;
+    let reader = AffsReader::new(&ffs).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+    assert!(matches!(file.next_block(), Err(AffsError::FsTypeMismatch)));
+}
+
+#[test]
+fn test_fs_type_ofs_bad_checksum() {
+    // A damaged OFS data block is still OFS data, not a type mismatch
+    let mut device = create_ofs_test_disk();
+    device.get_block_mut(883)[100] ^= 1;
+    let reader = AffsReader::new(&device).unwrap();
+    assert!(reader.verify_fs_type(882).unwrap());
+
+    let mut buf = [0u8; 64];
+    let mut file = reader.read_file(882).unwrap();
+    assert_eq!(file.read(&mut buf), Err(AffsError::ChecksumMismatch));
+
+    // Read as FFS it is still recognized as OFS
+    let mut file = FileReader::new(&device, FsType::Ffs, 882).unwrap();
+    assert_eq!(file.read(&mut buf), Err(AffsError::FsTypeMismatch));
+}
+
+#[test]
+fn test_read_verified_ffs() {
+    let (device, size) = create_large_ffs_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+    let mut out = vec![0u8; size as usize];
+    let mut total = 0;
+    while total < out.len() {
+        total += file.read_verified(&mut out[total..]).unwrap();
+    }
+    assert!(
+        out.iter()
+            .enumerate()
+            .all(|(i, &b)| b == large_file_byte(i))
+    );
+    assert_eq!(file.read_verified(&mut out).unwrap(), 0);
+}
+
+#[test]
+fn test_ofs_chunked_reads_match() {
+    let (device, size) = create_large_ofs_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let expected: Vec<u8> = (0..size as usize).map(large_file_byte).collect();
+
+    for chunk_size in [1, 7, 100, 487, 488, 489, 1000, 8192] {
+        let mut file = reader.read_file(882).unwrap();
+        let mut out = Vec::new();
+        let mut chunk = vec![0u8; chunk_size];
+        loop {
+            let n = file.read(&mut chunk).unwrap();
+            if n == 0 {
+                break;
+            }
+            out.extend_from_slice(&chunk[..n]);
+        }
+        assert_eq!(out, expected, "chunk size {chunk_size}");
+    }
+
+    // Reset and seek still work with the cached header
+    let mut file = reader.read_file(882).unwrap();
+    let mut byte = [0u8; 1];
+    file.seek(3000).unwrap();
+    file.read(&mut byte).unwrap();
+    assert_eq!(byte[0], large_file_byte(3000));
+    file.reset();
+    file.read(&mut byte).unwrap();
+    assert_eq!(byte[0], large_file_byte(0));
+}
+
+#[test]
+fn test_var_reader_buffer_storage() {
+    let file_size = core::mem::size_of::<VarFileReader<'_, MockDevice>>();
+    let dir_size = core::mem::size_of::<VarDirIter<'_, MockDevice>>();
+    if cfg!(feature = "alloc") {
+        // Boxed buffers: only pointers live inline
+        assert!(file_size < 256, "{file_size}");
+        assert!(dir_size < MAX_BLOCK_SIZE / 4, "{dir_size}");
+    } else {
+        assert!(file_size >= 2 * MAX_BLOCK_SIZE);
+        assert!(dir_size >= MAX_BLOCK_SIZE);
+    }
+
+    // Reading behaves the same with either storage
+    let (device, content) = create_var_file_disk();
+    let reader = AffsReaderVar::new(&device, 64 * 4).unwrap();
+    let mut file = reader.read_file(11).unwrap();
+    let mut buf = vec![0u8; content.len()];
+    assert_eq!(file.read_all(&mut buf).unwrap(), content.len());
+    assert_eq!(buf, content);
+}
+
+#[test]
+fn test_file_next_block() {
+    let (device, size) = create_large_ffs_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+
+    let expected: u64 = (0..size as usize).map(|i| large_file_byte(i) as u64).sum();
+    let mut sum = 0u64;
+    let mut lens = Vec::new();
+    while let Some(block) = file.next_block().unwrap() {
+        sum += block.iter().map(|&b| b as u64).sum::<u64>();
+        lens.push(block.len());
+    }
+    assert_eq!(sum, expected);
+    assert_eq!(lens.len(), 74);
+    assert!(lens[..73].iter().all(|&len| len == 512));
+    assert_eq!(lens[73], 100);
+    assert!(file.next_block().unwrap().is_none());
+
+    // After a partial read, the rest of the current block comes first
+    file.reset();
+    let mut head = [0u8; 10];
+    file.read(&mut head).unwrap();
+    let rest = file.next_block().unwrap().unwrap();
+    assert_eq!(rest.len(), 502);
+    assert_eq!(rest[0], large_file_byte(10));
+}
+
+#[test]
+fn test_file_next_block_ofs() {
+    let (device, size) = create_large_ofs_disk();
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+
+    let mut out = Vec::new();
+    while let Some(block) = file.next_block().unwrap() {
+        assert!(block.len() <= 488);
+        out.extend_from_slice(block);
+    }
+    let expected: Vec<u8> = (0..size as usize).map(large_file_byte).collect();
+    assert_eq!(out, expected);
+}
+
+/// Device wrapper that counts `read_block` and `read_blocks` calls.
+struct CountingDevice {
+    inner: MockDevice,
+    single: std::cell::Cell<u32>,
+    multi: std::cell::RefCell<Vec<(u32, usize)>>,
+}
+
+impl CountingDevice {
+    fn new(inner: MockDevice) -> Self {
+        Self {
+            inner,
+            single: std::cell::Cell::new(0),
+            multi: std::cell::RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl BlockDevice for CountingDevice {
+    fn read_block(&self, block: u32, buf: &mut [u8; 512]) -> Result<(), ()> {
+        self.single.set(self.single.get() + 1);
+        self.inner.read_block(block, buf)
+    }
+
+    fn read_blocks(&self, start: u32, bufs: &mut [[u8; 512]]) -> Result<(), ()> {
+        self.multi.borrow_mut().push((start, bufs.len()));
+        for (i, buf) in bufs.iter_mut().enumerate() {
+            self.inner.read_block(start + i as u32, buf)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_file_read_uses_read_blocks() {
+    let (device, _) = create_large_ffs_disk();
+    let device = CountingDevice::new(device);
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+    let before = device.single.get();
+
+    // Eight contiguous blocks (890..=897) in one call
+    let mut buf = vec![0u8; 8 * 512];
+    assert_eq!(file.read(&mut buf).unwrap(), 8 * 512);
+    assert_eq!(*device.multi.borrow(), vec![(890, 8)]);
+    assert_eq!(device.single.get(), before);
+    assert!(
+        buf.iter()
+            .enumerate()
+            .all(|(i, &b)| b == large_file_byte(i))
+    );
+
+    // Mixed partial and whole-block reads still produce the right bytes
+    let mut small = [0u8; 100];
+    assert_eq!(file.read(&mut small).unwrap(), 100);
+    assert_eq!(small[0], large_file_byte(8 * 512));
+    let mut rest = vec![0u8; 100_000];
+    let n = file.read_all(&mut rest).unwrap();
+    assert_eq!(n, 73 * 512 + 100 - 8 * 512 - 100);
+    assert!(
+        rest[..n]
+            .iter()
+            .enumerate()
+            .all(|(i, &b)| b == large_file_byte(8 * 512 + 100 + i))
+    );
+}
+
+/// FFS disk with a file "multi" at 882 spanning `ext_count` extension blocks.
+///
+/// The header and each extension (at 883, 884, ...) hold 72 data blocks,
+/// starting at block 1000, and the last extension holds 10 more.
+fn create_multi_ext_ffs_disk(ext_count: u32) -> (MockDevice, u32) {
+    let data_count = 72 * ext_count + 10;
+    let size = data_count * 512;
+    let mut device = create_test_disk();
+
+    let mut root = create_root_block(b"MultiExt");
+    write_u32_be(&mut root, 24 + hash_name(b"multi", false) * 4, 882);
+    set_checksum(&mut root, 20);
+    device.set_block(880, &root);
+
+    let data: Vec<u32> = (1000..1000 + data_count).collect();
+    let mut header = create_file_header(b"multi", size, 880, 1000, &data[..72]);
+    write_u32_be(&mut header, 0x1F8, 883);
+    set_checksum(&mut header, 20);
+    device.set_block(882, &header);
+    for ext in 0..ext_count {
+        let start = 72 * (ext as usize + 1);
+        let end = (start + 72).min(data.len());
+        let next = if ext + 1 < ext_count { 884 + ext } else { 0 };
+        device.set_block(
+            883 + ext,
+            &create_file_ext_block(882, &data[start..end], next),
+        );
+    }
+    for (i, &block) in data.iter().enumerate() {
+        let mut buf = [0u8; 512];
+        for (j, byte) in buf.iter_mut().enumerate() {
+            *byte = large_file_byte(i * 512 + j);
+        }
+        device.set_block(block, &buf);
+    }
+
+    (device, size)
+}
+
+#[test]
+fn test_seek_uses_extension_index() {
+    let (device, size) = create_multi_ext_ffs_disk(3);
+    let device = CountingDevice::new(device);
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+    let mut out = vec![0u8; size as usize];
+    assert_eq!(file.read_all(&mut out).unwrap(), size as usize);
+
+    // Back into the third extension table: one extension and one data block
+    let target = (72 * 3 + 5) * 512 + 7;
+    let before = device.single.get();
+    file.seek(target).unwrap();
+    assert_eq!(device.single.get() - before, 2);
+    let mut buf = [0u8; 600];
+    assert_eq!(file.read(&mut buf).unwrap(), 600);
+    assert!(
+        buf.iter()
+            .enumerate()
+            .all(|(i, &b)| b == large_file_byte(target as usize + i))
+    );
+
+    // Forward past a remembered extension, then back to the header table
+    file.seek(72 * 512).unwrap();
+    file.seek(100).unwrap();
+    assert_eq!(file.position(), 100);
+    file.read(&mut buf).unwrap();
+    assert_eq!(buf[0], large_file_byte(100));
+
+    let before = device.single.get();
+    file.seek(size - 1).unwrap();
+    assert_eq!(device.single.get() - before, 2);
+    assert_eq!(file.read(&mut buf).unwrap(), 1);
+    assert_eq!(buf[0], large_file_byte(size as usize - 1));
+    file.seek(size).unwrap();
+    assert!(file.is_eof());
+}
+
+#[test]
+fn test_file_read_blocks_not_contiguous() {
+    let mut device = create_test_disk();
+    // Two data blocks that are not adjacent on disk
+    device.set_block(
+        882,
+        &create_file_header(b"testfile", 1024, 880, 890, &[890, 892]),
+    );
+    device.set_block(890, &[1u8; 512]);
+    device.set_block(892, &[2u8; 512]);
+    let device = CountingDevice::new(device);
+
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+    let mut buf = [0u8; 1024];
+    assert_eq!(file.read(&mut buf).unwrap(), 1024);
+    assert!(device.multi.borrow().is_empty());
+    assert!(buf[..512].iter().all(|&b| b == 1));
+    assert!(buf[512..].iter().all(|&b| b == 2));
+}
+
+#[test]
+fn test_dyn_block_device() {
+    let floppy = create_test_disk();
+    let (large, size) = create_large_ffs_disk();
+    let counting = CountingDevice::new(create_test_disk());
+
+    let readers: Vec<DynAffsReader<'_>> = vec![
+        AffsReader::new_dyn(&floppy).unwrap(),
+        AffsReader::new_dyn(&large).unwrap(),
+        AffsReader::new(&counting as &dyn BlockDevice).unwrap(),
+    ];
+    assert_eq!(readers[0].find_path(b"testfile").unwrap().size, 100);
+    assert_eq!(readers[1].find_path(b"large").unwrap().size, size);
+
+    let mut buf = [0u8; 100];
+    let mut file = readers[2].read_file(882).unwrap();
+    assert_eq!(file.read(&mut buf).unwrap(), 100);
+    assert!(counting.single.get() > 0);
+
+    let boxed: Box<dyn BlockDevice> = Box::new(create_test_disk());
+    let reader = AffsReader::new(&*boxed).unwrap();
+    assert_eq!(reader.read_dir(880).unwrap().count(), 1);
+}
+
+#[test]
+fn test_read_blocks_default() {
+    let device = create_test_disk();
+    let mut bufs = [[0u8; 512]; 2];
+    device.read_blocks(882, &mut bufs).unwrap();
+    assert_eq!(bufs[0], device.blocks[882]);
+    assert_eq!(bufs[1], device.blocks[883]);
+    assert!(device.read_blocks(1759, &mut bufs).is_err());
+}
+
+/// Device wrapper that records `prefetch` hints.
+struct PrefetchDevice {
+    inner: MockDevice,
+    hints: std::cell::RefCell<Vec<u32>>,
+}
+
+impl BlockDevice for PrefetchDevice {
+    fn read_block(&self, block: u32, buf: &mut [u8; 512]) -> Result<(), ()> {
+        self.inner.read_block(block, buf)
+    }
+
+    fn prefetch(&self, block: u32) {
+        self.hints.borrow_mut().push(block);
+    }
+}
+
+#[test]
+fn test_file_read_prefetches_next_block() {
+    let (inner, size) = create_large_ffs_disk();
+    let device = PrefetchDevice {
+        inner,
+        hints: std::cell::RefCell::new(Vec::new()),
+    };
+    let reader = AffsReader::new(&device).unwrap();
+    let mut file = reader.read_file(882).unwrap();
+
+    let mut buf = [0u8; 512];
+    let mut total = 0;
+    loop {
+        let n = file.read(&mut buf).unwrap();
+        if n == 0 {
+            break;
+        }
+        total += n;
+    }
+    assert_eq!(total, size as usize);
+
+    // Each header-table block hints its successor, the last one hints the
+    // extension block, and the final data block hints nothing
+    let mut expected: Vec<u32> = (891..=961).collect();
+    expected.push(883);
+    expected.push(963);
+    assert_eq!(*device.hints.borrow(), expected);
+}
+
+#[cfg(feature = "async")]
+mod async_tests {
+    use super::*;
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+
+    /// Poll a future to completion on the current thread.
+    fn block_on<F: Future>(fut: F) -> F::Output {
+        let mut fut = pin!(fut);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+                return out;
+            }
+        }
+    }
+
+    /// Future that is pending on its first poll.
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = ();
+
+        fn poll(mut self: core::pin::Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                Poll::Ready(())
+            } else {
+                self.0 = true;
+                Poll::Pending
+            }
+        }
+    }
+
+    /// Async wrapper that yields before every read.
+    struct AsyncMock(MockDevice);
+
+    impl AsyncBlockDevice for AsyncMock {
+        async fn read_block(&self, block: u32, buf: &mut [u8; 512]) -> Result<(), ()> {
+            YieldOnce(false).await;
+            self.0.read_block(block, buf)
+        }
+    }
+
+    #[test]
+    fn test_async_open_and_list() {
+        let device = AsyncMock(create_test_disk());
+        block_on(async {
+            let reader = AsyncAffsReader::new(&device).await.unwrap();
+            assert_eq!(reader.fs_type(), FsType::Ffs);
+            assert_eq!(reader.disk_name(), b"TestDisk");
+
+            let mut dir = reader.read_dir(reader.root_block()).await.unwrap();
+            let entry = dir.next().await.unwrap().unwrap();
+            assert_eq!(entry.name(), b"testfile");
+            assert_eq!(entry.size, 100);
+            assert!(dir.next().await.is_none());
+        });
+    }
+
+    #[test]
+    fn test_async_find_and_read_ffs() {
+        let (device, size) = create_large_ffs_disk();
+        let device = AsyncMock(device);
+        block_on(async {
+            let reader = AsyncAffsReader::new(&device).await.unwrap();
+            let entry = reader
+                .find_entry(reader.root_block(), b"LARGE")
+                .await
+                .unwrap();
+            assert_eq!(entry.size, size);
+
+            let mut file = reader.read_file(entry.block).await.unwrap();
+            let mut out = vec![0u8; size as usize];
+            assert_eq!(file.read_all(&mut out).await.unwrap(), size as usize);
+            assert!(file.is_eof());
+            assert!(
+                out.iter()
+                    .enumerate()
+                    .all(|(i, &b)| b == large_file_byte(i))
+            );
+            assert_eq!(file.read(&mut out).await.unwrap(), 0);
+        });
+    }
+
+    #[test]
+    fn test_async_read_ofs_matches_sync() {
+        let sync_device = create_ofs_test_disk();
+        let reader = AffsReader::new(&sync_device).unwrap();
+        let entry = reader.find_entry(reader.root_block(), b"ofsfile").unwrap();
+        let mut expected = [0u8; 50];
+        reader
+            .read_file(entry.block)
+            .unwrap()
+            .read_all(&mut expected)
+            .unwrap();
+
+        let device = AsyncMock(create_ofs_test_disk());
+        block_on(async {
+            let reader = AsyncAffsReader::new(&device).await.unwrap();
+            assert_eq!(reader.fs_type(), FsType::Ofs);
+            let mut file = reader.read_file(entry.block).await.unwrap();
+
+            // Read in small chunks
+            let mut out = [0u8; 50];
+            let mut pos = 0;
+            while pos < out.len() {
+                let end = (pos + 7).min(out.len());
+                pos += file.read(&mut out[pos..end]).await.unwrap();
+            }
+            assert_eq!(out, expected);
+        });
+    }
+
+    #[test]
+    fn test_async_find_entry_skips_rejected() {
+        let device = AsyncMock(create_rejected_chain_disk());
+        block_on(async {
+            let reader = AsyncAffsReader::new(&device).await.unwrap();
+            let entry = reader.find_entry(880, b"TARGET").await.unwrap();
+            assert_eq!(entry.block, 891);
+        });
+    }
+
+    #[test]
+    fn test_async_long_name_layout() {
+        let device = AsyncMock(create_long_name_disk());
+        block_on(async {
+            let reader = AsyncAffsReader::new(&device).await.unwrap();
+            let entry = reader.find_entry(880, b"testfile").await.unwrap();
+            assert_eq!(entry.block, 882);
+            assert_eq!(
+                reader.read_entry(890).await.err(),
+                Some(AffsError::NameTooLong)
+            );
+
+            let mut iter = reader.read_dir(880).await.unwrap();
+            assert_eq!(
+                iter.next().await.unwrap().err(),
+                Some(AffsError::NameTooLong)
+            );
+            assert_eq!(iter.next().await.unwrap().unwrap().name(), b"testfile");
+            assert!(iter.next().await.is_none());
+        });
+    }
+
+    #[test]
+    fn test_async_errors() {
+        let device = AsyncMock(create_test_disk());
+        block_on(async {
+            let reader = AsyncAffsReader::new(&device).await.unwrap();
+            let root = reader.root_block();
+            assert_eq!(
+                reader.find_entry(root, b"missing").await.unwrap_err(),
+                AffsError::EntryNotFound
+            );
+            let entry = reader.find_entry(root, b"TESTFILE").await.unwrap();
+            assert_eq!(
+                reader.read_dir(entry.block).await.err(),
+                Some(AffsError::NotADirectory)
+            );
+            assert_eq!(
+                reader.read_file(root).await.err(),
+                Some(AffsError::NotAFile)
+            );
+        });
+
+        let empty = AsyncMock(MockDevice::new(1));
+        assert_eq!(
+            block_on(AsyncAffsReader::new(&empty)).err(),
+            Some(AffsError::BlockReadError)
+        );
+
+        let mut unformatted = MockDevice::new(1760);
+        let (boot0, boot1) = create_boot_block();
+        unformatted.set_block(0, &boot0);
+        unformatted.set_block(1, &boot1);
+        assert_eq!(
+            block_on(AsyncAffsReader::new(&AsyncMock(unformatted))).err(),
+            Some(AffsError::EmptyVolume)
+        );
+    }
+}